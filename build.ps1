@@ -84,18 +84,18 @@ Invoke-WithEnvironment `
         #
         # Run tests
         #
-        Invoke-CheckExitCode 'Test' { cargo test --locked }
+        Invoke-CheckExitCode 'Test' { cargo test --locked --features rand,hashbrown,encoding,libc }
 
         #
         # Lint and check formatting.
         #
-        Invoke-CheckExitCode 'Clippy' { cargo clippy --locked -- -D warnings }
+        Invoke-CheckExitCode 'Clippy' { cargo clippy --locked --features rand,hashbrown,encoding,libc -- -D warnings }
         Invoke-CheckExitCode 'Check format' { cargo fmt --check }
 
         #
         # Check docs
         #
-        Invoke-CheckExitCode 'Check docs' { cargo doc --locked }
+        Invoke-CheckExitCode 'Check docs' { cargo doc --locked --features rand,hashbrown,encoding,libc }
 
         #
         # Verify that we can build with #[cfg(no_global_oom_handling)] enabled.
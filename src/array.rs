@@ -0,0 +1,79 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::TryReserveError;
+use alloc::vec::Vec;
+use core::mem::ManuallyDrop;
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Fallible allocation equivalents for converting an owned array into a
+/// [`Vec`] (i.e., `Vec::from(array)`).
+pub trait TryIntoVec<T> {
+    /// Moves the items of an array into a new `Vec` that uses the provided
+    /// allocator.
+    ///
+    /// The `Vec` is allocated with a capacity of exactly the array's length,
+    /// and the items are moved with a single copy. If the allocation fails,
+    /// then the array is dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let vec = [1, 2, 3].try_into_vec_in(System)?;
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_into_vec_in<A: Allocator>(self, alloc: A) -> Result<Vec<T, A>, TryReserveError>;
+
+    /// Moves the items of an array into a new `Vec`.
+    ///
+    /// The `Vec` is allocated with a capacity of exactly the array's length,
+    /// and the items are moved with a single copy. If the allocation fails,
+    /// then the array is dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let buffer = [0u8; 16];
+    /// let vec = buffer.try_into_vec()?;
+    /// assert_eq!(vec.len(), 16);
+    /// assert_eq!(vec.capacity(), 16);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_into_vec(self) -> Result<Vec<T>, TryReserveError>;
+}
+
+impl<T, const N: usize> TryIntoVec<T> for [T; N] {
+    #[cfg(feature = "allocator_api")]
+    fn try_into_vec_in<A: Allocator>(self, alloc: A) -> Result<Vec<T, A>, TryReserveError> {
+        let mut vec = Vec::new_in(alloc);
+        vec.try_reserve_exact(N)?;
+        let array = ManuallyDrop::new(self);
+        unsafe {
+            core::ptr::copy_nonoverlapping(array.as_ptr(), vec.as_mut_ptr(), N);
+            vec.set_len(N);
+        }
+        Ok(vec)
+    }
+
+    fn try_into_vec(self) -> Result<Vec<T>, TryReserveError> {
+        let mut vec = Vec::new();
+        vec.try_reserve_exact(N)?;
+        let array = ManuallyDrop::new(self);
+        unsafe {
+            core::ptr::copy_nonoverlapping(array.as_ptr(), vec.as_mut_ptr(), N);
+            vec.set_len(N);
+        }
+        Ok(vec)
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{
+    alloc_error, error::capacity_overflow, FallibleVec, ImplementationDetails, TryReserveError,
+};
+use alloc::{alloc::Layout, boxed::Box, vec::Vec};
+use core::ptr;
+
+#[cfg(feature = "allocator_api")]
+use core::{alloc::Allocator, ptr::NonNull};
+
+/// Appends clones of the items in `slice` to `boxed`, reallocating it to
+/// exactly the new length.
+///
+/// If the reallocation fails, then `boxed` is returned unchanged along with
+/// the error.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then `boxed` and all of the items already
+/// cloned will be dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let boxed: Box<[i32]> = Box::new([1, 2]);
+/// let boxed = try_concat_boxed(boxed, &[3, 4]).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3, 4]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+#[allow(clippy::type_complexity)]
+pub fn try_concat_boxed<T: Clone>(
+    boxed: Box<[T]>,
+    slice: &[T],
+) -> Result<Box<[T]>, (Box<[T]>, TryReserveError)> {
+    let mut vec = try_grow_exact(boxed, slice.len())?;
+    // Cannot fail as the space has already been reserved.
+    let _ = vec.try_extend_from_slice(slice);
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Appends clones of the items in `slice` to `boxed`, reallocating it to
+/// exactly the new length.
+///
+/// If the reallocation fails, then `boxed` is returned unchanged along with
+/// the error.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then `boxed` and all of the items already
+/// cloned will be dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let boxed: Box<[i32]> = Box::new([1, 2]);
+/// let boxed = try_concat_boxed(boxed, &[3, 4]).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3, 4]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+#[allow(clippy::type_complexity)]
+pub fn try_concat_boxed<T: Clone, A: Allocator>(
+    boxed: Box<[T], A>,
+    slice: &[T],
+) -> Result<Box<[T], A>, (Box<[T], A>, TryReserveError)> {
+    let mut vec = try_grow_exact(boxed, slice.len())?;
+    // Cannot fail as the space has already been reserved.
+    let _ = vec.try_extend_from_slice(slice);
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Appends `item` to `boxed`, reallocating it to exactly the new length.
+///
+/// If the reallocation fails, then `boxed` is returned unchanged along with
+/// the error (and `item` is dropped).
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let boxed: Box<[i32]> = Box::new([1, 2]);
+/// let boxed = try_push_boxed(boxed, 3).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+#[allow(clippy::type_complexity)]
+pub fn try_push_boxed<T>(
+    boxed: Box<[T]>,
+    item: T,
+) -> Result<Box<[T]>, (Box<[T]>, TryReserveError)> {
+    let mut vec = try_grow_exact(boxed, 1)?;
+    // Cannot fail as the space has already been reserved.
+    let _ = vec.try_push(item);
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Appends `item` to `boxed`, reallocating it to exactly the new length.
+///
+/// If the reallocation fails, then `boxed` is returned unchanged along with
+/// the error (and `item` is dropped).
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let boxed: Box<[i32]> = Box::new([1, 2]);
+/// let boxed = try_push_boxed(boxed, 3).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+#[allow(clippy::type_complexity)]
+pub fn try_push_boxed<T, A: Allocator>(
+    boxed: Box<[T], A>,
+    item: T,
+) -> Result<Box<[T], A>, (Box<[T], A>, TryReserveError)> {
+    let mut vec = try_grow_exact(boxed, 1)?;
+    // Cannot fail as the space has already been reserved.
+    let _ = vec.try_push(item);
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Converts `vec` into a boxed slice, shrinking its allocation to exactly its
+/// length first if there is any spare capacity.
+///
+/// Unlike `Vec::into_boxed_slice`, a failure to shrink the allocation doesn't
+/// abort: `vec` is returned unchanged along with the error instead.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut vec = try_with_capacity(10)?;
+/// vec.try_extend_from_slice(&[1, 2, 3])?;
+/// let boxed = try_into_boxed_slice(vec).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+#[allow(clippy::type_complexity)]
+pub fn try_into_boxed_slice<T>(mut vec: Vec<T>) -> Result<Box<[T]>, (Vec<T>, TryReserveError)> {
+    if let Err(error) = vec.try_shrink_to_internal(0) {
+        return Err((vec, error));
+    }
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Converts `vec` into a boxed slice, shrinking its allocation to exactly its
+/// length first if there is any spare capacity.
+///
+/// Unlike `Vec::into_boxed_slice`, a failure to shrink the allocation doesn't
+/// abort: `vec` is returned unchanged along with the error instead.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut vec = try_with_capacity(10)?;
+/// vec.try_extend_from_slice(&[1, 2, 3])?;
+/// let boxed = try_into_boxed_slice(vec).map_err(|(_, e)| e)?;
+/// assert_eq!(*boxed, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+#[allow(clippy::type_complexity)]
+pub fn try_into_boxed_slice<T, A: Allocator>(
+    mut vec: Vec<T, A>,
+) -> Result<Box<[T], A>, (Vec<T, A>, TryReserveError)> {
+    if let Err(error) = vec.try_shrink_to_internal(0) {
+        return Err((vec, error));
+    }
+    Ok(unsafe { into_boxed_slice_exact(vec) })
+}
+
+/// Clones the items in `slice` into a new boxed slice.
+///
+/// The boxed slice is allocated with exactly the length of `slice`, so there
+/// is no excess capacity to shrink away afterwards.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the allocation and all of the items
+/// already cloned will be dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let boxed = try_boxed_slice_from(&[1, 2, 3])?;
+/// assert_eq!(*boxed, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_boxed_slice_from<T: Clone>(slice: &[T]) -> Result<Box<[T]>, TryReserveError> {
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(slice.len())?;
+    vec.try_extend_from_slice(slice)?;
+    // Shrinks the allocation only if more than was requested was reserved.
+    try_into_boxed_slice(vec).map_err(|(_, error)| error)
+}
+
+/// Clones the items in `slice` into a new boxed slice that uses the provided
+/// allocator.
+///
+/// The boxed slice is allocated with exactly the length of `slice`, so there
+/// is no excess capacity to shrink away afterwards.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the allocation and all of the items
+/// already cloned will be dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let boxed = try_boxed_slice_from_in(b"abc", System)?;
+/// assert_eq!(*boxed, *b"abc");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_boxed_slice_from_in<T: Clone, A: Allocator>(
+    slice: &[T],
+    alloc: A,
+) -> Result<Box<[T], A>, TryReserveError> {
+    let mut vec = Vec::new_in(alloc);
+    vec.try_reserve_exact(slice.len())?;
+    vec.try_extend_from_slice(slice)?;
+    // Shrinks the allocation only if more than was requested was reserved.
+    try_into_boxed_slice(vec).map_err(|(_, error)| error)
+}
+
+/// Reallocates `boxed` to hold exactly `additional` more items, returning it as
+/// a `Vec` whose capacity is exactly the new length.
+///
+/// This goes directly to the allocator (rather than using
+/// `Vec::try_reserve_exact`, which may reserve more than requested) so that
+/// the result can later be converted back into a boxed slice. If the
+/// reallocation fails, then `boxed` is returned unchanged along with the
+/// error.
+#[cfg(not(feature = "allocator_api"))]
+#[allow(clippy::type_complexity)]
+fn try_grow_exact<T>(
+    boxed: Box<[T]>,
+    additional: usize,
+) -> Result<Vec<T>, (Box<[T]>, TryReserveError)> {
+    let len = boxed.len();
+    let Some(new_layout) = len
+        .checked_add(additional)
+        .and_then(|new_len| Layout::array::<T>(new_len).ok())
+    else {
+        return Err((boxed, capacity_overflow()));
+    };
+    if new_layout.size() == 0 {
+        return Ok(boxed.into_vec());
+    }
+
+    let old_layout = Layout::for_value(&*boxed);
+    let ptr = Box::into_raw(boxed).cast::<T>();
+    let new_ptr = unsafe {
+        if old_layout.size() == 0 {
+            alloc::alloc::alloc(new_layout)
+        } else {
+            alloc::alloc::realloc(ptr.cast(), old_layout, new_layout.size())
+        }
+    };
+    if new_ptr.is_null() {
+        // The original allocation is untouched.
+        let boxed = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)) };
+        return Err((boxed, alloc_error(new_layout)));
+    }
+    Ok(unsafe { Vec::from_raw_parts(new_ptr.cast(), len, len + additional) })
+}
+
+/// Reallocates `boxed` to hold exactly `additional` more items, returning it as
+/// a `Vec` whose capacity is exactly the new length.
+///
+/// This goes directly to the allocator (rather than using
+/// `Vec::try_reserve_exact`, which may reserve more than requested) so that
+/// the result can later be converted back into a boxed slice. If the
+/// reallocation fails, then `boxed` is returned unchanged along with the
+/// error.
+#[cfg(feature = "allocator_api")]
+#[allow(clippy::type_complexity)]
+fn try_grow_exact<T, A: Allocator>(
+    boxed: Box<[T], A>,
+    additional: usize,
+) -> Result<Vec<T, A>, (Box<[T], A>, TryReserveError)> {
+    let len = boxed.len();
+    let Some(new_layout) = len
+        .checked_add(additional)
+        .and_then(|new_len| Layout::array::<T>(new_len).ok())
+    else {
+        return Err((boxed, capacity_overflow()));
+    };
+    if new_layout.size() == 0 {
+        return Ok(boxed.into_vec());
+    }
+
+    let old_layout = Layout::for_value(&*boxed);
+    let (ptr, alloc) = Box::into_raw_with_allocator(boxed);
+    let ptr = ptr.cast::<T>();
+    let result = if old_layout.size() == 0 {
+        alloc.allocate(new_layout)
+    } else {
+        unsafe { alloc.grow(NonNull::new_unchecked(ptr).cast(), old_layout, new_layout) }
+    };
+    match result {
+        Ok(new_ptr) => Ok(unsafe {
+            Vec::from_raw_parts_in(new_ptr.as_ptr().cast(), len, len + additional, alloc)
+        }),
+        Err(_) => {
+            // The original allocation is untouched.
+            let boxed = unsafe { Box::from_raw_in(ptr::slice_from_raw_parts_mut(ptr, len), alloc) };
+            Err((boxed, alloc_error(new_layout)))
+        }
+    }
+}
+
+/// Converts `vec` into a boxed slice without reallocating.
+///
+/// # Safety
+///
+/// `vec` must not have any spare capacity (i.e., its `len` must equal its
+/// `capacity`), unless `T` is zero-sized.
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) unsafe fn into_boxed_slice_exact<T>(vec: Vec<T>) -> Box<[T]> {
+    // A mismatched capacity would free the allocation with the wrong layout.
+    assert!(
+        core::mem::size_of::<T>() == 0 || vec.len() == vec.capacity(),
+        "the Vec must not have any spare capacity"
+    );
+    let mut vec = core::mem::ManuallyDrop::new(vec);
+    let slice = ptr::slice_from_raw_parts_mut(vec.as_mut_ptr(), vec.len());
+    unsafe { Box::from_raw(slice) }
+}
+
+/// Converts `vec` into a boxed slice without reallocating.
+///
+/// # Safety
+///
+/// `vec` must not have any spare capacity (i.e., its `len` must equal its
+/// `capacity`), unless `T` is zero-sized.
+#[cfg(feature = "allocator_api")]
+pub(crate) unsafe fn into_boxed_slice_exact<T, A: Allocator>(vec: Vec<T, A>) -> Box<[T], A> {
+    // A mismatched capacity would free the allocation with the wrong layout.
+    assert!(
+        core::mem::size_of::<T>() == 0 || vec.len() == vec.capacity(),
+        "the Vec must not have any spare capacity"
+    );
+    let mut vec = core::mem::ManuallyDrop::new(vec);
+    let slice = ptr::slice_from_raw_parts_mut(vec.as_mut_ptr(), vec.len());
+    unsafe {
+        let alloc = ptr::read(vec.allocator());
+        Box::from_raw_in(slice, alloc)
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{TryReserveError, TryToVec};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+macro_rules! put_methods {
+    { $($(#[doc = $doc:expr])+ fn $name:ident($ty:ty) => $to_bytes:ident;)+ } => {
+        $(
+            $(#[doc = $doc])+
+            fn $name(&mut self, n: $ty) -> Result<(), TryReserveError> {
+                self.try_put_slice(&n.$to_bytes())
+            }
+        )+
+    };
+}
+
+/// Fallible allocation methods for writing numbers into a byte [`Vec`].
+///
+/// Following the conventions of the `bytes` crate, methods without a suffix
+/// write in big-endian (network) byte order, methods ending in `_le` write in
+/// little-endian byte order and methods ending in `_ne` write in the native
+/// byte order of the target platform.
+///
+/// Each method makes a single fallible reservation and then copies the bytes
+/// directly into the `Vec`.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut buf = Vec::new();
+/// buf.try_put_u8(1)?;
+/// buf.try_put_u16(0x0203)?;
+/// buf.try_put_u32_le(0x07060504)?;
+/// buf.try_put_slice(b"hi")?;
+/// assert_eq!(buf, [1, 2, 3, 4, 5, 6, 7, b'h', b'i']);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub trait FallibleByteVec {
+    /// Appends all of the bytes in `src` to the `Vec`.
+    fn try_put_slice(&mut self, src: &[u8]) -> Result<(), TryReserveError>;
+
+    /// Appends the [`Display`] representation of `value` to the `Vec`.
+    ///
+    /// The value is formatted directly into the `Vec`'s buffer, growing it
+    /// fallibly as needed, so no intermediate `String` is allocated. If an
+    /// allocation fails, the `Vec` is restored to its original length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Display` implementation of `value` returns an error
+    /// that was not caused by a failed allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut line = Vec::new();
+    /// line.try_put_slice(b"answer=")?;
+    /// line.try_extend_from_display(42)?;
+    /// assert_eq!(line, b"answer=42");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_display(&mut self, value: impl Display) -> Result<(), TryReserveError>;
+
+    put_methods! {
+        /// Appends a `u8` to the `Vec`.
+        fn try_put_u8(u8) => to_ne_bytes;
+        /// Appends an `i8` to the `Vec`.
+        fn try_put_i8(i8) => to_ne_bytes;
+        /// Appends a `u16` to the `Vec` in big-endian byte order.
+        fn try_put_u16(u16) => to_be_bytes;
+        /// Appends a `u16` to the `Vec` in little-endian byte order.
+        fn try_put_u16_le(u16) => to_le_bytes;
+        /// Appends a `u16` to the `Vec` in native-endian byte order.
+        fn try_put_u16_ne(u16) => to_ne_bytes;
+        /// Appends an `i16` to the `Vec` in big-endian byte order.
+        fn try_put_i16(i16) => to_be_bytes;
+        /// Appends an `i16` to the `Vec` in little-endian byte order.
+        fn try_put_i16_le(i16) => to_le_bytes;
+        /// Appends an `i16` to the `Vec` in native-endian byte order.
+        fn try_put_i16_ne(i16) => to_ne_bytes;
+        /// Appends a `u32` to the `Vec` in big-endian byte order.
+        fn try_put_u32(u32) => to_be_bytes;
+        /// Appends a `u32` to the `Vec` in little-endian byte order.
+        fn try_put_u32_le(u32) => to_le_bytes;
+        /// Appends a `u32` to the `Vec` in native-endian byte order.
+        fn try_put_u32_ne(u32) => to_ne_bytes;
+        /// Appends an `i32` to the `Vec` in big-endian byte order.
+        fn try_put_i32(i32) => to_be_bytes;
+        /// Appends an `i32` to the `Vec` in little-endian byte order.
+        fn try_put_i32_le(i32) => to_le_bytes;
+        /// Appends an `i32` to the `Vec` in native-endian byte order.
+        fn try_put_i32_ne(i32) => to_ne_bytes;
+        /// Appends a `u64` to the `Vec` in big-endian byte order.
+        fn try_put_u64(u64) => to_be_bytes;
+        /// Appends a `u64` to the `Vec` in little-endian byte order.
+        fn try_put_u64_le(u64) => to_le_bytes;
+        /// Appends a `u64` to the `Vec` in native-endian byte order.
+        fn try_put_u64_ne(u64) => to_ne_bytes;
+        /// Appends an `i64` to the `Vec` in big-endian byte order.
+        fn try_put_i64(i64) => to_be_bytes;
+        /// Appends an `i64` to the `Vec` in little-endian byte order.
+        fn try_put_i64_le(i64) => to_le_bytes;
+        /// Appends an `i64` to the `Vec` in native-endian byte order.
+        fn try_put_i64_ne(i64) => to_ne_bytes;
+        /// Appends a `u128` to the `Vec` in big-endian byte order.
+        fn try_put_u128(u128) => to_be_bytes;
+        /// Appends a `u128` to the `Vec` in little-endian byte order.
+        fn try_put_u128_le(u128) => to_le_bytes;
+        /// Appends a `u128` to the `Vec` in native-endian byte order.
+        fn try_put_u128_ne(u128) => to_ne_bytes;
+        /// Appends an `i128` to the `Vec` in big-endian byte order.
+        fn try_put_i128(i128) => to_be_bytes;
+        /// Appends an `i128` to the `Vec` in little-endian byte order.
+        fn try_put_i128_le(i128) => to_le_bytes;
+        /// Appends an `i128` to the `Vec` in native-endian byte order.
+        fn try_put_i128_ne(i128) => to_ne_bytes;
+        /// Appends a `f32` to the `Vec` in big-endian byte order.
+        fn try_put_f32(f32) => to_be_bytes;
+        /// Appends a `f32` to the `Vec` in little-endian byte order.
+        fn try_put_f32_le(f32) => to_le_bytes;
+        /// Appends a `f32` to the `Vec` in native-endian byte order.
+        fn try_put_f32_ne(f32) => to_ne_bytes;
+        /// Appends a `f64` to the `Vec` in big-endian byte order.
+        fn try_put_f64(f64) => to_be_bytes;
+        /// Appends a `f64` to the `Vec` in little-endian byte order.
+        fn try_put_f64_le(f64) => to_le_bytes;
+        /// Appends a `f64` to the `Vec` in native-endian byte order.
+        fn try_put_f64_ne(f64) => to_ne_bytes;
+    }
+}
+
+macro_rules! impl_for_byte_vec {
+    { $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl FallibleByteVec for Vec<u8> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<A: Allocator> FallibleByteVec for Vec<u8, A> $impl
+    }
+}
+
+impl_for_byte_vec! {
+    {
+        fn try_put_slice(&mut self, src: &[u8]) -> Result<(), TryReserveError> {
+            self.try_reserve(src.len())?;
+            unsafe {
+                let len = self.len();
+                core::ptr::copy_nonoverlapping(src.as_ptr(), self.as_mut_ptr().add(len), src.len());
+                self.set_len(len + src.len());
+            }
+            Ok(())
+        }
+
+        fn try_extend_from_display(&mut self, value: impl Display) -> Result<(), TryReserveError> {
+            let original_len = self.len();
+            let result = write_display(self, value);
+            if result.is_err() {
+                self.truncate(original_len);
+            }
+            result
+        }
+    }
+}
+
+/// Fallible allocation methods for byte slices.
+pub trait FallibleByteSlice {
+    /// Copies the slice into a new `Vec`, with each ASCII lowercase letter
+    /// converted to uppercase (i.e., the fallible equivalent of
+    /// `[u8]::to_ascii_uppercase`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// assert_eq!(b"Content-Type".try_to_ascii_uppercase_vec()?, b"CONTENT-TYPE");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_to_ascii_uppercase_vec(&self) -> Result<Vec<u8>, TryReserveError>;
+
+    /// Copies the slice into a new `Vec`, with each ASCII uppercase letter
+    /// converted to lowercase (i.e., the fallible equivalent of
+    /// `[u8]::to_ascii_lowercase`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// assert_eq!(b"Content-Type".try_to_ascii_lowercase_vec()?, b"content-type");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_to_ascii_lowercase_vec(&self) -> Result<Vec<u8>, TryReserveError>;
+}
+
+impl FallibleByteSlice for [u8] {
+    fn try_to_ascii_uppercase_vec(&self) -> Result<Vec<u8>, TryReserveError> {
+        let mut vec = self.try_copy_to_vec()?;
+        vec.make_ascii_uppercase();
+        Ok(vec)
+    }
+
+    fn try_to_ascii_lowercase_vec(&self) -> Result<Vec<u8>, TryReserveError> {
+        let mut vec = self.try_copy_to_vec()?;
+        vec.make_ascii_lowercase();
+        Ok(vec)
+    }
+}
+
+/// Adapts a [`FallibleByteVec`] to [`fmt::Write`], remembering the
+/// allocation error that caused a write to fail.
+struct DisplayWriter<'a, V: ?Sized> {
+    buf: &'a mut V,
+    error: Option<TryReserveError>,
+}
+
+impl<V: FallibleByteVec + ?Sized> fmt::Write for DisplayWriter<'_, V> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.buf.try_put_slice(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Formats `value` directly into `buf`, without an intermediate `String`.
+///
+/// On failure `buf` may contain part of the formatted output: callers are
+/// responsible for rolling it back.
+pub(crate) fn write_display<V: FallibleByteVec + ?Sized>(
+    buf: &mut V,
+    value: impl Display,
+) -> Result<(), TryReserveError> {
+    let mut writer = DisplayWriter { buf, error: None };
+    match fmt::write(&mut writer, format_args!("{}", value)) {
+        Ok(()) => Ok(()),
+        Err(_) => match writer.error {
+            Some(error) => Err(error),
+            // Matches the behavior of `format!` and `ToString`.
+            None => panic!("a Display implementation returned an error unexpectedly"),
+        },
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, ptr};
+
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Fallible allocation equivalent of splitting an iterator into chunks.
+pub trait TryChunks: Iterator + Sized {
+    /// Creates an iterator that groups the items of this iterator into
+    /// vectors of `chunk_size` items (the last chunk may be shorter).
+    ///
+    /// Chunks are allocated fallibly and lazily, one at a time as they are
+    /// requested, so the whole iterator is never materialized at once. If
+    /// allocating a chunk fails, then the error is yielded instead of the
+    /// chunk (the items already taken for that chunk are dropped) and the
+    /// iterator then ends, so a consumer can stop cleanly on the first
+    /// failure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut chunks = (1..=5).try_chunks(2);
+    /// assert_eq!(chunks.next(), Some(Ok(vec![1, 2])));
+    /// assert_eq!(chunks.next(), Some(Ok(vec![3, 4])));
+    /// assert_eq!(chunks.next(), Some(Ok(vec![5])));
+    /// assert_eq!(chunks.next(), None);
+    /// ```
+    fn try_chunks(self, chunk_size: usize) -> TryChunksIter<Self>;
+
+    /// Creates an iterator that groups the items of this iterator into
+    /// vectors of `chunk_size` items (the last chunk may be shorter), where
+    /// each vector uses a clone of the provided allocator.
+    ///
+    /// See [`try_chunks`](TryChunks::try_chunks) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// for chunk in (0..10).try_chunks_in(4, System) {
+    ///     let chunk = chunk?;
+    ///     assert!(chunk.len() <= 4);
+    /// }
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_chunks_in<A: Allocator + Clone>(
+        self,
+        chunk_size: usize,
+        alloc: A,
+    ) -> TryChunksIter<Self, A>;
+}
+
+impl<I: Iterator> TryChunks for I {
+    fn try_chunks(self, chunk_size: usize) -> TryChunksIter<Self> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        TryChunksIter {
+            iter: Some(self),
+            chunk_size,
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+        }
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn try_chunks_in<A: Allocator + Clone>(
+        self,
+        chunk_size: usize,
+        alloc: A,
+    ) -> TryChunksIter<Self, A> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        TryChunksIter {
+            iter: Some(self),
+            chunk_size,
+            alloc,
+        }
+    }
+}
+
+/// An iterator over chunks of another iterator's items, allocated fallibly.
+///
+/// This `struct` is created by [`TryChunks::try_chunks`]. See its
+/// documentation for more.
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Clone, Debug)]
+pub struct TryChunksIter<I> {
+    /// The source iterator, or `None` once a chunk has failed to allocate.
+    iter: Option<I>,
+    chunk_size: usize,
+}
+
+/// An iterator over chunks of another iterator's items, allocated fallibly.
+///
+/// This `struct` is created by [`TryChunks::try_chunks`] and
+/// [`TryChunks::try_chunks_in`]. See their documentation for more.
+#[cfg(feature = "allocator_api")]
+#[derive(Clone, Debug)]
+pub struct TryChunksIter<I, A: Allocator + Clone = Global> {
+    /// The source iterator, or `None` once a chunk has failed to allocate.
+    iter: Option<I>,
+    chunk_size: usize,
+    alloc: A,
+}
+
+macro_rules! impl_try_chunks_iter {
+    { impl $trait:ident for TryChunksIter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<I: Iterator> $trait for TryChunksIter<I> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<I: Iterator, A: Allocator + Clone> $trait for TryChunksIter<I, A> $impl
+    }
+}
+
+impl_try_chunks_iter! {
+    impl Iterator for TryChunksIter {
+        #[cfg(not(feature = "allocator_api"))]
+        type Item = Result<Vec<I::Item>, TryReserveError>;
+        #[cfg(feature = "allocator_api")]
+        type Item = Result<Vec<I::Item, A>, TryReserveError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let iter = self.iter.as_mut()?;
+            // Don't allocate until the iterator has produced an item, so that
+            // the end of the iterator never causes an allocation.
+            let Some(first) = iter.next() else {
+                self.iter = None;
+                return None;
+            };
+
+            #[cfg(not(feature = "allocator_api"))]
+            let mut chunk = Vec::new();
+            #[cfg(feature = "allocator_api")]
+            let mut chunk = Vec::new_in(self.alloc.clone());
+
+            // Trust the lower bound only up to the chunk size.
+            let (lower_bound, _upper_bound) = iter.size_hint();
+            let result = chunk
+                .try_reserve_exact(lower_bound.saturating_add(1).min(self.chunk_size))
+                .and_then(|()| chunk.try_push(first))
+                .and_then(|()| chunk.try_extend(iter.take(self.chunk_size - 1)));
+            if let Err(error) = result {
+                self.iter = None;
+                return Some(Err(error));
+            }
+            Some(Ok(chunk))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            match &self.iter {
+                Some(iter) => {
+                    let (lower, upper) = iter.size_hint();
+                    let chunks = |len: usize| match len {
+                        0 => 0,
+                        len => (len - 1) / self.chunk_size + 1,
+                    };
+                    // The first chunk may fail to allocate, ending the iterator.
+                    (chunks(lower).min(1), upper.map(chunks))
+                }
+                None => (0, Some(0)),
+            }
+        }
+    }
+}
+
+impl_try_chunks_iter! {
+    impl FusedIterator for TryChunksIter {}
+}
+
+/// Splits an owned [`Vec`] into owned chunks.
+#[cfg(not(feature = "allocator_api"))]
+pub trait TryIntoChunks<T>: Sized {
+    /// Splits the `Vec` into owned chunks of `chunk_len` items (the last chunk
+    /// may be shorter).
+    ///
+    /// All of the chunks are allocated (each with a capacity of exactly its
+    /// length) before any items are moved, so if an allocation fails then the
+    /// `Vec` is returned unchanged along with the error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let chunks = try_vec![1, 2, 3, 4, 5]?
+    ///     .try_into_chunks(2)
+    ///     .map_err(|(_, e)| e)?;
+    /// assert_eq!(chunks, [&[1, 2][..], &[3, 4], &[5]]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[allow(clippy::type_complexity)]
+    fn try_into_chunks(self, chunk_len: usize) -> Result<Vec<Vec<T>>, (Self, TryReserveError)>;
+}
+
+/// Splits an owned [`Vec`] into owned chunks.
+#[cfg(feature = "allocator_api")]
+pub trait TryIntoChunks<T, A: Allocator + Clone>: Sized {
+    /// Splits the `Vec` into owned chunks of `chunk_len` items (the last chunk
+    /// may be shorter), which each use a clone of the allocator of the `Vec`.
+    ///
+    /// All of the chunks are allocated (each with a capacity of exactly its
+    /// length) before any items are moved, so if an allocation fails then the
+    /// `Vec` is returned unchanged along with the error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let chunks = try_vec![1, 2, 3, 4, 5]?
+    ///     .try_into_chunks(2)
+    ///     .map_err(|(_, e)| e)?;
+    /// assert_eq!(chunks, [&[1, 2][..], &[3, 4], &[5]]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[allow(clippy::type_complexity)]
+    fn try_into_chunks(
+        self,
+        chunk_len: usize,
+    ) -> Result<Vec<Vec<T, A>, A>, (Self, TryReserveError)>;
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T> TryIntoChunks<T> for Vec<T> {
+    fn try_into_chunks(mut self, chunk_len: usize) -> Result<Vec<Vec<T>>, (Self, TryReserveError)> {
+        assert!(chunk_len != 0, "chunk size must be non-zero");
+        let len = self.len();
+        let chunk_count = match len {
+            0 => 0,
+            len => (len - 1) / chunk_len + 1,
+        };
+
+        let mut chunks = Vec::new();
+        if let Err(error) = chunks.try_reserve_exact(chunk_count) {
+            return Err((self, error));
+        }
+        for index in 0..chunk_count {
+            let mut chunk = Vec::new();
+            if let Err(error) = chunk.try_reserve_exact(chunk_len.min(len - index * chunk_len)) {
+                return Err((self, error));
+            }
+            // Cannot fail as the space has already been reserved.
+            let _ = chunks.try_push(chunk);
+        }
+
+        // Nothing can fail or panic from here on, so move the items over.
+        unsafe {
+            self.set_len(0);
+            let ptr = self.as_ptr();
+            for (index, chunk) in chunks.iter_mut().enumerate() {
+                let start = index * chunk_len;
+                let this_len = chunk_len.min(len - start);
+                ptr::copy_nonoverlapping(ptr.add(start), chunk.as_mut_ptr(), this_len);
+                chunk.set_len(this_len);
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator + Clone> TryIntoChunks<T, A> for Vec<T, A> {
+    fn try_into_chunks(
+        mut self,
+        chunk_len: usize,
+    ) -> Result<Vec<Vec<T, A>, A>, (Self, TryReserveError)> {
+        assert!(chunk_len != 0, "chunk size must be non-zero");
+        let len = self.len();
+        let chunk_count = match len {
+            0 => 0,
+            len => (len - 1) / chunk_len + 1,
+        };
+
+        let mut chunks = Vec::new_in(self.allocator().clone());
+        if let Err(error) = chunks.try_reserve_exact(chunk_count) {
+            return Err((self, error));
+        }
+        for index in 0..chunk_count {
+            let mut chunk = Vec::new_in(self.allocator().clone());
+            if let Err(error) = chunk.try_reserve_exact(chunk_len.min(len - index * chunk_len)) {
+                return Err((self, error));
+            }
+            // Cannot fail as the space has already been reserved.
+            let _ = chunks.try_push(chunk);
+        }
+
+        // Nothing can fail or panic from here on, so move the items over.
+        unsafe {
+            self.set_len(0);
+            let ptr = self.as_ptr();
+            for (index, chunk) in chunks.iter_mut().enumerate() {
+                let start = index * chunk_len;
+                let this_len = chunk_len.min(len - start);
+                ptr::copy_nonoverlapping(ptr.add(start), chunk.as_mut_ptr(), this_len);
+                chunk.set_len(this_len);
+            }
+        }
+        Ok(chunks)
+    }
+}
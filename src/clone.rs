@@ -0,0 +1,122 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{CloneAllocator, FallibleVec, TryReserveError, TryToVec};
+use alloc::{string::String, vec::Vec};
+use core::borrow::Borrow;
+
+#[cfg(not(no_global_oom_handling))]
+use alloc::ffi::CString;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+#[cfg(not(no_global_oom_handling))]
+use core::ffi::CStr;
+
+/// Fallible allocation equivalent of [`Clone`].
+pub trait TryClone: Sized {
+    /// Returns a copy of the value, or an error if allocating the copy fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let vec = try_vec![1, 2, 3]?;
+    /// let clone = vec.try_clone()?;
+    /// assert_eq!(clone, vec);
+    /// assert_eq!(clone.capacity(), 3);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_clone(&self) -> Result<Self, TryReserveError>;
+}
+
+/// Clones the `Vec` into a new `Vec` with exactly the required capacity (that
+/// uses a clone of its allocator).
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the new `Vec` and any items already
+/// cloned into it are dropped.
+#[cfg(not(feature = "allocator_api"))]
+impl<T: Clone> TryClone for Vec<T> {
+    fn try_clone(&self) -> Result<Self, TryReserveError> {
+        let mut clone = self.new_with_cloned_allocator();
+        clone.try_reserve_exact(self.len())?;
+        clone.try_extend_from_slice(self)?;
+        Ok(clone)
+    }
+}
+
+/// Clones the `Vec` into a new `Vec` with exactly the required capacity (that
+/// uses a clone of its allocator).
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the new `Vec` and any items already
+/// cloned into it are dropped.
+#[cfg(feature = "allocator_api")]
+impl<T: Clone, A: Allocator + Clone> TryClone for Vec<T, A> {
+    fn try_clone(&self) -> Result<Self, TryReserveError> {
+        let mut clone = self.new_with_cloned_allocator();
+        clone.try_reserve_exact(self.len())?;
+        clone.try_extend_from_slice(self)?;
+        Ok(clone)
+    }
+}
+
+/// Fallible allocation equivalent of [`ToOwned`](alloc::borrow::ToOwned).
+pub trait TryToOwned {
+    /// The owned type that is created.
+    type Owned: Borrow<Self>;
+
+    /// Creates owned data from borrowed data, or returns an error if
+    /// allocating the owned data fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let owned: String = "hello".try_to_owned()?;
+    /// assert_eq!(owned, "hello");
+    /// let owned: Vec<u8> = b"world"[..].try_to_owned()?;
+    /// assert_eq!(owned, b"world");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_to_owned(&self) -> Result<Self::Owned, TryReserveError>;
+}
+
+impl<T: Clone> TryToOwned for [T] {
+    type Owned = Vec<T>;
+
+    fn try_to_owned(&self) -> Result<Vec<T>, TryReserveError> {
+        self.try_to_vec()
+    }
+}
+
+impl TryToOwned for str {
+    type Owned = String;
+
+    fn try_to_owned(&self) -> Result<String, TryReserveError> {
+        let bytes = self.as_bytes().try_copy_to_vec()?;
+        // SAFETY: The bytes were copied from a `str`, so they are valid UTF-8.
+        Ok(unsafe { String::from_utf8_unchecked(bytes) })
+    }
+}
+
+// The standard library doesn't provide `CString` with
+// `#[cfg(no_global_oom_handling)]`.
+#[cfg(not(no_global_oom_handling))]
+impl TryToOwned for CStr {
+    type Owned = CString;
+
+    fn try_to_owned(&self) -> Result<CString, TryReserveError> {
+        let bytes = self.to_bytes_with_nul().try_copy_to_vec()?;
+        // SAFETY: The bytes were copied from a `CStr`, so they end with the
+        // only nul byte.
+        Ok(unsafe { CString::from_vec_with_nul_unchecked(bytes) })
+    }
+}
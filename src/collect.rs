@@ -1,9 +1,21 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
+use crate::sort::try_merge_sort;
 use crate::FallibleVec;
+use crate::TryExtend;
 use crate::TryReserveError;
 use alloc::vec::Vec;
+use core::{cmp::Ordering, ptr};
+
+#[cfg(feature = "hashbrown")]
+use crate::hash_map::try_reserve;
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "hashbrown")]
+use hashbrown::HashMap;
+#[cfg(feature = "rand")]
+use rand_core::RngCore;
 
 #[cfg(feature = "allocator_api")]
 use core::alloc::Allocator;
@@ -44,6 +56,236 @@ pub trait TryCollect<T> {
     /// # Ok::<(), std::collections::TryReserveError>(())
     /// ```
     fn try_collect(self) -> Result<Vec<T>, TryReserveError>;
+
+    /// Attempts to add the items from an iterator to an existing collection,
+    /// returning a mutable reference to the collection.
+    ///
+    /// This is the fallible equivalent of the unstable `Iterator::collect_into`,
+    /// and works with any collection that implements [`TryExtend`], so reusable
+    /// buffers (such as a `String` or a `VecDeque`) can be filled without
+    /// allocating a new collection. If an allocation fails, then the items that
+    /// were added before the failure remain in the collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = String::new();
+    /// "hello".chars().rev().try_collect_into(&mut buffer)?;
+    /// assert_eq!(buffer, "olleh");
+    ///
+    /// let mut vec = try_vec![1]?;
+    /// assert_eq!([2, 3].try_collect_into(&mut vec)?.len(), 3);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_collect_into<C: TryExtend<T> + ?Sized>(
+        self,
+        collection: &mut C,
+    ) -> Result<&mut C, TryReserveError>;
+
+    /// Attempts to collect items from an iterator into a vector, and then sorts
+    /// the vector.
+    ///
+    /// The sort is stable (i.e., does not reorder equal elements) and allocates
+    /// its scratch space fallibly: if either collecting the items or allocating
+    /// the scratch space fails, then the collected items are dropped and the
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let vec = [5, 4, 1, 3, 2].try_collect_sorted()?;
+    /// assert_eq!(vec, [1, 2, 3, 4, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_collect_sorted(self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Ord;
+
+    /// Attempts to collect items from an iterator into a vector, and then sorts
+    /// the vector with a comparator function.
+    ///
+    /// The sort is stable (i.e., does not reorder equal elements) and allocates
+    /// its scratch space fallibly: if either collecting the items or allocating
+    /// the scratch space fails, then the collected items are dropped and the
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let vec = [5, 4, 1, 3, 2].try_collect_sorted_by(|a, b| b.cmp(a))?;
+    /// assert_eq!(vec, [5, 4, 3, 2, 1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_collect_sorted_by<F: FnMut(&T, &T) -> Ordering>(
+        self,
+        compare: F,
+    ) -> Result<Vec<T>, TryReserveError>;
+
+    /// Attempts to collect items from an iterator into a vector, and then sorts
+    /// the vector with a key extraction function.
+    ///
+    /// The sort is stable (i.e., does not reorder equal elements) and allocates
+    /// its scratch space fallibly: if either collecting the items or allocating
+    /// the scratch space fails, then the collected items are dropped and the
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let vec = [-5i32, 4, 1, -3, 2].try_collect_sorted_by_key(|k| k.abs())?;
+    /// assert_eq!(vec, [1, 2, -3, 4, -5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_collect_sorted_by_key<K: Ord, F: FnMut(&T) -> K>(
+        self,
+        f: F,
+    ) -> Result<Vec<T>, TryReserveError>;
+
+    /// Attempts to collect the `k` greatest items from an iterator, as
+    /// determined by the comparator function, into a vector sorted in
+    /// descending order.
+    ///
+    /// To collect the `k` smallest items instead, reverse the comparator (e.g.,
+    /// `|a, b| b.cmp(a)`).
+    ///
+    /// At most `k` items are held at once, so the allocation made by this
+    /// method is bounded by `k` (or by the iterator's upper bound hint, if that
+    /// is smaller) rather than by the length of the iterator. The relative
+    /// order of items that compare as equal is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let top = [5, 1, 8, 3, 9, 2].try_topk(3, |a, b| a.cmp(b))?;
+    /// assert_eq!(top, [9, 8, 5]);
+    ///
+    /// let bottom = [5, 1, 8, 3, 9, 2].try_topk(2, |a, b| b.cmp(a))?;
+    /// assert_eq!(bottom, [1, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_topk<F: FnMut(&T, &T) -> Ordering>(
+        self,
+        k: usize,
+        compare: F,
+    ) -> Result<Vec<T>, TryReserveError>;
+
+    /// Attempts to group the items from an iterator into arrays of `N` items
+    /// and collect them into a vector.
+    ///
+    /// Any items left over at the end (i.e., fewer than `N`) are returned in a
+    /// second vector (which is also the buffer used to gather each array, so
+    /// it has a capacity of `N` unless the iterator was empty).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let (chunks, leftovers) = (1..=7).try_collect_array_chunks::<3>()?;
+    /// assert_eq!(chunks, [[1, 2, 3], [4, 5, 6]]);
+    /// assert_eq!(leftovers, [7]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[allow(clippy::type_complexity)]
+    fn try_collect_array_chunks<const N: usize>(
+        self,
+    ) -> Result<(Vec<[T; N]>, Vec<T>), TryReserveError>;
+
+    /// Attempts to collect a uniformly random sample of `k` items from an
+    /// iterator into a vector, using reservoir sampling.
+    ///
+    /// If the iterator has `k` or fewer items, then all of them are collected.
+    /// At most `k` items are held at once, so the allocation made by this
+    /// method is bounded by `k` (or by the iterator's upper bound hint, if that
+    /// is smaller) rather than by the length of the iterator. The order of the
+    /// items in the returned vector is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// # struct XorShift(u64);
+    /// # impl rand_core::RngCore for XorShift {
+    /// #     fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+    /// #     fn next_u64(&mut self) -> u64 {
+    /// #         self.0 ^= self.0 << 13;
+    /// #         self.0 ^= self.0 >> 7;
+    /// #         self.0 ^= self.0 << 17;
+    /// #         self.0
+    /// #     }
+    /// #     fn fill_bytes(&mut self, dest: &mut [u8]) { rand_core::impls::fill_bytes_via_next(self, dest) }
+    /// #     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    /// #         Ok(self.fill_bytes(dest))
+    /// #     }
+    /// # }
+    /// # let mut rng = XorShift(42);
+    ///
+    /// let sample = (0..1_000_000).try_sample(10, &mut rng)?;
+    /// assert_eq!(sample.len(), 10);
+    /// assert!(sample.iter().all(|i| (0..1_000_000).contains(i)));
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "rand")]
+    fn try_sample<R: RngCore + ?Sized>(
+        self,
+        k: usize,
+        rng: &mut R,
+    ) -> Result<Vec<T>, TryReserveError>;
+
+    /// Attempts to count the number of times that each distinct item occurs in
+    /// an iterator.
+    ///
+    /// Space in the map is reserved fallibly before each distinct item is
+    /// inserted. If reserving fails, then the map (and the counts collected so
+    /// far) are dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use hashbrown::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let counts: HashMap<_, _, RandomState> = "hello world".chars().try_counts()?;
+    /// assert_eq!(counts[&'l'], 3);
+    /// assert_eq!(counts[&'o'], 2);
+    /// assert_eq!(counts[&'h'], 1);
+    /// assert_eq!(counts.get(&'z'), None);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "hashbrown")]
+    fn try_counts<S: BuildHasher + Default>(self) -> Result<HashMap<T, usize, S>, TryReserveError>
+    where
+        T: Eq + Hash;
 }
 
 impl<T, I> TryCollect<T> for I
@@ -53,13 +295,211 @@ where
     #[cfg(feature = "allocator_api")]
     fn try_collect_in<A: Allocator>(self, alloc: A) -> Result<Vec<T, A>, TryReserveError> {
         let mut vec = Vec::new_in(alloc);
-        vec.try_extend(self.into_iter())?;
+        vec.try_extend(self)?;
         Ok(vec)
     }
 
     fn try_collect(self) -> Result<Vec<T>, TryReserveError> {
         let mut vec = Vec::new();
-        vec.try_extend(self.into_iter())?;
+        vec.try_extend(self)?;
         Ok(vec)
     }
+
+    fn try_collect_into<C: TryExtend<T> + ?Sized>(
+        self,
+        collection: &mut C,
+    ) -> Result<&mut C, TryReserveError> {
+        collection.try_extend_from_iter(self)?;
+        Ok(collection)
+    }
+
+    fn try_collect_sorted(self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Ord,
+    {
+        let mut vec = self.try_collect()?;
+        try_merge_sort(&mut vec, T::lt)?;
+        Ok(vec)
+    }
+
+    fn try_collect_sorted_by<F: FnMut(&T, &T) -> Ordering>(
+        self,
+        mut compare: F,
+    ) -> Result<Vec<T>, TryReserveError> {
+        let mut vec = self.try_collect()?;
+        try_merge_sort(&mut vec, |a, b| compare(a, b) == Ordering::Less)?;
+        Ok(vec)
+    }
+
+    fn try_collect_sorted_by_key<K: Ord, F: FnMut(&T) -> K>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<T>, TryReserveError> {
+        let mut vec = self.try_collect()?;
+        try_merge_sort(&mut vec, |a, b| f(a).lt(&f(b)))?;
+        Ok(vec)
+    }
+
+    fn try_topk<F: FnMut(&T, &T) -> Ordering>(
+        self,
+        k: usize,
+        mut compare: F,
+    ) -> Result<Vec<T>, TryReserveError> {
+        let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let iter = self.into_iter();
+        let capacity = match iter.size_hint() {
+            (_, Some(upper_bound)) => upper_bound.min(k),
+            (_, None) => k,
+        };
+        let mut heap = Vec::new();
+        heap.try_reserve_exact(capacity)?;
+
+        // Keep a min-heap of the `k` greatest items seen so far, so that the
+        // smallest of those is always at the root.
+        for item in iter {
+            if heap.len() < k {
+                heap.try_push(item)?;
+                let last = heap.len() - 1;
+                sift_up(&mut heap, last, &mut is_less);
+            } else if is_less(&heap[0], &item) {
+                heap[0] = item;
+                sift_down(&mut heap, &mut is_less);
+            }
+        }
+
+        // Heap sort: repeatedly move the smallest remaining item to the end.
+        for end in (1..heap.len()).rev() {
+            heap.swap(0, end);
+            sift_down(&mut heap[..end], &mut is_less);
+        }
+        Ok(heap)
+    }
+
+    fn try_collect_array_chunks<const N: usize>(
+        self,
+    ) -> Result<(Vec<[T; N]>, Vec<T>), TryReserveError> {
+        assert!(N != 0, "chunk size must be non-zero");
+        let iter = self.into_iter();
+        let mut chunks = Vec::new();
+        chunks.try_reserve(iter.size_hint().0 / N)?;
+        let mut buffer = Vec::new();
+        for item in iter {
+            // Don't allocate the buffer until there's an item to gather, so
+            // that an empty iterator never causes an allocation.
+            if buffer.capacity() == 0 {
+                buffer.try_reserve_exact(N)?;
+            }
+            // Cannot fail as the space has already been reserved.
+            let _ = buffer.try_push(item);
+            if buffer.len() == N {
+                // SAFETY: The buffer holds exactly `N` items, which are moved
+                // out into the array.
+                let chunk = unsafe {
+                    buffer.set_len(0);
+                    ptr::read(buffer.as_ptr().cast::<[T; N]>())
+                };
+                chunks.try_push(chunk)?;
+            }
+        }
+        Ok((chunks, buffer))
+    }
+
+    #[cfg(feature = "rand")]
+    fn try_sample<R: RngCore + ?Sized>(
+        self,
+        k: usize,
+        rng: &mut R,
+    ) -> Result<Vec<T>, TryReserveError> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let iter = self.into_iter();
+        let capacity = match iter.size_hint() {
+            (_, Some(upper_bound)) => upper_bound.min(k),
+            (_, None) => k,
+        };
+        let mut reservoir = Vec::new();
+        reservoir.try_reserve_exact(capacity)?;
+
+        for (index, item) in iter.enumerate() {
+            if index < k {
+                reservoir.try_push(item)?;
+            } else {
+                let replace = random_index(rng, index + 1);
+                if replace < k {
+                    reservoir[replace] = item;
+                }
+            }
+        }
+        Ok(reservoir)
+    }
+
+    #[cfg(feature = "hashbrown")]
+    fn try_counts<S: BuildHasher + Default>(self) -> Result<HashMap<T, usize, S>, TryReserveError>
+    where
+        T: Eq + Hash,
+    {
+        let mut counts = HashMap::with_hasher(S::default());
+        for item in self {
+            if let Some(count) = counts.get_mut(&item) {
+                *count += 1;
+            } else {
+                try_reserve(&mut counts, 1)?;
+                // Space was reserved above, so this won't reallocate.
+                counts.insert(item, 1);
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Moves the item at `index` towards the root of the min-heap `heap` until it
+/// is no less than its parent.
+fn sift_up<T, F: FnMut(&T, &T) -> bool>(heap: &mut [T], mut index: usize, is_less: &mut F) {
+    while index > 0 {
+        let parent = (index - 1) / 2;
+        if !is_less(&heap[index], &heap[parent]) {
+            break;
+        }
+        heap.swap(index, parent);
+        index = parent;
+    }
+}
+
+/// Moves the root of the min-heap `heap` away from the root until it is no
+/// greater than its children.
+fn sift_down<T, F: FnMut(&T, &T) -> bool>(heap: &mut [T], is_less: &mut F) {
+    let mut index = 0;
+    loop {
+        let mut smallest = index;
+        for child in [2 * index + 1, 2 * index + 2] {
+            if child < heap.len() && is_less(&heap[child], &heap[smallest]) {
+                smallest = child;
+            }
+        }
+        if smallest == index {
+            break;
+        }
+        heap.swap(index, smallest);
+        index = smallest;
+    }
+}
+
+/// Generates a uniformly random index in `0..bound`.
+#[cfg(feature = "rand")]
+fn random_index<R: RngCore + ?Sized>(rng: &mut R, bound: usize) -> usize {
+    // Reject values below `threshold` to avoid modulo bias.
+    let bound = bound as u64;
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let value = rng.next_u64();
+        if value >= threshold {
+            return (value % bound) as usize;
+        }
+    }
 }
@@ -0,0 +1,136 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, TryReserveError};
+use alloc::vec::Vec;
+use core::ptr;
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Concatenates the `Vec`s in `vecs` into a single `Vec`.
+///
+/// The inner `Vec` with the largest capacity is reused for the result, so
+/// there is at most one reallocation (and none if that `Vec` already has
+/// enough capacity), and the items of each of the other inner `Vec`s are then
+/// moved over with a single copy. If the reallocation fails, then `vecs` is
+/// returned unchanged along with the error.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vecs = try_vec![try_vec![1, 2]?, Vec::new(), try_vec![3]?]?;
+/// let flat = try_concat_vecs(vecs).map_err(|(_, e)| e)?;
+/// assert_eq!(flat, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+#[allow(clippy::type_complexity)]
+pub fn try_concat_vecs<T>(mut vecs: Vec<Vec<T>>) -> Result<Vec<T>, (Vec<Vec<T>>, TryReserveError)> {
+    let Some(largest) = (0..vecs.len()).max_by_key(|&index| vecs[index].capacity()) else {
+        return Ok(Vec::new());
+    };
+    let Some(total) = vecs
+        .iter()
+        .try_fold(0usize, |total, vec| total.checked_add(vec.len()))
+    else {
+        return Err((vecs, capacity_overflow()));
+    };
+    let additional = total - vecs[largest].len();
+    if let Err(error) = vecs[largest].try_reserve_exact(additional) {
+        return Err((vecs, error));
+    }
+
+    let mut result = vecs.remove(largest);
+    let (before, after) = vecs.split_at_mut(largest);
+    let before_len: usize = before.iter().map(Vec::len).sum();
+    let ptr = result.as_mut_ptr();
+    unsafe {
+        // Make room for the items from the `Vec`s before the reused one.
+        ptr::copy(ptr, ptr.add(before_len), result.len());
+        let mut offset = 0;
+        for vec in before {
+            ptr::copy_nonoverlapping(vec.as_ptr(), ptr.add(offset), vec.len());
+            offset += vec.len();
+            vec.set_len(0);
+        }
+        offset += result.len();
+        for vec in after {
+            ptr::copy_nonoverlapping(vec.as_ptr(), ptr.add(offset), vec.len());
+            offset += vec.len();
+            vec.set_len(0);
+        }
+        result.set_len(total);
+    }
+    Ok(result)
+}
+
+/// Concatenates the `Vec`s in `vecs` into a single `Vec`.
+///
+/// The inner `Vec` with the largest capacity is reused for the result, so
+/// there is at most one reallocation (and none if that `Vec` already has
+/// enough capacity), and the items of each of the other inner `Vec`s are then
+/// moved over with a single copy. If the reallocation fails, then `vecs` is
+/// returned unchanged along with the error.
+///
+/// If `vecs` is empty, then the result uses the default allocator of type
+/// `A`.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vecs = try_vec![try_vec![1, 2]?, Vec::new(), try_vec![3]?]?;
+/// let flat = try_concat_vecs(vecs).map_err(|(_, e)| e)?;
+/// assert_eq!(flat, [1, 2, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+#[allow(clippy::type_complexity)]
+pub fn try_concat_vecs<T, A: Allocator + Default, B: Allocator>(
+    mut vecs: Vec<Vec<T, A>, B>,
+) -> Result<Vec<T, A>, (Vec<Vec<T, A>, B>, TryReserveError)> {
+    let Some(largest) = (0..vecs.len()).max_by_key(|&index| vecs[index].capacity()) else {
+        return Ok(Vec::new_in(A::default()));
+    };
+    let Some(total) = vecs
+        .iter()
+        .try_fold(0usize, |total, vec| total.checked_add(vec.len()))
+    else {
+        return Err((vecs, capacity_overflow()));
+    };
+    let additional = total - vecs[largest].len();
+    if let Err(error) = vecs[largest].try_reserve_exact(additional) {
+        return Err((vecs, error));
+    }
+
+    let mut result = vecs.remove(largest);
+    let (before, after) = vecs.split_at_mut(largest);
+    let before_len: usize = before.iter().map(Vec::len).sum();
+    let ptr = result.as_mut_ptr();
+    unsafe {
+        // Make room for the items from the `Vec`s before the reused one.
+        ptr::copy(ptr, ptr.add(before_len), result.len());
+        let mut offset = 0;
+        for vec in before {
+            ptr::copy_nonoverlapping(vec.as_ptr(), ptr.add(offset), vec.len());
+            offset += vec.len();
+            vec.set_len(0);
+        }
+        offset += result.len();
+        for vec in after {
+            ptr::copy_nonoverlapping(vec.as_ptr(), ptr.add(offset), vec.len());
+            offset += vec.len();
+            vec.set_len(0);
+        }
+        result.set_len(total);
+    }
+    Ok(result)
+}
@@ -0,0 +1,259 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, FallibleVec, TryReserveError};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_PADDING: u8 = b'=';
+
+/// The error type for the decoding functions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The length of the input is not valid for the encoding.
+    InvalidLength,
+
+    /// The input contains a byte that is not valid at its position.
+    InvalidByte {
+        /// The index of the invalid byte in the input.
+        index: usize,
+    },
+
+    /// Allocating space for the decoded output failed.
+    Alloc(TryReserveError),
+}
+
+impl From<TryReserveError> for DecodeError {
+    fn from(error: TryReserveError) -> Self {
+        DecodeError::Alloc(error)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength => f.write_str("invalid input length"),
+            DecodeError::InvalidByte { index } => write!(f, "invalid byte at index {index}"),
+            DecodeError::Alloc(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Creates a new `String` containing `data` encoded as lowercase hexadecimal.
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_hex_encode(&[0x01, 0xab, 0xff])?, "01abff");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_hex_encode(data: &[u8]) -> Result<String, TryReserveError> {
+    let mut encoded = String::new();
+    try_hex_encode_into(&mut encoded, data)?;
+    Ok(encoded)
+}
+
+/// Appends `data` encoded as lowercase hexadecimal to `out`.
+///
+/// Exactly the required space is reserved up front, so at most one allocation
+/// is made. If it fails, `out` is unchanged.
+pub fn try_hex_encode_into(out: &mut String, data: &[u8]) -> Result<(), TryReserveError> {
+    let len = data.len().checked_mul(2).ok_or_else(capacity_overflow)?;
+    // SAFETY: Only ASCII characters are written.
+    let out = unsafe { out.as_mut_vec() };
+    out.try_reserve_exact(len)?;
+    for &byte in data {
+        // Cannot fail as the space has already been reserved.
+        let _ = out.try_push(HEX_DIGITS[usize::from(byte >> 4)]);
+        let _ = out.try_push(HEX_DIGITS[usize::from(byte & 0xf)]);
+    }
+    Ok(())
+}
+
+/// Decodes hexadecimal (either uppercase or lowercase) into a new `Vec`.
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_hex_decode("01abFF")?, [0x01, 0xab, 0xff]);
+/// assert_eq!(try_hex_decode("abc"), Err(DecodeError::InvalidLength));
+/// assert_eq!(try_hex_decode("0g"), Err(DecodeError::InvalidByte { index: 1 }));
+/// # Ok::<(), DecodeError>(())
+/// ```
+pub fn try_hex_decode(hex: impl AsRef<[u8]>) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::new();
+    try_hex_decode_into(&mut decoded, hex)?;
+    Ok(decoded)
+}
+
+/// Decodes hexadecimal (either uppercase or lowercase) and appends it to
+/// `out`.
+///
+/// Exactly the required space is reserved up front, so at most one allocation
+/// is made. If decoding fails, `out` is unchanged.
+pub fn try_hex_decode_into(out: &mut Vec<u8>, hex: impl AsRef<[u8]>) -> Result<(), DecodeError> {
+    let hex = hex.as_ref();
+    if hex.len() % 2 != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    out.try_reserve_exact(hex.len() / 2)?;
+    decode_or_truncate(out, |out| {
+        for (i, pair) in hex.chunks_exact(2).enumerate() {
+            let high = hex_digit_value(pair[0]).ok_or(DecodeError::InvalidByte { index: 2 * i })?;
+            let low =
+                hex_digit_value(pair[1]).ok_or(DecodeError::InvalidByte { index: 2 * i + 1 })?;
+            let _ = out.try_push(high << 4 | low);
+        }
+        Ok(())
+    })
+}
+
+/// Creates a new `String` containing `data` encoded as padded base64, using
+/// the standard alphabet (RFC 4648).
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_base64_encode(b"hello")?, "aGVsbG8=");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_base64_encode(data: &[u8]) -> Result<String, TryReserveError> {
+    let mut encoded = String::new();
+    try_base64_encode_into(&mut encoded, data)?;
+    Ok(encoded)
+}
+
+/// Appends `data` encoded as padded base64, using the standard alphabet
+/// (RFC 4648), to `out`.
+///
+/// Exactly the required space is reserved up front, so at most one allocation
+/// is made. If it fails, `out` is unchanged.
+pub fn try_base64_encode_into(out: &mut String, data: &[u8]) -> Result<(), TryReserveError> {
+    let len = data
+        .chunks(3)
+        .len()
+        .checked_mul(4)
+        .ok_or_else(capacity_overflow)?;
+    // SAFETY: Only ASCII characters are written.
+    let out = unsafe { out.as_mut_vec() };
+    out.try_reserve_exact(len)?;
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            // Cannot fail as the space has already been reserved.
+            let _ = out.try_push(if i <= chunk.len() {
+                BASE64_ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize]
+            } else {
+                BASE64_PADDING
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Decodes padded base64, using the standard alphabet (RFC 4648), into a new
+/// `Vec`.
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_base64_decode("aGVsbG8=")?, b"hello");
+/// assert_eq!(try_base64_decode("aGVsbG8"), Err(DecodeError::InvalidLength));
+/// # Ok::<(), DecodeError>(())
+/// ```
+pub fn try_base64_decode(base64: impl AsRef<[u8]>) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::new();
+    try_base64_decode_into(&mut decoded, base64)?;
+    Ok(decoded)
+}
+
+/// Decodes padded base64, using the standard alphabet (RFC 4648), and appends
+/// it to `out`.
+///
+/// Exactly the required space is reserved up front, so at most one allocation
+/// is made. If decoding fails, `out` is unchanged.
+pub fn try_base64_decode_into(
+    out: &mut Vec<u8>,
+    base64: impl AsRef<[u8]>,
+) -> Result<(), DecodeError> {
+    let base64 = base64.as_ref();
+    if base64.len() % 4 != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    let padding = base64
+        .iter()
+        .rev()
+        .take(2)
+        .take_while(|&&byte| byte == BASE64_PADDING)
+        .count();
+    out.try_reserve_exact(base64.len() / 4 * 3 - padding)?;
+    decode_or_truncate(out, |out| {
+        let data_len = base64.len() - padding;
+        for (chunk_index, chunk) in base64.chunks_exact(4).enumerate() {
+            let start = chunk_index * 4;
+            let mut bits = 0u32;
+            let mut digits = 0;
+            for (i, &byte) in chunk.iter().enumerate() {
+                if start + i >= data_len {
+                    break;
+                }
+                let value = base64_digit_value(byte)
+                    .ok_or(DecodeError::InvalidByte { index: start + i })?;
+                bits |= u32::from(value) << (18 - 6 * i);
+                digits += 1;
+            }
+            // Every digit after the first completes another byte.
+            for i in 0..digits - 1 {
+                let _ = out.try_push((bits >> (16 - 8 * i)) as u8);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Runs `decode` to append to `out`, truncating `out` back to its original
+/// length if decoding fails.
+fn decode_or_truncate(
+    out: &mut Vec<u8>,
+    decode: impl FnOnce(&mut Vec<u8>) -> Result<(), DecodeError>,
+) -> Result<(), DecodeError> {
+    let original_len = out.len();
+    let result = decode(out);
+    if result.is_err() {
+        out.truncate(original_len);
+    }
+    result
+}
+
+fn hex_digit_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn base64_digit_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'A'..=b'Z' => Some(digit - b'A'),
+        b'a'..=b'z' => Some(digit - b'a' + 26),
+        b'0'..=b'9' => Some(digit - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{alloc_error, error::capacity_overflow, ItemOrAllocError, TryReserveError};
+use core::alloc::Layout;
+use libc::{c_int, ENOMEM, EOVERFLOW};
+
+/// Conversion of an error into an `errno`-style status code, for reporting
+/// failures across an FFI boundary.
+///
+/// Allocation failures are reported as `ENOMEM`, and capacity overflows (where
+/// the requested capacity exceeded the maximum that a collection can hold) are
+/// reported as `EOVERFLOW`.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// #[no_mangle]
+/// pub extern "C" fn buffer_append(
+///     buffer: &mut Vec<u8>,
+///     data: *const u8,
+///     len: usize,
+/// ) -> libc::c_int {
+///     let data = unsafe { std::slice::from_raw_parts(data, len) };
+///     match buffer.try_extend_from_slice(data) {
+///         Ok(()) => 0,
+///         Err(error) => error.to_errno(),
+///     }
+/// }
+///
+/// let mut buffer = Vec::new();
+/// assert_eq!(buffer_append(&mut buffer, b"abc".as_ptr(), 3), 0);
+/// assert_eq!(buffer, b"abc");
+/// ```
+pub trait ToErrno {
+    /// Returns the `errno` value that corresponds to this error.
+    fn to_errno(&self) -> c_int;
+}
+
+impl ToErrno for TryReserveError {
+    fn to_errno(&self) -> c_int {
+        if *self == capacity_overflow() {
+            EOVERFLOW
+        } else {
+            ENOMEM
+        }
+    }
+}
+
+impl<E: ToErrno> ToErrno for ItemOrAllocError<E> {
+    fn to_errno(&self) -> c_int {
+        match self {
+            ItemOrAllocError::Item(error) => error.to_errno(),
+            ItemOrAllocError::Alloc(error) => error.to_errno(),
+        }
+    }
+}
+
+/// Creates a [`TryReserveError`] from an `errno`-style status code, or returns
+/// `None` if `errno` doesn't indicate an allocation failure.
+///
+/// This is the reverse of [`ToErrno::to_errno`]: `EOVERFLOW` becomes a
+/// capacity overflow error and `ENOMEM` becomes an allocation failure. As the
+/// status code doesn't record the layout of the failed allocation, an empty
+/// layout is reported instead.
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// let error = try_reserve_error_from_errno(libc::ENOMEM).unwrap();
+/// assert_eq!(error.to_errno(), libc::ENOMEM);
+/// assert!(try_reserve_error_from_errno(libc::EINVAL).is_none());
+/// ```
+pub fn try_reserve_error_from_errno(errno: c_int) -> Option<TryReserveError> {
+    match errno {
+        ENOMEM => Some(alloc_error(Layout::new::<()>())),
+        EOVERFLOW => Some(capacity_overflow()),
+        _ => None,
+    }
+}
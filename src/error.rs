@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
-use core::alloc::Layout;
+use alloc::collections::TryReserveError;
+use core::{alloc::Layout, fmt};
 
 #[allow(dead_code)]
 #[cfg(any(test, not(feature = "use_unstable_apis")))]
@@ -42,6 +43,14 @@ mod internal {
             })
         }
     }
+
+    pub fn build_capacity_overflow_error() -> alloc::collections::TryReserveError {
+        unsafe {
+            core::mem::transmute(internal::TryReserveError {
+                kind: internal::TryReserveErrorKind::CapacityOverflow,
+            })
+        }
+    }
 }
 
 #[cfg(feature = "use_unstable_apis")]
@@ -53,7 +62,40 @@ fn build_error_from_layout(layout: Layout) -> alloc::collections::TryReserveErro
     .into()
 }
 
-#[doc(hidden)]
+#[cfg(feature = "use_unstable_apis")]
+fn build_capacity_overflow_error() -> alloc::collections::TryReserveError {
+    alloc::collections::TryReserveErrorKind::CapacityOverflow.into()
+}
+
+/// Creates a [`TryReserveError`] indicating that the allocator failed to
+/// allocate memory for `layout`.
+///
+/// This is useful when implementing fallible allocation for other containers:
+/// the error returned from an [`Allocator`](core::alloc::Allocator) (or a null
+/// pointer returned from a [`GlobalAlloc`](core::alloc::GlobalAlloc)) can be
+/// converted into the same error type as the `try_reserve` methods on `Vec`.
+///
+/// `TryReserveError` cannot be constructed directly on the stable compiler,
+/// so if the `use_unstable_apis` feature is disabled then the error is built
+/// from a private copy of its definition (which is checked to have the same
+/// size as the real type at compile time).
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::alloc_error;
+/// use std::alloc::{Allocator, Global, Layout};
+/// use std::collections::TryReserveError;
+///
+/// fn try_allocate_buffer(len: usize) -> Result<std::ptr::NonNull<[u8]>, TryReserveError> {
+///     let layout = Layout::array::<u8>(len).unwrap();
+///     Global.allocate(layout).map_err(|_| alloc_error(layout))
+/// }
+/// # let buffer = try_allocate_buffer(16)?;
+/// # unsafe { Global.deallocate(buffer.cast(), Layout::array::<u8>(16).unwrap()) };
+/// # Ok::<(), TryReserveError>(())
+/// ```
 pub fn alloc_error(layout: Layout) -> alloc::collections::TryReserveError {
     #[cfg(feature = "use_unstable_apis")]
     {
@@ -65,6 +107,117 @@ pub fn alloc_error(layout: Layout) -> alloc::collections::TryReserveError {
     }
 }
 
+/// Creates a `TryReserveError` indicating that the computed capacity exceeded
+/// the collection's maximum.
+pub(crate) fn capacity_overflow() -> alloc::collections::TryReserveError {
+    #[cfg(feature = "use_unstable_apis")]
+    {
+        build_capacity_overflow_error()
+    }
+    #[cfg(not(feature = "use_unstable_apis"))]
+    {
+        internal::build_capacity_overflow_error()
+    }
+}
+
+/// The error type for operations that add items produced by a fallible source
+/// (e.g., a closure or an iterator returning `Result`s).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ItemOrAllocError<E> {
+    /// The source failed to produce an item.
+    Item(E),
+
+    /// Allocating space for an item failed.
+    Alloc(TryReserveError),
+}
+
+impl<E> From<TryReserveError> for ItemOrAllocError<E> {
+    fn from(error: TryReserveError) -> Self {
+        ItemOrAllocError::Alloc(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ItemOrAllocError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemOrAllocError::Item(error) => error.fmt(f),
+            ItemOrAllocError::Alloc(error) => error.fmt(f),
+        }
+    }
+}
+
+/// The error type for the `checked_*` methods, which return an error for
+/// invalid arguments instead of panicking.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FallibleVecError {
+    /// The index is greater than the length of the `Vec`.
+    IndexOutOfBounds {
+        /// The index that was provided.
+        index: usize,
+        /// The length of the `Vec`.
+        len: usize,
+    },
+
+    /// The starting point of the range is greater than its end point, or the
+    /// end point is greater than the length of the `Vec`.
+    RangeOutOfBounds,
+
+    /// Allocating space for the new items failed.
+    Alloc(TryReserveError),
+}
+
+impl From<TryReserveError> for FallibleVecError {
+    fn from(error: TryReserveError) -> Self {
+        FallibleVecError::Alloc(error)
+    }
+}
+
+impl fmt::Display for FallibleVecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FallibleVecError::IndexOutOfBounds { index, len } => write!(
+                f,
+                "index out of bounds: the len is {len} but the index is {index}"
+            ),
+            FallibleVecError::RangeOutOfBounds => f.write_str("range out of bounds"),
+            FallibleVecError::Alloc(error) => error.fmt(f),
+        }
+    }
+}
+
+/// The error type for [`try_extend_resumable`](crate::FallibleVec::try_extend_resumable),
+/// which records how far the extension got so that it can be resumed.
+#[derive(Clone, Debug)]
+pub struct ExtendError<T, I> {
+    /// The number of items that were appended before the failure.
+    pub appended: usize,
+
+    /// The item that had been taken from the iterator but could not be
+    /// appended.
+    pub item: T,
+
+    /// The rest of the iterator, which has not been consumed.
+    pub remaining: I,
+
+    /// The error from reserving space for `item`.
+    pub error: TryReserveError,
+}
+
+impl<T, I: Iterator<Item = T>> ExtendError<T, I> {
+    /// Returns an iterator over the items that were not appended: the item
+    /// that failed followed by the rest of the original iterator.
+    pub fn into_remaining(self) -> core::iter::Chain<core::iter::Once<T>, I> {
+        core::iter::once(self.item).chain(self.remaining)
+    }
+}
+
+impl<T, I> fmt::Display for ExtendError<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
 #[test]
 #[cfg(feature = "use_unstable_apis")]
 fn check_error_transmute() {
@@ -74,3 +227,12 @@ fn check_error_transmute() {
         internal::build_error_from_layout(layout)
     );
 }
+
+#[test]
+#[cfg(feature = "use_unstable_apis")]
+fn check_capacity_overflow_transmute() {
+    assert_eq!(
+        build_capacity_overflow_error(),
+        internal::build_capacity_overflow_error()
+    );
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{FallibleVec, TryReserveError};
+use alloc::{string::String, vec::Vec};
+
+#[cfg(not(no_global_oom_handling))]
+use alloc::collections::VecDeque;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+// Reserves space for the first item (and the iterator's lower bound), then
+// pushes each item onto the back of the `VecDeque` after reserving space for
+// it.
+#[cfg(not(no_global_oom_handling))]
+macro_rules! extend_deque {
+    ($deque:expr, $iter:expr) => {{
+        let deque = $deque;
+        let mut iter = $iter.into_iter();
+        // Don't trust the lower bound until the iterator has produced an
+        // item, so that an empty iterator never causes an allocation.
+        if let Some(first) = iter.next() {
+            let (low_bound, _upper_bound) = iter.size_hint();
+            deque.try_reserve(low_bound.saturating_add(1))?;
+            deque.push_back(first);
+            for item in iter {
+                deque.try_reserve(1)?;
+                // Cannot reallocate as the space has already been reserved.
+                deque.push_back(item);
+            }
+        }
+        Ok(())
+    }};
+}
+
+/// A collection that can be extended with the items from an iterator, where
+/// space for the items is allocated fallibly.
+///
+/// This is the fallible equivalent of [`Extend`], and allows code (such as
+/// [`TryCollect::try_collect_into`](crate::TryCollect::try_collect_into)) to
+/// fill any supported collection without naming its concrete extend method.
+///
+/// If an allocation fails, then the items that were added before the failure
+/// remain in the collection.
+///
+/// `VecDeque` is only supported if `#[cfg(no_global_oom_handling)]` is not
+/// enabled, since the standard library doesn't provide it in that case.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::collections::VecDeque;
+///
+/// fn fill<C: TryExtend<u32>>(sink: &mut C) -> Result<(), std::collections::TryReserveError> {
+///     sink.try_extend_from_iter((1..=3).map(|i| i * 10))
+/// }
+///
+/// let mut vec = Vec::new();
+/// fill(&mut vec)?;
+/// assert_eq!(vec, [10, 20, 30]);
+///
+/// let mut deque = VecDeque::new();
+/// fill(&mut deque)?;
+/// assert_eq!(deque, [10, 20, 30]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub trait TryExtend<T> {
+    /// Extends the collection with the items from `iter`.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `next()` on `iter` panics, then all of the items previously
+    /// returned from the iterator will be added to the collection.
+    fn try_extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError>;
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T> TryExtend<T> for Vec<T> {
+    fn try_extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        self.try_extend(iter)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> TryExtend<T> for Vec<T, A> {
+    fn try_extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        self.try_extend(iter)
+    }
+}
+
+#[cfg(all(not(no_global_oom_handling), not(feature = "allocator_api")))]
+impl<T> TryExtend<T> for VecDeque<T> {
+    fn try_extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        extend_deque!(self, iter)
+    }
+}
+
+#[cfg(all(not(no_global_oom_handling), feature = "allocator_api"))]
+impl<T, A: Allocator> TryExtend<T> for VecDeque<T, A> {
+    fn try_extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        extend_deque!(self, iter)
+    }
+}
+
+impl TryExtend<char> for String {
+    fn try_extend_from_iter<I: IntoIterator<Item = char>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        for c in iter {
+            // SAFETY: The UTF-8 encoding of a `char` is valid UTF-8.
+            unsafe { self.as_mut_vec() }
+                .try_extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryExtend<&'a str> for String {
+    fn try_extend_from_iter<I: IntoIterator<Item = &'a str>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError> {
+        for s in iter {
+            // SAFETY: A `str` is valid UTF-8.
+            unsafe { self.as_mut_vec() }.try_extend_from_slice(s.as_bytes())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,409 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{SetLenOnDrop, SpareCapacityWriter, TryReserveError};
+use alloc::vec::Vec;
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+macro_rules! struct_extend_guard {
+    { $(#[doc = $doc:expr])+ pub struct ExtendGuard $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct ExtendGuard<'a, T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct ExtendGuard<'a, T, A: Allocator> $impl
+    }
+}
+
+struct_extend_guard! {
+    /// A guard for appending items to the spare capacity of a [`Vec`] that
+    /// commits the new length of the `Vec` when it goes out of scope.
+    ///
+    /// This is the pattern used internally by this crate for bulk writes:
+    /// * The length is tracked in a local field rather than updated on the
+    ///   `Vec` after each write. The optimizer can see that this field doesn't
+    ///   alias with the writes through the `Vec`'s data pointer, which
+    ///   allows loops writing items to be optimized as well as writes to a
+    ///   slice (see <https://github.com/rust-lang/rust/issues/32155>).
+    /// * The length is committed to the `Vec` when the guard is dropped,
+    ///   including if it is dropped while unwinding from a panic (e.g., in a
+    ///   call to `clone` or an iterator's `next`). This means that every item
+    ///   written before the panic is owned by the `Vec`, and nothing is leaked
+    ///   or double-dropped.
+    ///
+    /// Items can only be written into capacity that the `Vec` already has, so
+    /// writing never allocates: reserve the required space first (or use
+    /// [`try_reserve`](ExtendGuard::try_reserve)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = vec![1];
+    /// {
+    ///     let mut guard = ExtendGuard::try_reserve(&mut vec, 2)?;
+    ///     guard.push_within_capacity(2).unwrap();
+    ///     guard.push_within_capacity(3).unwrap();
+    /// }
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    pub struct ExtendGuard {
+        ptr: *mut T,
+        capacity: usize,
+        #[cfg(not(feature = "allocator_api"))]
+        local_len: SetLenOnDrop<'a, T>,
+        #[cfg(feature = "allocator_api")]
+        local_len: SetLenOnDrop<'a, T, A>,
+    }
+}
+
+macro_rules! impl_extend_guard {
+    { impl ExtendGuard $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<'a, T> ExtendGuard<'a, T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<'a, T, A: Allocator> ExtendGuard<'a, T, A> $impl
+    }
+}
+
+impl_extend_guard! {
+    impl ExtendGuard {
+        /// Creates a guard for appending items to the existing spare capacity
+        /// of `vec`.
+        #[cfg(not(feature = "allocator_api"))]
+        pub fn new(vec: &'a mut Vec<T>) -> Self {
+            ExtendGuard {
+                ptr: vec.as_mut_ptr(),
+                capacity: vec.capacity(),
+                local_len: SetLenOnDrop::new(vec),
+            }
+        }
+
+        /// Creates a guard for appending items to the existing spare capacity
+        /// of `vec`.
+        #[cfg(feature = "allocator_api")]
+        pub fn new(vec: &'a mut Vec<T, A>) -> Self {
+            ExtendGuard {
+                ptr: vec.as_mut_ptr(),
+                capacity: vec.capacity(),
+                local_len: SetLenOnDrop::new(vec),
+            }
+        }
+
+        /// Reserves capacity for at least `additional` more items in `vec`,
+        /// then creates a guard for appending items to its spare capacity.
+        #[cfg(not(feature = "allocator_api"))]
+        pub fn try_reserve(vec: &'a mut Vec<T>, additional: usize) -> Result<Self, TryReserveError> {
+            vec.try_reserve(additional)?;
+            Ok(Self::new(vec))
+        }
+
+        /// Reserves capacity for at least `additional` more items in `vec`,
+        /// then creates a guard for appending items to its spare capacity.
+        #[cfg(feature = "allocator_api")]
+        pub fn try_reserve(
+            vec: &'a mut Vec<T, A>,
+            additional: usize,
+        ) -> Result<Self, TryReserveError> {
+            vec.try_reserve(additional)?;
+            Ok(Self::new(vec))
+        }
+
+        /// Appends `item` to the `Vec` if there is spare capacity, otherwise
+        /// returns it.
+        pub fn push_within_capacity(&mut self, item: T) -> Result<(), T> {
+            if self.spare_capacity() == 0 {
+                return Err(item);
+            }
+            unsafe {
+                self.ptr.add(self.local_len.current_len()).write(item);
+            }
+            self.local_len.increment_len(1);
+            Ok(())
+        }
+
+        /// Appends `item` to the `Vec`.
+        ///
+        /// This is intended for use after reserving space (e.g., with
+        /// [`try_reserve_guard`](ReserveGuard::try_reserve_guard)), so it
+        /// returns nothing rather than a `Result`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if there is no spare capacity (i.e., if more items are pushed
+        /// than space was reserved for).
+        pub fn push(&mut self, item: T) {
+            assert!(
+                self.spare_capacity() != 0,
+                "pushed more items than the reserved capacity"
+            );
+            unsafe {
+                self.ptr.add(self.local_len.current_len()).write(item);
+            }
+            self.local_len.increment_len(1);
+        }
+
+        /// Returns the length that will be committed to the `Vec`, including
+        /// the items written through this guard.
+        pub fn len(&self) -> usize {
+            self.local_len.current_len()
+        }
+
+        /// Returns `true` if [`len`](ExtendGuard::len) is zero.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Returns the number of items that can still be written before the
+        /// `Vec`'s capacity is exhausted.
+        pub fn spare_capacity(&self) -> usize {
+            self.capacity - self.local_len.current_len()
+        }
+    }
+}
+
+/// Creates guards for writing into the spare capacity of a [`Vec`] after
+/// reserving space.
+#[cfg(not(feature = "allocator_api"))]
+pub trait ReserveGuard<T> {
+    /// Reserves capacity for at least `additional` more items, then returns a
+    /// guard for appending items to the spare capacity without checking for
+    /// allocation failures.
+    ///
+    /// The new length is committed to the `Vec` when the guard is dropped. If
+    /// reserving fails, then the error is returned and the `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = vec![1];
+    /// {
+    ///     let mut guard = vec.try_reserve_guard(2)?;
+    ///     guard.push(2);
+    ///     guard.push(3);
+    /// }
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_reserve_guard(
+        &mut self,
+        additional: usize,
+    ) -> Result<ExtendGuard<'_, T>, TryReserveError>;
+
+    /// Reserves capacity for at least `additional` more items, then returns a
+    /// writer that exposes `additional` items of that space as a
+    /// `[MaybeUninit<T>]` so that it can be written to directly (e.g., by
+    /// reading from a socket).
+    ///
+    /// Like [`Vec::try_reserve`], this may reserve more space than requested
+    /// so that repeated calls are amortized; use
+    /// [`try_spare_capacity_exact`](ReserveGuard::try_spare_capacity_exact)
+    /// to avoid over-allocating.
+    ///
+    /// The length of the `Vec` is unchanged until
+    /// [`commit`](SpareCapacityWriter::commit) is called. If reserving fails,
+    /// then the error is returned and the `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = vec![1u8];
+    /// let mut writer = buffer.try_spare_capacity(4)?;
+    /// assert_eq!(writer.len(), 4);
+    /// writer[0].write(2);
+    /// writer[1].write(3);
+    /// // SAFETY: The first two items were initialized above.
+    /// unsafe { writer.commit(2) };
+    /// assert_eq!(buffer, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_spare_capacity(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T>, TryReserveError>;
+
+    /// Reserves capacity for exactly `additional` more items, then returns a
+    /// writer that exposes that space as a `[MaybeUninit<T>]`.
+    ///
+    /// Otherwise the same as
+    /// [`try_spare_capacity`](ReserveGuard::try_spare_capacity).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// let mut writer = buffer.try_spare_capacity_exact(4)?;
+    /// writer[0].write(1);
+    /// // SAFETY: The first item was initialized above.
+    /// unsafe { writer.commit(1) };
+    /// assert_eq!(buffer, [1]);
+    /// assert_eq!(buffer.capacity(), 4);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_spare_capacity_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T>, TryReserveError>;
+}
+
+/// Creates guards for writing into the spare capacity of a [`Vec`] after
+/// reserving space.
+#[cfg(feature = "allocator_api")]
+pub trait ReserveGuard<T, A: Allocator> {
+    /// Reserves capacity for at least `additional` more items, then returns a
+    /// guard for appending items to the spare capacity without checking for
+    /// allocation failures.
+    ///
+    /// The new length is committed to the `Vec` when the guard is dropped. If
+    /// reserving fails, then the error is returned and the `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = vec![1];
+    /// {
+    ///     let mut guard = vec.try_reserve_guard(2)?;
+    ///     guard.push(2);
+    ///     guard.push(3);
+    /// }
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_reserve_guard(
+        &mut self,
+        additional: usize,
+    ) -> Result<ExtendGuard<'_, T, A>, TryReserveError>;
+
+    /// Reserves capacity for at least `additional` more items, then returns a
+    /// writer that exposes `additional` items of that space as a
+    /// `[MaybeUninit<T>]` so that it can be written to directly (e.g., by
+    /// reading from a socket).
+    ///
+    /// Like [`Vec::try_reserve`], this may reserve more space than requested
+    /// so that repeated calls are amortized; use
+    /// [`try_spare_capacity_exact`](ReserveGuard::try_spare_capacity_exact)
+    /// to avoid over-allocating.
+    ///
+    /// The length of the `Vec` is unchanged until
+    /// [`commit`](SpareCapacityWriter::commit) is called. If reserving fails,
+    /// then the error is returned and the `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = vec![1u8];
+    /// let mut writer = buffer.try_spare_capacity(4)?;
+    /// assert_eq!(writer.len(), 4);
+    /// writer[0].write(2);
+    /// writer[1].write(3);
+    /// // SAFETY: The first two items were initialized above.
+    /// unsafe { writer.commit(2) };
+    /// assert_eq!(buffer, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_spare_capacity(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T, A>, TryReserveError>;
+
+    /// Reserves capacity for exactly `additional` more items, then returns a
+    /// writer that exposes that space as a `[MaybeUninit<T>]`.
+    ///
+    /// Otherwise the same as
+    /// [`try_spare_capacity`](ReserveGuard::try_spare_capacity).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// let mut writer = buffer.try_spare_capacity_exact(4)?;
+    /// writer[0].write(1);
+    /// // SAFETY: The first item was initialized above.
+    /// unsafe { writer.commit(1) };
+    /// assert_eq!(buffer, [1]);
+    /// assert_eq!(buffer.capacity(), 4);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_spare_capacity_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T, A>, TryReserveError>;
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T> ReserveGuard<T> for Vec<T> {
+    fn try_reserve_guard(
+        &mut self,
+        additional: usize,
+    ) -> Result<ExtendGuard<'_, T>, TryReserveError> {
+        ExtendGuard::try_reserve(self, additional)
+    }
+
+    fn try_spare_capacity(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T>, TryReserveError> {
+        self.try_reserve(additional)?;
+        Ok(SpareCapacityWriter::new(self, additional))
+    }
+
+    fn try_spare_capacity_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T>, TryReserveError> {
+        self.try_reserve_exact(additional)?;
+        Ok(SpareCapacityWriter::new(self, additional))
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> ReserveGuard<T, A> for Vec<T, A> {
+    fn try_reserve_guard(
+        &mut self,
+        additional: usize,
+    ) -> Result<ExtendGuard<'_, T, A>, TryReserveError> {
+        ExtendGuard::try_reserve(self, additional)
+    }
+
+    fn try_spare_capacity(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T, A>, TryReserveError> {
+        self.try_reserve(additional)?;
+        Ok(SpareCapacityWriter::new(self, additional))
+    }
+
+    fn try_spare_capacity_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<SpareCapacityWriter<'_, T, A>, TryReserveError> {
+        self.try_reserve_exact(additional)?;
+        Ok(SpareCapacityWriter::new(self, additional))
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{alloc_error, error::capacity_overflow, FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+use hashbrown::HashMap;
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Fallibly reserves capacity for at least `additional` more items in `map`.
+pub(crate) fn try_reserve<K: Eq + Hash, V, S: BuildHasher>(
+    map: &mut HashMap<K, V, S>,
+    additional: usize,
+) -> Result<(), TryReserveError> {
+    map.try_reserve(additional).map_err(|err| match err {
+        hashbrown::TryReserveError::CapacityOverflow => capacity_overflow(),
+        hashbrown::TryReserveError::AllocError { layout } => alloc_error(layout),
+    })
+}
+
+/// Fallible allocation equivalents for grouping the items of an iterator into
+/// a [`HashMap`].
+pub trait TryIntoGroupMap<K, V> {
+    /// Attempts to group the values from an iterator of key-value pairs into a
+    /// map from each distinct key to a vector of its values.
+    ///
+    /// Values are added to each group in the order that they are returned from
+    /// the iterator.
+    ///
+    /// Space is reserved fallibly both in the map (before each distinct key is
+    /// inserted) and in each group's vector (before each value is added). If
+    /// either fails, then the map, all of the groups collected so far and the
+    /// key-value pair being added are dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use hashbrown::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let pairs = [("odd", 1), ("even", 2), ("odd", 3), ("odd", 5)];
+    /// let groups: HashMap<_, _, RandomState> = pairs.try_into_group_map()?;
+    /// assert_eq!(groups["odd"], [1, 3, 5]);
+    /// assert_eq!(groups["even"], [2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_into_group_map<S: BuildHasher + Default>(
+        self,
+    ) -> Result<HashMap<K, Vec<V>, S>, TryReserveError>;
+
+    /// Attempts to group the values from an iterator of key-value pairs into a
+    /// map from each distinct key to a vector of its values, with each vector
+    /// using a clone of the provided allocator.
+    ///
+    /// Values are added to each group in the order that they are returned from
+    /// the iterator.
+    ///
+    /// Space is reserved fallibly both in the map (before each distinct key is
+    /// inserted) and in each group's vector (before each value is added). If
+    /// either fails, then the map, all of the groups collected so far and the
+    /// key-value pair being added are dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use hashbrown::HashMap;
+    /// use std::alloc::System;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let pairs = [("odd", 1), ("even", 2), ("odd", 3), ("odd", 5)];
+    /// let groups: HashMap<_, _, RandomState> = pairs.try_into_group_map_in(System)?;
+    /// assert_eq!(groups["odd"], [1, 3, 5]);
+    /// assert_eq!(groups["even"], [2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_into_group_map_in<S: BuildHasher + Default, A: Allocator + Clone>(
+        self,
+        alloc: A,
+    ) -> Result<HashMap<K, Vec<V, A>, S>, TryReserveError>;
+}
+
+impl<K, V, I> TryIntoGroupMap<K, V> for I
+where
+    K: Eq + Hash,
+    I: IntoIterator<Item = (K, V)>,
+{
+    fn try_into_group_map<S: BuildHasher + Default>(
+        self,
+    ) -> Result<HashMap<K, Vec<V>, S>, TryReserveError> {
+        let mut groups: HashMap<K, Vec<V>, S> = HashMap::with_hasher(S::default());
+        for (key, value) in self {
+            if let Some(group) = groups.get_mut(&key) {
+                group.try_push(value)?;
+            } else {
+                try_reserve(&mut groups, 1)?;
+                let mut group = Vec::new();
+                group.try_push(value)?;
+                // Space was reserved above, so this won't reallocate.
+                groups.insert(key, group);
+            }
+        }
+        Ok(groups)
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn try_into_group_map_in<S: BuildHasher + Default, A: Allocator + Clone>(
+        self,
+        alloc: A,
+    ) -> Result<HashMap<K, Vec<V, A>, S>, TryReserveError> {
+        let mut groups: HashMap<K, Vec<V, A>, S> = HashMap::with_hasher(S::default());
+        for (key, value) in self {
+            if let Some(group) = groups.get_mut(&key) {
+                group.try_push(value)?;
+            } else {
+                try_reserve(&mut groups, 1)?;
+                let mut group = Vec::new_in(alloc.clone());
+                group.try_push(value)?;
+                // Space was reserved above, so this won't reallocate.
+                groups.insert(key, group);
+            }
+        }
+        Ok(groups)
+    }
+}
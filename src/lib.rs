@@ -41,6 +41,13 @@
 //!
 //! The exact behavior of each method is specified in its documentations.
 //!
+//! # Allocation
+//!
+//! These methods only call into the allocator if the `Vec` needs to grow:
+//! zero-length inputs (e.g., an empty slice, an empty iterator or an empty
+//! range being replaced with nothing) and inputs that fit within the existing
+//! capacity will never allocate, and so cannot fail.
+//!
 //! # Completeness
 //!
 //! NOTE: This API is incomplete, there are many more infallible functions on
@@ -53,18 +60,103 @@
 
 extern crate alloc;
 
+mod array;
+mod boxed;
+mod bytes;
+mod chunks;
+mod clone;
 mod collect;
+mod concat;
+#[cfg(feature = "encoding")]
+mod encoding;
+#[cfg(feature = "libc")]
+mod errno;
 mod error;
+mod extend;
+mod extend_guard;
+#[cfg(feature = "hashbrown")]
+mod hash_map;
+mod log_buffer;
+mod map;
+mod multipeek;
+#[cfg(feature = "allocator_api")]
+mod persistent;
+mod reserve;
+mod rle;
 mod set_len_on_drop;
+#[cfg(feature = "allocator_api")]
+mod shared;
+mod shrink;
+mod slice;
+mod slot_map;
+mod sort;
+mod sorted;
+mod spare;
+mod string;
+mod zeroed;
+mod zeroizing;
 
-use alloc::{collections::TryReserveError, vec::Vec};
+use alloc::{alloc::Layout, collections::TryReserveError, vec::Vec};
+use core::ops::{Bound, Range, RangeBounds};
+use error::capacity_overflow;
 use set_len_on_drop::SetLenOnDrop;
 
 #[cfg(feature = "allocator_api")]
 use core::alloc::Allocator;
 
+pub use array::TryIntoVec;
+#[cfg(feature = "allocator_api")]
+pub use boxed::try_boxed_slice_from_in;
+pub use boxed::{try_boxed_slice_from, try_concat_boxed, try_into_boxed_slice, try_push_boxed};
+pub use bytes::{FallibleByteSlice, FallibleByteVec};
+pub use chunks::{TryChunks, TryChunksIter, TryIntoChunks};
+pub use clone::{TryClone, TryToOwned};
 pub use collect::TryCollect;
-pub use error::alloc_error;
+pub use concat::try_concat_vecs;
+#[cfg(feature = "encoding")]
+pub use encoding::{
+    try_base64_decode, try_base64_decode_into, try_base64_encode, try_base64_encode_into,
+    try_hex_decode, try_hex_decode_into, try_hex_encode, try_hex_encode_into, DecodeError,
+};
+#[cfg(feature = "libc")]
+pub use errno::{try_reserve_error_from_errno, ToErrno};
+pub use error::{alloc_error, ExtendError, FallibleVecError, ItemOrAllocError};
+pub use extend::TryExtend;
+pub use extend_guard::{ExtendGuard, ReserveGuard};
+#[cfg(feature = "hashbrown")]
+pub use hash_map::TryIntoGroupMap;
+pub use log_buffer::{BoundedLogBuffer, OverflowPolicy};
+pub use map::{try_map, try_map_in_place};
+pub use multipeek::{TryMultiPeek, TryMultiPeekIter};
+#[cfg(feature = "allocator_api")]
+pub use persistent::PersistentVec;
+pub use reserve::{try_reserve_all, Reservable};
+pub use rle::{try_rle_decode, try_rle_encode};
+#[cfg(feature = "allocator_api")]
+pub use rle::{try_rle_decode_in, try_rle_encode_in};
+#[cfg(feature = "allocator_api")]
+pub use shared::TryMakeMut;
+pub use shrink::{shrink_on_drop, shrink_on_drop_with, ShrinkOnDrop};
+pub use slice::{try_concat, try_interleave, try_join, try_transpose, FallibleSlice, TryToVec};
+#[cfg(feature = "allocator_api")]
+pub use slice::{try_concat_in, try_interleave_in, try_join_in, try_transpose_in};
+pub use slot_map::{SlotKey, SlotMap};
+pub use sort::FallibleSliceSort;
+pub use sorted::{
+    try_merge_sorted, try_merge_sorted_by_key, try_sorted_difference, try_sorted_intersection,
+    try_sorted_symmetric_difference, try_sorted_union,
+};
+#[cfg(feature = "allocator_api")]
+pub use sorted::{
+    try_merge_sorted_by_key_in, try_merge_sorted_in, try_sorted_difference_in,
+    try_sorted_intersection_in, try_sorted_symmetric_difference_in, try_sorted_union_in,
+};
+pub use spare::SpareCapacityWriter;
+pub use string::{try_escape_debug, try_escape_default, FallibleString};
+#[cfg(feature = "allocator_api")]
+pub use zeroed::try_vec_zeroed_in;
+pub use zeroed::{try_vec_zeroed, Zeroable};
+pub use zeroizing::ZeroizingVec;
 
 // These are defined so that the try_vec! and try_vec_in! macros can refer to
 // these types in a consistent way: even if the consuming crate doesn't use
@@ -277,194 +369,2386 @@ pub trait FallibleVec<T>: Sized {
     fn try_resize(&mut self, new_len: usize, item: T) -> Result<(), TryReserveError>
     where
         T: Clone;
-}
 
-macro_rules! impl_trait_for_vec {
-    { impl $trait:ident $impl:tt } => {
-        #[cfg(not(feature = "allocator_api"))]
-        impl<T> $trait<T> for Vec<T> $impl
+    /// Removes the elements at each of the positions in `indices` and returns
+    /// them, in order, in a new `Vec`.
+    ///
+    /// The remaining elements are compacted in a single pass, so removing `k`
+    /// elements is *O*(*n*) rather than the *O*(*k*·*n*) of calling
+    /// [`Vec::remove`] repeatedly.
+    ///
+    /// If allocating the returned `Vec` fails, then `self` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indices` is not sorted in strictly ascending order (i.e., it
+    /// contains duplicates) or if any index is greater than or equal to `len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2, 3, 4, 5, 6]?;
+    /// let removed = vec.try_remove_indices(&[0, 2, 3])?;
+    /// assert_eq!(vec, [2, 5, 6]);
+    /// assert_eq!(removed, [1, 3, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_remove_indices(&mut self, indices: &[usize]) -> Result<Self, TryReserveError>
+    where
+        Self: CloneAllocator;
 
-        #[cfg(feature = "allocator_api")]
-        impl<T, A: Allocator> $trait<T> for Vec<T, A> $impl
-    }
-}
+    /// Removes the items in `range` from the `Vec` and returns them in a new
+    /// `Vec` using the provided allocator.
+    ///
+    /// This is the fallible equivalent of `vec.drain(range).collect()`: the
+    /// new `Vec` is allocated before anything is removed, so if the allocation
+    /// fails then `self` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut vec = try_vec![1, 2, 3, 4, 5]?;
+    /// let drained = vec.try_drain_collect_in(1..3, System)?;
+    /// assert_eq!(vec, [1, 4, 5]);
+    /// assert_eq!(drained, [2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_drain_collect_in<A2: Allocator>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        alloc: A2,
+    ) -> Result<Vec<T, A2>, TryReserveError>;
 
-impl_trait_for_vec! {
-    impl FallibleVec {
-        fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
-            let iter = iter.into_iter();
-            let (low_bound, _upper_bound) = iter.size_hint();
-            self.try_reserve(low_bound)?;
-            for item in iter {
-                self.try_push(item)?;
-            }
-            Ok(())
-        }
+    /// Splits the collection into two at the given index, moving the tail into
+    /// a new `Vec` that uses the provided allocator.
+    ///
+    /// Returns a newly allocated vector containing the elements in the range
+    /// `[at, len)`. After the call, the original vector will be left containing
+    /// the elements `[0, at)` with its previous capacity unchanged.
+    ///
+    /// The new `Vec` is allocated before anything is moved, so if the
+    /// allocation fails then `self` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut vec = try_vec![1, 2, 3]?;
+    /// let vec2 = vec.try_split_off_in(1, System)?;
+    /// assert_eq!(vec, [1]);
+    /// assert_eq!(vec2, [2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_split_off_in<A2: Allocator>(
+        &mut self,
+        at: usize,
+        alloc: A2,
+    ) -> Result<Vec<T, A2>, TryReserveError>;
 
-        fn try_extend_from_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError>
-        where
-            T: Clone,
-        {
-            self.try_reserve(slice.len())?;
-            let ptr = self.as_mut_ptr();
-            let mut local_len = SetLenOnDrop::new(self);
-            for item in slice.iter() {
-                unsafe {
-                    ptr.add(local_len.current_len()).write(item.clone());
-                }
-                local_len.increment_len(1);
-            }
+    /// Replaces the elements in `range` with clones of the elements in
+    /// `replace_with`.
+    ///
+    /// Unlike [`try_splice_in`], this works out the exact change in length up
+    /// front, so it makes at most one reservation and moves the elements after
+    /// `range` at most once, without needing a temporary `Vec`.
+    ///
+    /// # Panic safety
+    ///
+    /// If dropping one of the removed elements or a call to `clone` panics,
+    /// then the elements after `range` will be leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = try_vec![1, 2, 3, 4]?;
+    /// v.try_splice_from_slice(1..3, &[7, 8, 9])?;
+    /// assert_eq!(v, [1, 7, 8, 9, 4]);
+    /// v.try_splice_from_slice(..2, &[])?;
+    /// assert_eq!(v, [8, 9, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    ///
+    /// [`try_splice_in`]: Vec::try_splice_in
+    fn try_splice_from_slice(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: &[T],
+    ) -> Result<(), TryReserveError>
+    where
+        T: Clone;
 
-            Ok(())
-        }
+    /// Returns the index of the first element in the `Vec` that `eq`
+    /// considers equal to `item`, or appends `item` and returns its index if
+    /// there is no such element.
+    ///
+    /// `eq` is called with each existing element and `item`, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut names = Vec::new();
+    /// assert_eq!(names.try_get_or_push("a", |x, y| x == y)?, 0);
+    /// assert_eq!(names.try_get_or_push("b", |x, y| x == y)?, 1);
+    /// assert_eq!(names.try_get_or_push("a", |x, y| x == y)?, 0);
+    /// assert_eq!(names, ["a", "b"]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_get_or_push<F: FnMut(&T, &T) -> bool>(
+        &mut self,
+        item: T,
+        eq: F,
+    ) -> Result<usize, TryReserveError>;
 
-        fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
-            self.try_reserve(1)?;
-            unsafe {
-                self.as_mut_ptr().add(self.len()).write(item);
-                self.set_len(self.len() + 1);
-            }
-            Ok(())
-        }
+    /// Returns the index of the first element in the `Vec` that matches
+    /// `is_match`, or appends the item returned by `f` and returns its index if
+    /// there is no such element.
+    ///
+    /// `f` is only called if no element matches, so the new item is only
+    /// constructed if it is needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut interned: Vec<String> = Vec::new();
+    /// let index = interned.try_get_or_push_with(|s| s == "key", || String::from("key"))?;
+    /// assert_eq!(index, 0);
+    /// let index = interned.try_get_or_push_with(|s| s == "key", || unreachable!())?;
+    /// assert_eq!(index, 0);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_get_or_push_with<P: FnMut(&T) -> bool, F: FnOnce() -> T>(
+        &mut self,
+        is_match: P,
+        f: F,
+    ) -> Result<usize, TryReserveError>;
 
-        fn try_insert(&mut self, index: usize, element: T) -> Result<(), TryReserveError> {
-            self.move_tail(index, 1)?;
-            unsafe {
-                self.as_mut_ptr().add(index).write(element);
-                self.set_len(self.len() + 1);
-            }
-            Ok(())
-        }
+    /// Resizes the `Vec` in-place so that `len` is equal to `new_len`, filling
+    /// any new slots with the items returned by the fallible closure `f`.
+    ///
+    /// If `new_len` is less than `len`, the `Vec` is simply truncated and `f`
+    /// is never called.
+    ///
+    /// If `f` returns an error, then resizing stops: the `Vec` is left with
+    /// the items added so far and the error is returned as
+    /// [`ItemOrAllocError::Item`].
+    ///
+    /// # Panic safety
+    ///
+    /// If `f` panics, then all of the items previously returned by `f` will
+    /// have been added to the `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut readings = [3, 2, 1].into_iter();
+    /// let mut vec = Vec::new();
+    /// let result = vec.try_resize_with_fallible(5, || readings.next().ok_or("sensor offline"));
+    /// assert_eq!(result, Err(ItemOrAllocError::Item("sensor offline")));
+    /// assert_eq!(vec, [3, 2, 1]);
+    /// ```
+    fn try_resize_with_fallible<E, F: FnMut() -> Result<T, E>>(
+        &mut self,
+        new_len: usize,
+        f: F,
+    ) -> Result<(), ItemOrAllocError<E>>;
 
-        fn try_resize(&mut self, new_len: usize, item: T) -> Result<(), TryReserveError>
-        where
-            T: Clone,
-        {
-            #[allow(clippy::comparison_chain)]
-            if new_len < self.len() {
-                self.truncate(new_len);
-            } else if new_len > self.len() {
-                self.try_reserve(new_len - self.len())?;
-                let ptr = self.as_mut_ptr();
-                let mut local_len = SetLenOnDrop::new(self);
-                loop {
-                    unsafe {
-                        ptr.add(local_len.current_len()).write(item.clone());
-                    }
-                    local_len.increment_len(1);
-                    if local_len.current_len() == new_len {
-                        break;
-                    }
-                }
-            }
-            Ok(())
-        }
+    /// Extends the `Vec` using the `Ok` items from the given iterator,
+    /// stopping at the first `Err`.
+    ///
+    /// On success, returns the number of items that were appended. On failure,
+    /// returns the number of items that were appended before the failure
+    /// along with the error: either the `Err` from the iterator or the failure
+    /// to allocate space for an item.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `next()` on `iter` panics, then all of the items previously
+    /// returned from the iterator will be added to the `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = Vec::new();
+    /// assert_eq!(vec.try_extend_fallible([Ok::<_, &str>(1), Ok(2)]), Ok(2));
+    /// assert_eq!(
+    ///     vec.try_extend_fallible([Ok(3), Err("bad input"), Ok(4)]),
+    ///     Err((1, ItemOrAllocError::Item("bad input")))
+    /// );
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// ```
+    fn try_extend_fallible<E, I: IntoIterator<Item = Result<T, E>>>(
+        &mut self,
+        iter: I,
+    ) -> Result<usize, (usize, ItemOrAllocError<E>)>;
 
-        fn try_resize_with<F: FnMut() -> T>(
-            &mut self,
-            new_len: usize,
-            mut f: F,
-        ) -> Result<(), TryReserveError> {
-            #[allow(clippy::comparison_chain)]
-            if new_len < self.len() {
-                self.truncate(new_len);
-            } else if new_len > self.len() {
-                self.try_reserve(new_len - self.len())?;
-                let ptr = self.as_mut_ptr();
-                let mut local_len = SetLenOnDrop::new(self);
-                loop {
-                    let item = f();
-                    // Immediately set the length, to protect against panics that occur when calling 'f'.
-                    unsafe {
-                        ptr.add(local_len.current_len()).write(item);
-                    }
-                    local_len.increment_len(1);
-                    if local_len.current_len() == new_len {
-                        break;
-                    }
-                }
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// Unlike `Vec::append`, `other` may use a different allocator to `self`.
+    /// The elements are moved with a single copy once space has been reserved
+    /// in `self`. If the reservation fails, then both `Vec`s are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut vec = try_vec![1, 2]?;
+    /// let mut staging = try_vec_in![3, 4 => System]?;
+    /// vec.try_append_from(&mut staging)?;
+    /// assert_eq!(vec, [1, 2, 3, 4]);
+    /// assert!(staging.is_empty());
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_append_from<B: Allocator>(
+        &mut self,
+        other: &mut Vec<T, B>,
+    ) -> Result<(), TryReserveError>;
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// The elements are moved with a single copy once space has been reserved
+    /// in `self`. If the reservation fails, then both `Vec`s are unchanged.
+    ///
+    /// # Panic safety
+    ///
+    /// This method does not call any user code (e.g., `clone` or `drop`), so
+    /// it cannot panic partway through moving the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2]?;
+    /// let mut other = try_vec![3, 4]?;
+    /// vec.try_append(&mut other)?;
+    /// assert_eq!(vec, [1, 2, 3, 4]);
+    /// assert!(other.is_empty());
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_append(&mut self, other: &mut Self) -> Result<(), TryReserveError>;
+
+    /// Splits the collection into two at the given index, moving the tail into
+    /// a new `Vec` that uses the same allocator as `self`.
+    ///
+    /// Returns a newly allocated vector containing the elements in the range
+    /// `[at, len)`. After the call, the original vector will be left containing
+    /// the elements `[0, at)` with its previous capacity unchanged.
+    ///
+    /// The new `Vec` is allocated before anything is moved, so if the
+    /// allocation fails then `self` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    ///
+    /// # Panic safety
+    ///
+    /// The index is checked before anything is allocated or moved, and no user
+    /// code (e.g., `clone` or `drop`) is called while moving the elements, so
+    /// `self` is never left partially split.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2, 3]?;
+    /// let vec2 = vec.try_split_off(1)?;
+    /// assert_eq!(vec, [1]);
+    /// assert_eq!(vec2, [2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_split_off(&mut self, at: usize) -> Result<Self, TryReserveError>
+    where
+        Self: CloneAllocator;
+
+    /// Clones the elements in `src` and appends them to the end of the `Vec`.
+    ///
+    /// This is the fallible equivalent of `Vec::extend_from_within`, and is
+    /// useful for copying back-references in decompressors. Space for all of
+    /// the clones is reserved up front, so if the reservation fails then the
+    /// `Vec` is unchanged. For `Copy` types,
+    /// [`try_extend_from_within_copy`](FallibleVec::try_extend_from_within_copy)
+    /// copies all of the items at once instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end
+    /// point is greater than the length of the `Vec`.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then all of the items cloned before the
+    /// panicking item will have been added to the `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![0, 1, 2, 3, 4]?;
+    /// vec.try_extend_from_within(2..)?;
+    /// assert_eq!(vec, [0, 1, 2, 3, 4, 2, 3, 4]);
+    /// vec.try_extend_from_within(..2)?;
+    /// assert_eq!(vec, [0, 1, 2, 3, 4, 2, 3, 4, 0, 1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_within<R: RangeBounds<usize>>(
+        &mut self,
+        src: R,
+    ) -> Result<(), TryReserveError>
+    where
+        T: Clone;
+
+    /// Copies the elements in `src` and appends them to the end of the `Vec`.
+    ///
+    /// This is the same as
+    /// [`try_extend_from_within`](FallibleVec::try_extend_from_within), except
+    /// that it requires `T: Copy` so that the items can be appended with a
+    /// single `memcpy` rather than being cloned one at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end
+    /// point is greater than the length of the `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut window = try_vec![b'a', b'b', b'c']?;
+    /// window.try_extend_from_within_copy(1..)?;
+    /// assert_eq!(window, b"abcbc");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_within_copy<R: RangeBounds<usize>>(
+        &mut self,
+        src: R,
+    ) -> Result<(), TryReserveError>
+    where
+        T: Copy;
+
+    /// Inserts clones of the elements in `slice` at position `index` within
+    /// the vector, shifting all elements after it to the right.
+    ///
+    /// This makes a single reservation, moves the elements after `index`
+    /// once, and then clones the elements of `slice` directly into place. If
+    /// the reservation fails, then the `Vec` is unchanged.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the elements after `index` will be
+    /// leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 5]?;
+    /// vec.try_insert_slice(1, &[2, 3, 4])?;
+    /// assert_eq!(vec, [1, 2, 3, 4, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_insert_slice(&mut self, index: usize, slice: &[T]) -> Result<(), TryReserveError>
+    where
+        T: Clone;
+
+    /// Inserts the items from an exact-size iterator at position `index`
+    /// within the vector, shifting all elements after it to the right.
+    ///
+    /// The length of the iterator is used to make a single reservation and
+    /// to move the elements after `index` once, so unlike [`try_splice_in`]
+    /// no temporary `Vec` (or allocator for it) is needed. If the reservation
+    /// fails, then the `Vec` is unchanged.
+    ///
+    /// If the iterator produces fewer items than its reported length, then
+    /// only those items are inserted; any items beyond its reported length are
+    /// not consumed.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `next()` on `iter` panics, then the items previously
+    /// returned from the iterator will be added to the `Vec`, and the elements
+    /// after `index` will be leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 5]?;
+    /// vec.try_insert_many(1, (2..5).map(|i| i * 10))?;
+    /// assert_eq!(vec, [1, 20, 30, 40, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    ///
+    /// [`try_splice_in`]: Vec::try_splice_in
+    fn try_insert_many<I>(&mut self, index: usize, iter: I) -> Result<(), TryReserveError>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator;
+
+    /// Removes the items in `range` from the `Vec` and returns them in a new
+    /// `Vec` that uses the same allocator as `self`.
+    ///
+    /// This is the fallible equivalent of `vec.drain(range).collect()`: the
+    /// new `Vec` is allocated before anything is removed, so if the allocation
+    /// fails then `self` is left unchanged. To place the removed items in a
+    /// different allocator, use [`try_drain_collect_in`] instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2, 3, 4, 5]?;
+    /// let drained = vec.try_drain_into(1..3)?;
+    /// assert_eq!(vec, [1, 4, 5]);
+    /// assert_eq!(drained, [2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    ///
+    /// [`try_drain_collect_in`]: Vec::try_drain_collect_in
+    fn try_drain_into(&mut self, range: impl RangeBounds<usize>) -> Result<Self, TryReserveError>
+    where
+        Self: CloneAllocator;
+
+    /// Appends `n` copies of `item` to the `Vec`.
+    ///
+    /// This is like [`try_resize`](FallibleVec::try_resize) with a new length
+    /// of `len + n`, but without the risk of truncating due to a miscalculated
+    /// length. Space for all of the items is reserved up front, so if the
+    /// reservation fails then the `Vec` is unchanged. `item` is cloned `n - 1`
+    /// times and then moved into the last slot (or dropped if `n` is 0).
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` for `item` panics, then the `Vec` will be partially
+    /// extended with all of the items cloned before the panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1]?;
+    /// vec.try_extend_repeat(0, 3)?;
+    /// assert_eq!(vec, [1, 0, 0, 0]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_repeat(&mut self, item: T, n: usize) -> Result<(), TryReserveError>
+    where
+        T: Clone;
+
+    /// Appends `n` values returned from calling `f` to the `Vec`.
+    ///
+    /// This is like [`try_resize_with`](FallibleVec::try_resize_with) with a
+    /// new length of `len + n`, but without the risk of truncating due to a
+    /// miscalculated length. Space for all of the values is reserved up front,
+    /// so if the reservation fails then `f` is never called and the `Vec` is
+    /// unchanged.
+    ///
+    /// # Panic safety
+    ///
+    /// If `f` panics, then the `Vec` will be partially extended with all of
+    /// the values returned before the panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1]?;
+    /// let mut p = 1;
+    /// vec.try_extend_with(3, || { p *= 2; p })?;
+    /// assert_eq!(vec, [1, 2, 4, 8]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_with<F: FnMut() -> T>(&mut self, n: usize, f: F) -> Result<(), TryReserveError>;
+
+    /// Resizes the `Vec` in-place so that `len` is equal to `new_len`, filling
+    /// any new slots with zeros.
+    ///
+    /// This is equivalent to `try_resize(new_len, 0)` for types like `u8`, but
+    /// the new slots are zeroed in bulk (with a single `memset`) rather than
+    /// by writing each item individually. If `new_len` is less than `len`,
+    /// the `Vec` is simply truncated. If the reservation fails, then the
+    /// `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = try_vec![1u8, 2]?;
+    /// buffer.try_resize_zeroed(5)?;
+    /// assert_eq!(buffer, [1, 2, 0, 0, 0]);
+    /// buffer.try_resize_zeroed(1)?;
+    /// assert_eq!(buffer, [1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_resize_zeroed(&mut self, new_len: usize) -> Result<(), TryReserveError>
+    where
+        T: Zeroable;
+
+    /// Shrinks the capacity of the `Vec` as much as possible.
+    ///
+    /// Unlike `Vec::shrink_to_fit`, a failure to reallocate the buffer into a
+    /// smaller allocation is returned as an error (in which case the `Vec` is
+    /// unchanged) instead of aborting.
+    ///
+    /// NOTE: Recent nightly compilers have an unstable inherent method with
+    /// the same name, so call this as `FallibleVec::try_shrink_to_fit(&mut vec)`
+    /// to avoid the ambiguity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_with_capacity(10)?;
+    /// vec.try_extend_from_slice(&[1, 2, 3])?;
+    /// FallibleVec::try_shrink_to_fit(&mut vec)?;
+    /// assert_eq!(vec.capacity(), 3);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_shrink_to_fit(&mut self) -> Result<(), TryReserveError>;
+
+    /// Shrinks the capacity of the `Vec` with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length and the
+    /// supplied value. If the current capacity is less than the lower bound,
+    /// this is a no-op.
+    ///
+    /// Unlike `Vec::shrink_to`, a failure to reallocate the buffer into a
+    /// smaller allocation is returned as an error (in which case the `Vec` is
+    /// unchanged) instead of aborting.
+    ///
+    /// NOTE: As with [`try_shrink_to_fit`](FallibleVec::try_shrink_to_fit),
+    /// call this as `FallibleVec::try_shrink_to(&mut vec, min_capacity)` to
+    /// avoid ambiguity with the unstable inherent method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_with_capacity(10)?;
+    /// vec.try_extend_from_slice(&[1, 2, 3])?;
+    /// FallibleVec::try_shrink_to(&mut vec, 4)?;
+    /// assert_eq!(vec.capacity(), 4);
+    /// FallibleVec::try_shrink_to(&mut vec, 0)?;
+    /// assert_eq!(vec.capacity(), 3);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), TryReserveError>;
+
+    /// Inserts an element into a sorted vector at the position that keeps it
+    /// sorted, returning the index it was inserted at.
+    ///
+    /// The position is found with a binary search, and the element is inserted
+    /// after any equal elements already in the vector. If the vector is not
+    /// sorted, then the position is unspecified (but the element is still
+    /// inserted).
+    ///
+    /// If reserving space for the element fails, then the vector is unchanged,
+    /// the element is dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 3, 5]?;
+    /// assert_eq!(vec.try_insert_sorted(4)?, 2);
+    /// assert_eq!(vec.try_insert_sorted(0)?, 0);
+    /// assert_eq!(vec, [0, 1, 3, 4, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_insert_sorted(&mut self, element: T) -> Result<usize, TryReserveError>
+    where
+        T: Ord;
+
+    /// Inserts an element into a vector that is sorted by the given comparator
+    /// function at the position that keeps it sorted, returning the index it
+    /// was inserted at.
+    ///
+    /// See [`try_insert_sorted`](FallibleVec::try_insert_sorted) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![5, 3, 1]?;
+    /// assert_eq!(vec.try_insert_sorted_by(4, |a, b| b.cmp(a))?, 1);
+    /// assert_eq!(vec, [5, 4, 3, 1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_insert_sorted_by<F: FnMut(&T, &T) -> core::cmp::Ordering>(
+        &mut self,
+        element: T,
+        compare: F,
+    ) -> Result<usize, TryReserveError>;
+
+    /// Inserts an element into a vector that is sorted by the given key
+    /// extraction function at the position that keeps it sorted, returning the
+    /// index it was inserted at.
+    ///
+    /// See [`try_insert_sorted`](FallibleVec::try_insert_sorted) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut map = try_vec![(1, "one"), (3, "three")]?;
+    /// assert_eq!(map.try_insert_sorted_by_key((2, "two"), |(key, _)| *key)?, 1);
+    /// assert_eq!(map, [(1, "one"), (2, "two"), (3, "three")]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_insert_sorted_by_key<K: Ord, F: FnMut(&T) -> K>(
+        &mut self,
+        element: T,
+        f: F,
+    ) -> Result<usize, TryReserveError>;
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// Unlike [`try_insert`](FallibleVec::try_insert), this never panics: if
+    /// `index > len` then the element is dropped and
+    /// [`FallibleVecError::IndexOutOfBounds`] is returned. The vector is
+    /// unchanged if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2, 3]?;
+    /// vec.checked_insert(1, 4)?;
+    /// assert_eq!(vec, [1, 4, 2, 3]);
+    /// assert_eq!(
+    ///     vec.checked_insert(5, 5),
+    ///     Err(FallibleVecError::IndexOutOfBounds { index: 5, len: 4 })
+    /// );
+    /// # Ok::<(), FallibleVecError>(())
+    /// ```
+    fn checked_insert(&mut self, index: usize, element: T) -> Result<(), FallibleVecError>;
+
+    /// Removes the items in `range` and replaces them with `replace_with` using
+    /// the provided allocator for temporary allocations.
+    ///
+    /// Unlike [`try_splice_in`](FallibleVec::try_splice_in), this doesn't
+    /// panic if `range` is invalid: [`FallibleVecError::RangeOutOfBounds`] is
+    /// returned instead (before anything is taken from `replace_with`).
+    ///
+    /// # Panic safety
+    ///
+    /// If `replace_with` panics on a call to `next()` then the items that were
+    /// previously returned by that iterator will either be added to the `Vec`
+    /// or dropped. Some of the items after the splicing point (i.e., the end of
+    /// `range`) in the `Vec` may be leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut v = try_vec_in![1, 2, 3, 4 => System]?;
+    /// v.checked_splice_in(1..3, [7, 8, 9], System)?;
+    /// assert_eq!(&v, &[1, 7, 8, 9, 4]);
+    /// assert_eq!(
+    ///     v.checked_splice_in(4..6, [10], System),
+    ///     Err(FallibleVecError::RangeOutOfBounds)
+    /// );
+    /// # Ok::<(), FallibleVecError>(())
+    /// ```
+    #[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
+    fn checked_splice_in<I: IntoIterator<Item = T>, A: Allocator>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: I,
+        alloc: A,
+    ) -> Result<(), FallibleVecError>;
+
+    /// Appends an element to the back of a collection, returning the element
+    /// along with the error if reserving space for it fails.
+    ///
+    /// Unlike [`try_push`](FallibleVec::try_push), the element isn't dropped
+    /// on failure, so it can be pushed again later (e.g., after freeing some
+    /// memory).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2]?;
+    /// vec.try_push_or_return(3).map_err(|(_, e)| e)?;
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_push_or_return(&mut self, item: T) -> Result<(), (T, TryReserveError)>;
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right, and returning the element along with
+    /// the error if reserving space for it fails.
+    ///
+    /// Unlike [`try_insert`](FallibleVec::try_insert), the element isn't
+    /// dropped on failure, so it can be inserted again later (e.g., after
+    /// freeing some memory).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1, 2, 3]?;
+    /// vec.try_insert_or_return(1, 4).map_err(|(_, e)| e)?;
+    /// assert_eq!(vec, [1, 4, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_insert_or_return(
+        &mut self,
+        index: usize,
+        element: T,
+    ) -> Result<(), (T, TryReserveError)>;
+
+    /// Extends the `Vec` using the items from an iterator, recording how far
+    /// it got if reserving space fails so that the extension can be resumed.
+    ///
+    /// This reserves space in the same way as
+    /// [`try_extend`](FallibleVec::try_extend), falling back to reserving
+    /// space for a single item if the iterator's lower bound cannot be
+    /// reserved. If that also fails, then the items already appended are kept
+    /// and an [`ExtendError`] is returned with the number of items appended,
+    /// the item that could not be appended and the rest of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = Vec::new();
+    /// if let Err(error) = vec.try_extend_resumable(1..=3) {
+    ///     // Free some memory, then try again with the remaining items.
+    ///     vec.try_extend(error.into_remaining())?;
+    /// }
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_resumable<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), ExtendError<T, I::IntoIter>>;
+
+    /// Appends an element to the back of the `Vec` if there is spare capacity,
+    /// otherwise returns the element.
+    ///
+    /// This never allocates (it is a stable equivalent of
+    /// `Vec::push_within_capacity`), so it can be combined with `try_reserve`
+    /// to write loops that are guaranteed not to allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_with_exact_capacity(2)?;
+    /// assert_eq!(vec.push_no_grow(1), Ok(()));
+    /// assert_eq!(vec.push_no_grow(2), Ok(()));
+    /// assert_eq!(vec.push_no_grow(3), Err(3));
+    /// assert_eq!(vec, [1, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn push_no_grow(&mut self, item: T) -> Result<(), T>;
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right, if there is spare capacity. Otherwise
+    /// the element is returned and the vector is unchanged.
+    ///
+    /// This never allocates, so it can be combined with `try_reserve` to write
+    /// code that is guaranteed not to allocate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_with_exact_capacity(3)?;
+    /// vec.try_extend_from_slice(&[1, 3])?;
+    /// assert_eq!(vec.insert_within_capacity(1, 2), Ok(()));
+    /// assert_eq!(vec.insert_within_capacity(0, 0), Err(0));
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn insert_within_capacity(&mut self, index: usize, element: T) -> Result<(), T>;
+
+    /// Clones the `Vec` into a new `Vec` that uses the provided allocator.
+    ///
+    /// The new `Vec` is allocated with exactly the required capacity before
+    /// any items are cloned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the new `Vec` and any items already
+    /// cloned into it are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let vec = try_vec![1, 2, 3]?;
+    /// let clone = vec.try_clone_in(System)?;
+    /// assert_eq!(clone, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_clone_in<B: Allocator>(&self, alloc: B) -> Result<Vec<T, B>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Replaces the contents of `dest` with clones of the items in the `Vec`,
+    /// reusing the existing allocation of `dest` (i.e., a fallible equivalent
+    /// of `dest.clone_from(self)`).
+    ///
+    /// `dest` only reallocates if its capacity is less than the length of the
+    /// `Vec`, and it is unchanged if that reallocation fails.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then `dest` will contain the items cloned
+    /// before the panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut dest = try_with_capacity(10)?;
+    /// dest.try_push(0)?;
+    /// try_vec![1, 2, 3]?.try_clone_into(&mut dest)?;
+    /// assert_eq!(dest, [1, 2, 3]);
+    /// assert!(dest.capacity() >= 10);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_clone_into(&self, dest: &mut Self) -> Result<(), TryReserveError>
+    where
+        T: Clone;
+
+    /// Moves the items of the `Vec` into a new `Vec` that uses the provided
+    /// allocator, then frees the original allocation.
+    ///
+    /// The new `Vec` is allocated with exactly the required capacity. If that
+    /// fails, then the original `Vec` is returned unchanged along with the
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let vec = try_vec![1, 2, 3]?;
+    /// let vec = vec.try_migrate_allocator(System).map_err(|(_, e)| e)?;
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_migrate_allocator<B: Allocator>(
+        self,
+        alloc: B,
+    ) -> Result<Vec<T, B>, (Self, TryReserveError)>;
+
+    /// Removes the items in `range`, replaces them with `replace_with` and
+    /// returns the removed items in a new `Vec` that uses the provided
+    /// allocator (which is also used for temporary allocations).
+    ///
+    /// The `Vec` for the removed items is allocated first: if that fails, then
+    /// an error is returned before anything is removed. If inserting the
+    /// replacement items then fails, any replacement items that were already
+    /// inserted are dropped and the removed items are moved back, so the `Vec`
+    /// is unchanged.
+    ///
+    /// # Panic safety
+    ///
+    /// If `replace_with` panics on a call to `next()` then the items that were
+    /// previously returned by that iterator will either be added to the `Vec`
+    /// or dropped, and the removed items are dropped. Some of the items after
+    /// the splicing point (i.e., the end of `range`) in the `Vec` may be
+    /// leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut v = try_vec_in![1, 2, 3, 4 => System]?;
+    /// let removed = v.try_splice_out_in(1..3, [7, 8, 9], System)?;
+    /// assert_eq!(v, [1, 7, 8, 9, 4]);
+    /// assert_eq!(removed, [2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
+    fn try_splice_out_in<I: IntoIterator<Item = T>, A: Allocator>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: I,
+        alloc: A,
+    ) -> Result<Vec<T, A>, TryReserveError>;
+
+    /// Removes the items in `range` and replaces them with the items from an
+    /// iterator of known length.
+    ///
+    /// Unlike [`try_splice_in`](FallibleVec::try_splice_in), this doesn't need
+    /// any temporary allocations: space for the replacement items is reserved
+    /// once (before anything is removed, so the `Vec` is unchanged if that
+    /// fails), the items after `range` are moved once and the replacement
+    /// items are written directly into place.
+    ///
+    /// If the iterator produces fewer items than its `len()`, then the gap is
+    /// closed; any items beyond its `len()` are not taken from it.
+    ///
+    /// # Panic safety
+    ///
+    /// If `replace_with` panics on a call to `next()` then the items that were
+    /// previously returned by that iterator will be added to the `Vec`. The
+    /// items after the splicing point (i.e., the end of `range`) in the `Vec`
+    /// will be leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = try_vec![1, 2, 3, 4]?;
+    /// v.try_splice_exact(1..3, [7, 8, 9])?;
+    /// assert_eq!(v, [1, 7, 8, 9, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_splice_exact<I>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: I,
+    ) -> Result<(), TryReserveError>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator;
+
+    /// Removes the items in `range` and replaces them with `replace_with`.
+    ///
+    /// Unlike [`try_splice_in`](FallibleVec::try_splice_in), this doesn't
+    /// need an allocator for temporary allocations: the replacement items are
+    /// first collected into a temporary `Vec` that uses a clone of this
+    /// `Vec`'s allocator, and then moved into place. If an allocation fails,
+    /// then the `Vec` is unchanged.
+    ///
+    /// # Panic safety
+    ///
+    /// If `replace_with` panics on a call to `next()` then the items that were
+    /// previously returned by that iterator will be dropped and the `Vec` is
+    /// unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = try_vec![1, 2, 3, 4]?;
+    /// v.try_splice(1..3, (7..).take(3))?;
+    /// assert_eq!(v, [1, 7, 8, 9, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_splice<I: IntoIterator<Item = T>>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: I,
+    ) -> Result<(), TryReserveError>
+    where
+        Self: CloneAllocator;
+
+    /// Copies and appends all elements in a slice to the `Vec`.
+    ///
+    /// This is the same as
+    /// [`try_extend_from_slice`](FallibleVec::try_extend_from_slice), except
+    /// that it requires `T: Copy` so that the items can be appended with a
+    /// single `memcpy` rather than being cloned one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = try_vec![0u8; 4]?;
+    /// buffer.try_extend_from_copy_slice(b"data")?;
+    /// assert_eq!(buffer, [0, 0, 0, 0, b'd', b'a', b't', b'a']);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_copy_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError>
+    where
+        T: Copy;
+
+    /// Appends `n` zeroed items to the `Vec` (e.g., to pad a serialized
+    /// buffer to an alignment).
+    ///
+    /// This makes a single reservation and then zeroes the new items in bulk,
+    /// in the same way as [`try_resize_zeroed`](FallibleVec::try_resize_zeroed).
+    /// If the reservation fails, then the `Vec` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer = try_vec![1u8, 2, 3]?;
+    /// buffer.try_extend_zeroed(8 - buffer.len() % 8)?;
+    /// assert_eq!(buffer, [1, 2, 3, 0, 0, 0, 0, 0]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_zeroed(&mut self, n: usize) -> Result<(), TryReserveError>
+    where
+        T: Zeroable;
+
+    /// Replaces the contents of the `Vec` with the items from `iter`.
+    ///
+    /// The `Vec` is cleared and then extended in the same way as
+    /// [`try_extend`](FallibleVec::try_extend), so the existing allocation is
+    /// reused unless it is too small to hold all of the new items.
+    ///
+    /// # Panic safety
+    ///
+    /// If `iter` panics on a call to `next()` then the `Vec` will contain the
+    /// items that were previously returned by that iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut frame = try_vec![0; 16]?;
+    /// let capacity = frame.capacity();
+    /// frame.try_assign(1..=3)?;
+    /// assert_eq!(frame, [1, 2, 3]);
+    /// assert_eq!(frame.capacity(), capacity);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_assign<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError>;
+
+    /// Removes all of the items for which `pred` returns `true` and returns
+    /// them, in order, in a new `Vec`.
+    ///
+    /// The matching items are counted first so that the returned `Vec` can be
+    /// allocated before anything is removed: if that allocation fails, then
+    /// `self` is left unchanged. As a result, `pred` is called twice for each
+    /// item (and if it doesn't return the same results both times, then no
+    /// more items are removed than were counted).
+    ///
+    /// # Panic safety
+    ///
+    /// If `pred` panics, then the items that were already removed are dropped
+    /// and the items that had not yet been checked are leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut numbers = try_vec![1, 2, 3, 4, 5, 6]?;
+    /// let evens = numbers.try_extract_if_collect(|x| x % 2 == 0)?;
+    /// assert_eq!(numbers, [1, 3, 5]);
+    /// assert_eq!(evens, [2, 4, 6]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extract_if_collect<F: FnMut(&T) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> Result<Self, TryReserveError>
+    where
+        Self: CloneAllocator;
+
+    /// Resizes the `Vec` in-place so that `len` is equal to `new_len`, filling
+    /// each new slot with the result of calling `f` with its index.
+    ///
+    /// This is the same as [`try_resize_with`](FallibleVec::try_resize_with),
+    /// except that `f` is passed the index of the slot being filled. If
+    /// `new_len` is less than `len`, the `Vec` is simply truncated.
+    ///
+    /// # Panic safety
+    ///
+    /// If `f` panics, then the `Vec` will contain all of the items created
+    /// before the panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut squares = try_vec![0]?;
+    /// squares.try_resize_with_index(5, |i| i * i)?;
+    /// assert_eq!(squares, [0, 1, 4, 9, 16]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_resize_with_index<F: FnMut(usize) -> T>(
+        &mut self,
+        new_len: usize,
+        f: F,
+    ) -> Result<(), TryReserveError>;
+
+    /// Extends the `Vec` using the items from an iterator that knows its
+    /// exact length.
+    ///
+    /// Space for all of the items is reserved once up front and then the items
+    /// are written directly into the `Vec`, without checking the capacity for
+    /// each item. If the reservation fails, then the `Vec` is unchanged.
+    ///
+    /// An incorrect `ExactSizeIterator` implementation can't cause undefined
+    /// behavior: at most `len()` items are taken from the iterator, and if it
+    /// produces fewer then only those items are added.
+    ///
+    /// # Panic safety
+    ///
+    /// If `iter` panics on a call to `next()` then the `Vec` will contain the
+    /// items that were previously returned by that iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # #[macro_use] extern crate fallible_vec;
+    /// use fallible_vec::*;
+    ///
+    /// let mut vec = try_vec![1]?;
+    /// vec.try_extend_from_iter_exact([2, 3, 4].iter().map(|x| x * 10))?;
+    /// assert_eq!(vec, [1, 20, 30, 40]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_iter_exact<I: ExactSizeIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError>;
+
+    /// Reserves capacity for `additional` more items, growing the `Vec` by at
+    /// most `increment` items at a time.
+    ///
+    /// Growing in smaller steps allows a caller to make progress (or to give
+    /// up gracefully) when a single huge reservation would fail. If one of the
+    /// steps fails, then the capacity obtained by the earlier steps is kept
+    /// and the error is returned along with the number of items that can now
+    /// be added without reallocating (i.e., `capacity() - len()`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `increment` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// match buffer.try_reserve_in_increments(1 << 20, 64 << 10) {
+    ///     Ok(()) => assert!(buffer.capacity() >= 1 << 20),
+    ///     // Continue with whatever could be reserved.
+    ///     Err((available, _)) => assert_eq!(buffer.capacity(), available),
+    /// }
+    /// ```
+    fn try_reserve_in_increments(
+        &mut self,
+        additional: usize,
+        increment: usize,
+    ) -> Result<(), (usize, TryReserveError)>;
+}
+
+/// Creates new, empty `Vec`s that use the same allocator as an existing `Vec`.
+///
+/// Methods on [`FallibleVec`] that return a new `Vec` in the same allocator as
+/// `self` (e.g., [`try_remove_indices`](FallibleVec::try_remove_indices))
+/// require this trait. It is implemented for every `Vec` whose allocator
+/// implements [`Clone`].
+pub trait CloneAllocator {
+    /// Creates a new, empty `Vec` using a clone of this `Vec`'s allocator.
+    ///
+    /// This does not allocate.
+    fn new_with_cloned_allocator(&self) -> Self;
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T> CloneAllocator for Vec<T> {
+    fn new_with_cloned_allocator(&self) -> Self {
+        Vec::new()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator + Clone> CloneAllocator for Vec<T, A> {
+    fn new_with_cloned_allocator(&self) -> Self {
+        Vec::new_in(self.allocator().clone())
+    }
+}
+
+macro_rules! impl_trait_for_vec {
+    { impl $trait:ident $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> $trait<T> for Vec<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> $trait<T> for Vec<T, A> $impl
+    }
+}
+
+impl_trait_for_vec! {
+    impl FallibleVec {
+        fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+            let mut iter = iter.into_iter();
+            // Don't trust the lower bound until the iterator has produced an
+            // item, so that an empty iterator never causes an allocation.
+            if let Some(first) = iter.next() {
+                let (low_bound, _upper_bound) = iter.size_hint();
+                self.try_reserve(low_bound.saturating_add(1))?;
+                self.try_push(first)?;
+                for item in iter {
+                    self.try_push(item)?;
+                }
+            }
+            Ok(())
+        }
+
+        fn try_extend_from_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            self.try_reserve(slice.len())?;
+            let ptr = self.as_mut_ptr();
+            let mut local_len = SetLenOnDrop::new(self);
+            for item in slice.iter() {
+                unsafe {
+                    ptr.add(local_len.current_len()).write(item.clone());
+                }
+                local_len.increment_len(1);
+            }
+
+            Ok(())
+        }
+
+        fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+            self.try_reserve(1)?;
+            unsafe {
+                self.as_mut_ptr().add(self.len()).write(item);
+                self.set_len(self.len() + 1);
+            }
+            Ok(())
+        }
+
+        fn try_insert(&mut self, index: usize, element: T) -> Result<(), TryReserveError> {
+            self.move_tail(index, 1)?;
+            unsafe {
+                self.as_mut_ptr().add(index).write(element);
+                self.set_len(self.len() + 1);
+            }
+            Ok(())
+        }
+
+        fn try_resize(&mut self, new_len: usize, item: T) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            #[allow(clippy::comparison_chain)]
+            if new_len < self.len() {
+                self.truncate(new_len);
+            } else if new_len > self.len() {
+                self.try_reserve(new_len - self.len())?;
+                let ptr = self.as_mut_ptr();
+                let mut local_len = SetLenOnDrop::new(self);
+                loop {
+                    unsafe {
+                        ptr.add(local_len.current_len()).write(item.clone());
+                    }
+                    local_len.increment_len(1);
+                    if local_len.current_len() == new_len {
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn try_resize_with<F: FnMut() -> T>(
+            &mut self,
+            new_len: usize,
+            mut f: F,
+        ) -> Result<(), TryReserveError> {
+            #[allow(clippy::comparison_chain)]
+            if new_len < self.len() {
+                self.truncate(new_len);
+            } else if new_len > self.len() {
+                self.try_reserve(new_len - self.len())?;
+                let ptr = self.as_mut_ptr();
+                let mut local_len = SetLenOnDrop::new(self);
+                loop {
+                    let item = f();
+                    // Immediately set the length, to protect against panics that occur when calling 'f'.
+                    unsafe {
+                        ptr.add(local_len.current_len()).write(item);
+                    }
+                    local_len.increment_len(1);
+                    if local_len.current_len() == new_len {
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        #[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
+        fn try_splice_in<I: IntoIterator<Item = T>, ATemp: Allocator>(
+            &mut self,
+            range: impl core::ops::RangeBounds<usize>,
+            replace_with: I,
+            alloc: ATemp,
+        ) -> Result<(), TryReserveError> {
+            let mut replace_with = replace_with.into_iter();
+            let core::ops::Range {
+                start: mut index,
+                end,
+            } = core::slice::range(range, ..self.len());
+
+            // Write over the items that need to be removed first.
+            while index < end {
+                if let Some(item) = replace_with.next() {
+                    self[index] = item;
+                    index += 1;
+                } else {
+                    // Nothing else to insert, drop the rest.
+                    self.drain(index..end);
+                    return Ok(());
+                }
+            }
+
+            // Only trust the lower bound once there's definitely another item,
+            // so that an empty replacement never causes an allocation.
+            let Some(next) = replace_with.next() else {
+                return Ok(());
+            };
+            let mut replace_with = core::iter::once(next).chain(replace_with);
+
+            // If we know roughly how many more there are, copy those directly.
+            let (lower_bound, ..) = replace_with.size_hint();
+            if lower_bound > 0 {
+                self.move_tail(index, lower_bound)?;
+
+                // Temporarily reduce the length: this will result in both the
+                // uninitialized memory AND the post-splice items being leaked if a
+                // call to next() panics.
+                let after_splice = self.len() - index;
+                unsafe {
+                    self.set_len(index);
+                }
+
+                {
+                    let ptr = self.as_mut_ptr();
+                    let mut local_len = SetLenOnDrop::new(self);
+                    loop {
+                        unsafe {
+                            ptr.add(local_len.current_len())
+                                .write(replace_with.next().unwrap());
+                        }
+                        local_len.increment_len(1);
+                        if local_len.current_len() == index + lower_bound {
+                            break;
+                        }
+                    }
+                }
+
+                // Update the index to insert at.
+                index += lower_bound;
+                // Fixup length to include the port-splice items.
+                unsafe {
+                    self.set_len(self.len() + after_splice);
+                }
+            }
+
+            // Gather up the remainder and copy those as well.
+            let remainder = replace_with.try_collect_in(alloc)?;
+            if !remainder.is_empty() {
+                self.move_tail(index, remainder.len())?;
+                // Don't need to use `SetLenOnDrop` here since we're enumerating
+                // over a Vec that we own.
+                unsafe {
+                    self.set_len(self.len() + remainder.len());
+                }
+                let ptr = unsafe { self.as_mut_ptr().add(index) };
+                for (i, item) in remainder.into_iter().enumerate() {
+                    unsafe { ptr.add(i).write(item) };
+                }
+            }
+
+            Ok(())
+        }
+
+        fn try_remove_indices(&mut self, indices: &[usize]) -> Result<Self, TryReserveError>
+        where
+            Self: CloneAllocator,
+        {
+            // Validate everything up front so that we never panic part way
+            // through compacting.
+            let len = self.len();
+            for (i, &index) in indices.iter().enumerate() {
+                assert!(
+                    index < len,
+                    "removal index (is {index}) should be < len (is {len})"
+                );
+                if i > 0 {
+                    assert!(
+                        indices[i - 1] < index,
+                        "removal indices must be sorted in ascending order without duplicates"
+                    );
+                }
+            }
+
+            let mut removed = self.new_with_cloned_allocator();
+            removed.try_reserve_exact(indices.len())?;
+
+            // No external code is called from here on, so there's nothing that
+            // can panic while the `Vec` is being compacted.
+            let ptr = self.as_mut_ptr();
+            let removed_ptr = removed.as_mut_ptr();
+            let mut write_index = indices.first().copied().unwrap_or(len);
+            for (i, &index) in indices.iter().enumerate() {
+                let next_index = indices.get(i + 1).copied().unwrap_or(len);
+                let to_keep = next_index - index - 1;
+                unsafe {
+                    removed_ptr.add(i).write(ptr.add(index).read());
+                    core::ptr::copy(ptr.add(index + 1), ptr.add(write_index), to_keep);
+                }
+                write_index += to_keep;
+            }
+
+            unsafe {
+                self.set_len(write_index);
+                removed.set_len(indices.len());
+            }
+            Ok(removed)
+        }
+
+        #[cfg(feature = "allocator_api")]
+        fn try_drain_collect_in<A2: Allocator>(
+            &mut self,
+            range: impl RangeBounds<usize>,
+            alloc: A2,
+        ) -> Result<Vec<T, A2>, TryReserveError> {
+            let Range { start, end } = slice_range(range, self.len());
+            let mut drained = try_with_capacity_in(end - start, alloc)?;
+            unsafe {
+                self.move_range_to(start..end, drained.as_mut_ptr());
+                drained.set_len(end - start);
+            }
+            Ok(drained)
+        }
+
+        #[cfg(feature = "allocator_api")]
+        fn try_split_off_in<A2: Allocator>(
+            &mut self,
+            at: usize,
+            alloc: A2,
+        ) -> Result<Vec<T, A2>, TryReserveError> {
+            let len = self.len();
+            assert!(at <= len, "`at` split index (is {at}) should be <= len (is {len})");
+            let mut other = try_with_capacity_in(len - at, alloc)?;
+            unsafe {
+                self.move_range_to(at..len, other.as_mut_ptr());
+                other.set_len(len - at);
+            }
+            Ok(other)
+        }
+
+        fn try_splice_from_slice(
+            &mut self,
+            range: impl RangeBounds<usize>,
+            replace_with: &[T],
+        ) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            let len = self.len();
+            let Range { start, end } = slice_range(range, len);
+            if replace_with.len() > end - start {
+                self.try_reserve(replace_with.len() - (end - start))?;
+            }
+
+            let tail_len = len - end;
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                // Truncate first so that the tail is leaked, rather than
+                // double-dropped, if anything below panics.
+                self.set_len(start);
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    ptr.add(start),
+                    end - start,
+                ));
+                core::ptr::copy(ptr.add(end), ptr.add(start + replace_with.len()), tail_len);
+            }
+
+            {
+                let mut local_len = SetLenOnDrop::new(self);
+                for item in replace_with {
+                    unsafe {
+                        ptr.add(local_len.current_len()).write(item.clone());
+                    }
+                    local_len.increment_len(1);
+                }
+            }
+
+            unsafe {
+                self.set_len(start + replace_with.len() + tail_len);
+            }
+            Ok(())
+        }
+
+        fn try_get_or_push<F: FnMut(&T, &T) -> bool>(
+            &mut self,
+            item: T,
+            mut eq: F,
+        ) -> Result<usize, TryReserveError> {
+            if let Some(index) = self.iter().position(|existing| eq(existing, &item)) {
+                return Ok(index);
+            }
+            self.try_push(item)?;
+            Ok(self.len() - 1)
+        }
+
+        fn try_get_or_push_with<P: FnMut(&T) -> bool, F: FnOnce() -> T>(
+            &mut self,
+            is_match: P,
+            f: F,
+        ) -> Result<usize, TryReserveError> {
+            if let Some(index) = self.iter().position(is_match) {
+                return Ok(index);
+            }
+            // Reserve before calling `f` so that the new item isn't created
+            // only to be dropped.
+            self.try_reserve(1)?;
+            self.try_push(f())?;
+            Ok(self.len() - 1)
+        }
+
+        fn try_resize_with_fallible<E, F: FnMut() -> Result<T, E>>(
+            &mut self,
+            new_len: usize,
+            mut f: F,
+        ) -> Result<(), ItemOrAllocError<E>> {
+            #[allow(clippy::comparison_chain)]
+            if new_len < self.len() {
+                self.truncate(new_len);
+            } else if new_len > self.len() {
+                self.try_reserve(new_len - self.len())?;
+                let ptr = self.as_mut_ptr();
+                let mut local_len = SetLenOnDrop::new(self);
+                loop {
+                    let item = f().map_err(ItemOrAllocError::Item)?;
+                    unsafe {
+                        ptr.add(local_len.current_len()).write(item);
+                    }
+                    local_len.increment_len(1);
+                    if local_len.current_len() == new_len {
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn try_extend_fallible<E, I: IntoIterator<Item = Result<T, E>>>(
+            &mut self,
+            iter: I,
+        ) -> Result<usize, (usize, ItemOrAllocError<E>)> {
+            let mut iter = iter.into_iter();
+            let original_len = self.len();
+            let appended = |vec: &Self| vec.len() - original_len;
+            // As with `try_extend`, only trust the lower bound once the
+            // iterator has produced an item.
+            if let Some(first) = iter.next() {
+                // Don't reserve anything if the first item is an error.
+                let first = match first {
+                    Ok(first) => first,
+                    Err(error) => return Err((0, ItemOrAllocError::Item(error))),
+                };
+                let (low_bound, _upper_bound) = iter.size_hint();
+                if let Err(error) = self.try_reserve(low_bound.saturating_add(1)) {
+                    return Err((0, ItemOrAllocError::Alloc(error)));
+                }
+                // Cannot fail as the space has already been reserved.
+                let _ = self.try_push(first);
+                for item in iter {
+                    let result = match item {
+                        Ok(item) => self.try_push(item).map_err(ItemOrAllocError::Alloc),
+                        Err(error) => Err(ItemOrAllocError::Item(error)),
+                    };
+                    if let Err(error) = result {
+                        return Err((appended(self), error));
+                    }
+                }
+            }
+            Ok(appended(self))
+        }
+
+        #[cfg(feature = "allocator_api")]
+        fn try_append_from<B: Allocator>(
+            &mut self,
+            other: &mut Vec<T, B>,
+        ) -> Result<(), TryReserveError> {
+            unsafe {
+                self.try_append_raw(other.as_ptr(), other.len())?;
+                other.set_len(0);
+            }
+            Ok(())
+        }
+
+        fn try_append(&mut self, other: &mut Self) -> Result<(), TryReserveError> {
+            unsafe {
+                self.try_append_raw(other.as_ptr(), other.len())?;
+                other.set_len(0);
+            }
+            Ok(())
+        }
+
+        fn try_split_off(&mut self, at: usize) -> Result<Self, TryReserveError>
+        where
+            Self: CloneAllocator,
+        {
+            let len = self.len();
+            assert!(at <= len, "`at` split index (is {at}) should be <= len (is {len})");
+            let mut other = self.new_with_cloned_allocator();
+            other.try_reserve_exact(len - at)?;
+            unsafe {
+                self.move_range_to(at..len, other.as_mut_ptr());
+                other.set_len(len - at);
+            }
+            Ok(other)
+        }
+
+        fn try_extend_from_within<R: RangeBounds<usize>>(
+            &mut self,
+            src: R,
+        ) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            let Range { start, end } = slice_range(src, self.len());
+            self.try_reserve(end - start)?;
+            let ptr = self.as_mut_ptr();
+            let mut local_len = SetLenOnDrop::new(self);
+            for index in start..end {
+                // SAFETY: The source items are before `len`, and the
+                // destination is in the reserved spare capacity, so they don't
+                // overlap.
+                unsafe {
+                    let item = (*ptr.add(index)).clone();
+                    ptr.add(local_len.current_len()).write(item);
+                }
+                local_len.increment_len(1);
+            }
+            Ok(())
+        }
+
+        fn try_extend_from_within_copy<R: RangeBounds<usize>>(
+            &mut self,
+            src: R,
+        ) -> Result<(), TryReserveError>
+        where
+            T: Copy,
+        {
+            let Range { start, end } = slice_range(src, self.len());
+            self.try_reserve(end - start)?;
+            let len = self.len();
+            let ptr = self.as_mut_ptr();
+            // SAFETY: The source items are before `len`, and the destination is
+            // in the reserved spare capacity, so they don't overlap.
+            unsafe {
+                core::ptr::copy_nonoverlapping(ptr.add(start), ptr.add(len), end - start);
+                self.set_len(len + end - start);
+            }
+            Ok(())
+        }
+
+        fn try_insert_slice(&mut self, index: usize, slice: &[T]) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            let len = self.len();
+            assert!(
+                index <= len,
+                "insertion index (is {index}) should be <= len (is {len})"
+            );
+            // Nothing is removed, so this is a single reservation and tail move.
+            self.try_splice_from_slice(index..index, slice)
+        }
+
+        fn try_insert_many<I>(&mut self, index: usize, iter: I) -> Result<(), TryReserveError>
+        where
+            I: IntoIterator<Item = T>,
+            I::IntoIter: ExactSizeIterator,
+        {
+            let len = self.len();
+            assert!(
+                index <= len,
+                "insertion index (is {index}) should be <= len (is {len})"
+            );
+            let iter = iter.into_iter();
+            let count = iter.len();
+            self.try_reserve(count)?;
+
+            let tail_len = len - index;
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                // Truncate first so that the tail is leaked, rather than
+                // double-dropped, if the iterator panics.
+                self.set_len(index);
+                core::ptr::copy(ptr.add(index), ptr.add(index + count), tail_len);
+            }
+
+            {
+                let mut local_len = SetLenOnDrop::new(self);
+                for item in iter.take(count) {
+                    unsafe {
+                        ptr.add(local_len.current_len()).write(item);
+                    }
+                    local_len.increment_len(1);
+                }
+            }
+
+            let inserted = self.len() - index;
+            unsafe {
+                if inserted < count {
+                    // The iterator was shorter than it claimed, so close the
+                    // gap before the tail.
+                    core::ptr::copy(ptr.add(index + count), ptr.add(index + inserted), tail_len);
+                }
+                self.set_len(len + inserted);
+            }
+            Ok(())
+        }
+
+        fn try_drain_into(&mut self, range: impl RangeBounds<usize>) -> Result<Self, TryReserveError>
+        where
+            Self: CloneAllocator,
+        {
+            let Range { start, end } = slice_range(range, self.len());
+            let mut drained = self.new_with_cloned_allocator();
+            drained.try_reserve_exact(end - start)?;
+            unsafe {
+                self.move_range_to(start..end, drained.as_mut_ptr());
+                drained.set_len(end - start);
+            }
+            Ok(drained)
+        }
+
+        fn try_extend_repeat(&mut self, item: T, n: usize) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            if n == 0 {
+                return Ok(());
+            }
+            self.try_reserve(n)?;
+            let ptr = self.as_mut_ptr();
+            let mut local_len = SetLenOnDrop::new(self);
+            for _ in 1..n {
+                unsafe {
+                    ptr.add(local_len.current_len()).write(item.clone());
+                }
+                local_len.increment_len(1);
+            }
+            // Move the original into the last slot, rather than cloning it.
+            unsafe {
+                ptr.add(local_len.current_len()).write(item);
+            }
+            local_len.increment_len(1);
+            Ok(())
+        }
+
+        fn try_extend_with<F: FnMut() -> T>(
+            &mut self,
+            n: usize,
+            mut f: F,
+        ) -> Result<(), TryReserveError> {
+            self.try_reserve(n)?;
+            let ptr = self.as_mut_ptr();
+            let mut local_len = SetLenOnDrop::new(self);
+            for _ in 0..n {
+                let item = f();
+                unsafe {
+                    ptr.add(local_len.current_len()).write(item);
+                }
+                local_len.increment_len(1);
+            }
+            Ok(())
+        }
+
+        fn try_resize_zeroed(&mut self, new_len: usize) -> Result<(), TryReserveError>
+        where
+            T: Zeroable,
+        {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+                return Ok(());
+            }
+            self.try_reserve(new_len - len)?;
+            // SAFETY: The space has been reserved, and all-zero is a valid `T`.
+            unsafe {
+                self.as_mut_ptr().add(len).write_bytes(0, new_len - len);
+                self.set_len(new_len);
+            }
+            Ok(())
+        }
+
+        fn try_shrink_to_fit(&mut self) -> Result<(), TryReserveError> {
+            self.try_shrink_to_internal(0)
+        }
+
+        fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), TryReserveError> {
+            self.try_shrink_to_internal(min_capacity)
+        }
+
+        fn try_insert_sorted(&mut self, element: T) -> Result<usize, TryReserveError>
+        where
+            T: Ord,
+        {
+            self.try_insert_sorted_by(element, T::cmp)
+        }
+
+        fn try_insert_sorted_by<F: FnMut(&T, &T) -> core::cmp::Ordering>(
+            &mut self,
+            element: T,
+            mut compare: F,
+        ) -> Result<usize, TryReserveError> {
+            let index = self.partition_point(|item| compare(item, &element).is_le());
+            self.try_insert(index, element)?;
+            Ok(index)
+        }
+
+        fn try_insert_sorted_by_key<K: Ord, F: FnMut(&T) -> K>(
+            &mut self,
+            element: T,
+            mut f: F,
+        ) -> Result<usize, TryReserveError> {
+            let key = f(&element);
+            let index = self.partition_point(|item| f(item) <= key);
+            self.try_insert(index, element)?;
+            Ok(index)
+        }
+
+        fn checked_insert(&mut self, index: usize, element: T) -> Result<(), FallibleVecError> {
+            let len = self.len();
+            if index > len {
+                return Err(FallibleVecError::IndexOutOfBounds { index, len });
+            }
+            self.try_insert(index, element)?;
+            Ok(())
+        }
+
+        #[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
+        fn checked_splice_in<I: IntoIterator<Item = T>, ATemp: Allocator>(
+            &mut self,
+            range: impl RangeBounds<usize>,
+            replace_with: I,
+            alloc: ATemp,
+        ) -> Result<(), FallibleVecError> {
+            let range = checked_slice_range(range, self.len())
+                .ok_or(FallibleVecError::RangeOutOfBounds)?;
+            self.try_splice_in(range, replace_with, alloc)?;
+            Ok(())
+        }
+
+        fn try_push_or_return(&mut self, item: T) -> Result<(), (T, TryReserveError)> {
+            if let Err(error) = self.try_reserve(1) {
+                return Err((item, error));
+            }
+            // Cannot fail as the space has already been reserved.
+            let _ = self.try_push(item);
+            Ok(())
+        }
+
+        fn try_insert_or_return(
+            &mut self,
+            index: usize,
+            element: T,
+        ) -> Result<(), (T, TryReserveError)> {
+            let len = self.len();
+            assert!(
+                index <= len,
+                "insertion index (is {index}) should be <= len (is {len})"
+            );
+            if let Err(error) = self.try_reserve(1) {
+                return Err((element, error));
+            }
+            // Cannot fail as the space has already been reserved.
+            let _ = self.try_insert(index, element);
+            Ok(())
+        }
+
+        fn try_extend_resumable<I: IntoIterator<Item = T>>(
+            &mut self,
+            iter: I,
+        ) -> Result<(), ExtendError<T, I::IntoIter>> {
+            let mut iter = iter.into_iter();
+            let mut appended = 0;
+            while let Some(item) = iter.next() {
+                if self.len() == self.capacity() {
+                    let (low_bound, _upper_bound) = iter.size_hint();
+                    // If there isn't enough space for the rest of the iterator,
+                    // then still try to make room for the current item.
+                    let reserved = match self.try_reserve(low_bound.saturating_add(1)) {
+                        Err(_) if low_bound > 0 => self.try_reserve(1),
+                        result => result,
+                    };
+                    if let Err(error) = reserved {
+                        return Err(ExtendError {
+                            appended,
+                            item,
+                            remaining: iter,
+                            error,
+                        });
+                    }
+                }
+                // Cannot fail as the space has already been reserved.
+                let _ = self.try_push(item);
+                appended += 1;
+            }
+            Ok(())
+        }
+
+        fn push_no_grow(&mut self, item: T) -> Result<(), T> {
+            let len = self.len();
+            if len == self.capacity() {
+                return Err(item);
+            }
+            unsafe {
+                self.as_mut_ptr().add(len).write(item);
+                self.set_len(len + 1);
+            }
+            Ok(())
+        }
+
+        fn insert_within_capacity(&mut self, index: usize, element: T) -> Result<(), T> {
+            let len = self.len();
+            assert!(
+                index <= len,
+                "insertion index (is {index}) should be <= len (is {len})"
+            );
+            if len == self.capacity() {
+                return Err(element);
+            }
+            unsafe {
+                let ptr = self.as_mut_ptr().add(index);
+                core::ptr::copy(ptr, ptr.add(1), len - index);
+                ptr.write(element);
+                self.set_len(len + 1);
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "allocator_api")]
+        fn try_clone_in<B: Allocator>(&self, alloc: B) -> Result<Vec<T, B>, TryReserveError>
+        where
+            T: Clone,
+        {
+            let mut clone = Vec::new_in(alloc);
+            clone.try_reserve_exact(self.len())?;
+            clone.try_extend_from_slice(self)?;
+            Ok(clone)
+        }
+
+        fn try_clone_into(&self, dest: &mut Self) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            // Reserve before clearing `dest`, so that it's unchanged on failure.
+            dest.try_reserve_exact(self.len().saturating_sub(dest.len()))?;
+            dest.clear();
+            dest.try_extend_from_slice(self)
+        }
+
+        #[cfg(feature = "allocator_api")]
+        fn try_migrate_allocator<B: Allocator>(
+            mut self,
+            alloc: B,
+        ) -> Result<Vec<T, B>, (Self, TryReserveError)> {
+            let len = self.len();
+            let mut migrated = Vec::new_in(alloc);
+            if let Err(error) = migrated.try_reserve_exact(len) {
+                return Err((self, error));
             }
-            Ok(())
+            unsafe {
+                core::ptr::copy_nonoverlapping(self.as_ptr(), migrated.as_mut_ptr(), len);
+                // The items are now owned by `migrated`, so only the original
+                // allocation is freed when `self` is dropped.
+                self.set_len(0);
+                migrated.set_len(len);
+            }
+            Ok(migrated)
         }
 
         #[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
-        fn try_splice_in<I: IntoIterator<Item = T>, ATemp: Allocator>(
+        fn try_splice_out_in<I: IntoIterator<Item = T>, ATemp: Allocator>(
             &mut self,
-            range: impl core::ops::RangeBounds<usize>,
+            range: impl RangeBounds<usize>,
             replace_with: I,
             alloc: ATemp,
-        ) -> Result<(), TryReserveError> {
-            let mut replace_with = replace_with.into_iter();
-            let core::ops::Range {
-                start: mut index,
-                end,
-            } = core::slice::range(range, ..self.len());
-
-            // Write over the items that need to be removed first.
-            while index < end {
-                if let Some(item) = replace_with.next() {
-                    self[index] = item;
-                    index += 1;
-                } else {
-                    // Nothing else to insert, drop the rest.
-                    self.drain(index..end);
-                    return Ok(());
+        ) -> Result<Vec<T, ATemp>, TryReserveError> {
+            let Range { start, end } = slice_range(range, self.len());
+            let mut removed = Vec::new_in(alloc);
+            removed.try_reserve_exact(end - start)?;
+            unsafe {
+                self.move_range_to(start..end, removed.as_mut_ptr());
+                removed.set_len(end - start);
+            }
+            let len = self.len();
+            let inserted = self.try_splice_in(start..start, replace_with, removed.allocator());
+            if let Err(error) = inserted {
+                // Drop any replacement items that were inserted, then move the
+                // removed items back: this cannot reallocate since the capacity
+                // is still at least the original length.
+                self.drain(start..start + (self.len() - len));
+                debug_assert!(self.capacity() - len >= removed.len());
+                unsafe {
+                    let ptr = self.as_mut_ptr().add(start);
+                    core::ptr::copy(ptr, ptr.add(removed.len()), len - start);
+                    core::ptr::copy_nonoverlapping(removed.as_ptr(), ptr, removed.len());
+                    self.set_len(len + removed.len());
+                    removed.set_len(0);
                 }
+                return Err(error);
             }
+            Ok(removed)
+        }
 
-            // If we know roughly how many more there are, copy those directly.
-            let (lower_bound, ..) = replace_with.size_hint();
-            if lower_bound > 0 {
-                self.move_tail(index, lower_bound)?;
+        fn try_splice_exact<I>(
+            &mut self,
+            range: impl RangeBounds<usize>,
+            replace_with: I,
+        ) -> Result<(), TryReserveError>
+        where
+            I: IntoIterator<Item = T>,
+            I::IntoIter: ExactSizeIterator,
+        {
+            let Range { start, end } = slice_range(range, self.len());
+            let replace_with = replace_with.into_iter();
+            let count = replace_with.len();
+            self.try_reserve(count.saturating_sub(end - start))?;
 
-                // Temporarily reduce the length: this will result in both the
-                // uninitialized memory AND the post-splice items being leaked if a
-                // call to next() panics.
-                let after_splice = self.len() - index;
+            let len = self.len();
+            let tail = len - end;
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                // Leak the tail rather than double-dropping it if dropping the
+                // removed items or a call to `next()` panics.
+                self.set_len(start);
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    ptr.add(start),
+                    end - start,
+                ));
+                core::ptr::copy(ptr.add(end), ptr.add(start + count), tail);
+            }
+
+            let mut local_len = SetLenOnDrop::new(self);
+            for item in replace_with.take(count) {
                 unsafe {
-                    self.set_len(index);
+                    ptr.add(local_len.current_len()).write(item);
                 }
+                local_len.increment_len(1);
+            }
+            let written = local_len.current_len() - start;
+            drop(local_len);
 
-                {
-                    let ptr = self.as_mut_ptr();
-                    let mut local_len = SetLenOnDrop::new(self);
-                    loop {
-                        unsafe {
-                            ptr.add(local_len.current_len())
-                                .write(replace_with.next().unwrap());
-                        }
-                        local_len.increment_len(1);
-                        if local_len.current_len() == index + lower_bound {
-                            break;
-                        }
-                    }
+            unsafe {
+                if written < count {
+                    // The iterator was shorter than it claimed, so close the
+                    // gap.
+                    core::ptr::copy(ptr.add(start + count), ptr.add(start + written), tail);
                 }
+                self.set_len(start + written + tail);
+            }
+            Ok(())
+        }
 
-                // Update the index to insert at.
-                index += lower_bound;
-                // Fixup length to include the port-splice items.
+        fn try_splice<I: IntoIterator<Item = T>>(
+            &mut self,
+            range: impl RangeBounds<usize>,
+            replace_with: I,
+        ) -> Result<(), TryReserveError>
+        where
+            Self: CloneAllocator,
+        {
+            let Range { start, end } = slice_range(range, self.len());
+            // Collect the replacement items before touching `self`, so that it
+            // is unchanged if an allocation fails.
+            let mut replacement = self.new_with_cloned_allocator();
+            replacement.try_extend(replace_with)?;
+            self.try_splice_exact(start..end, replacement)
+        }
+
+        fn try_extend_from_copy_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError>
+        where
+            T: Copy,
+        {
+            self.try_reserve(slice.len())?;
+            let len = self.len();
+            unsafe {
+                core::ptr::copy_nonoverlapping(slice.as_ptr(), self.as_mut_ptr().add(len), slice.len());
+                self.set_len(len + slice.len());
+            }
+            Ok(())
+        }
+
+        fn try_extend_zeroed(&mut self, n: usize) -> Result<(), TryReserveError>
+        where
+            T: Zeroable,
+        {
+            let new_len = self.len().checked_add(n).ok_or_else(capacity_overflow)?;
+            self.try_resize_zeroed(new_len)
+        }
+
+        fn try_assign<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+            self.clear();
+            self.try_extend(iter)
+        }
+
+        fn try_extract_if_collect<F: FnMut(&T) -> bool>(
+            &mut self,
+            mut pred: F,
+        ) -> Result<Self, TryReserveError>
+        where
+            Self: CloneAllocator,
+        {
+            let count = self.iter().filter(|item| pred(item)).count();
+            let mut extracted = self.new_with_cloned_allocator();
+            extracted.try_reserve_exact(count)?;
+
+            // If `pred` panics, then only the items that have been kept are
+            // left in the `Vec`.
+            let len = self.len();
+            let ptr = self.as_mut_ptr();
+            unsafe { self.set_len(0) };
+            let mut kept = SetLenOnDrop::new(self);
+            for index in 0..len {
                 unsafe {
-                    self.set_len(self.len() + after_splice);
+                    if extracted.len() < count && pred(&*ptr.add(index)) {
+                        // Cannot fail as the space has already been reserved.
+                        let _ = extracted.try_push(ptr.add(index).read());
+                    } else {
+                        core::ptr::copy(ptr.add(index), ptr.add(kept.current_len()), 1);
+                        kept.increment_len(1);
+                    }
                 }
             }
+            drop(kept);
+            Ok(extracted)
+        }
 
-            // Gather up the remainder and copy those as well.
-            let remainder = replace_with.try_collect_in(alloc)?;
-            if !remainder.is_empty() {
-                self.move_tail(index, remainder.len())?;
-                // Don't need to use `SetLenOnDrop` here since we're enumerating
-                // over a Vec that we own.
+        fn try_resize_with_index<F: FnMut(usize) -> T>(
+            &mut self,
+            new_len: usize,
+            mut f: F,
+        ) -> Result<(), TryReserveError> {
+            let mut index = self.len();
+            self.try_resize_with(new_len, || {
+                let item = f(index);
+                index += 1;
+                item
+            })
+        }
+
+        fn try_extend_from_iter_exact<I: ExactSizeIterator<Item = T>>(
+            &mut self,
+            iter: I,
+        ) -> Result<(), TryReserveError> {
+            let count = iter.len();
+            self.try_reserve(count)?;
+            let ptr = self.as_mut_ptr();
+            let mut local_len = SetLenOnDrop::new(self);
+            // Never trust the length for more than the space that was reserved.
+            for item in iter.take(count) {
                 unsafe {
-                    self.set_len(self.len() + remainder.len());
-                }
-                let ptr = unsafe { self.as_mut_ptr().add(index) };
-                for (i, item) in remainder.into_iter().enumerate() {
-                    unsafe { ptr.add(i).write(item) };
+                    ptr.add(local_len.current_len()).write(item);
                 }
+                local_len.increment_len(1);
             }
-
             Ok(())
         }
+
+        fn try_reserve_in_increments(
+            &mut self,
+            additional: usize,
+            increment: usize,
+        ) -> Result<(), (usize, TryReserveError)> {
+            assert!(increment != 0, "increment must be non-zero");
+            loop {
+                let available = self.capacity() - self.len();
+                if available >= additional {
+                    return Ok(());
+                }
+                let step = increment.min(additional - available);
+                self.try_reserve_exact(available + step)
+                    .map_err(|error| (available, error))?;
+            }
+        }
     }
 }
 
@@ -517,7 +2801,7 @@ macro_rules! try_vec {
             $crate::alloc_usings::Vec::new())
     );
     ($elem:expr; $n:expr) => (
-        $crate::try_new_repeat_item($elem, $n)
+        $crate::try_from_elem($elem, $n)
     );
     ($($x:expr),+ $(,)?) => ({
         let values = [$($x),+];
@@ -582,7 +2866,7 @@ macro_rules! try_vec_in {
             $crate::alloc_usings::Vec::new_in($allocator))
     );
     ($elem:expr; $n:expr => $allocator:expr) => (
-        $crate::try_new_repeat_item_in($elem, $n, $allocator)
+        $crate::try_from_elem_in($elem, $n, $allocator)
     );
     ($($x:expr),+ $(,)? => $allocator:expr) => ({
         let values = [$($x),+];
@@ -683,19 +2967,245 @@ pub fn try_with_capacity<T>(size: usize) -> Result<Vec<T>, TryReserveError> {
     Ok(vec)
 }
 
+/// Constructs a new, empty `Vec<T, A>` with a capacity of exactly `size`
+/// elements, using the provided allocator.
+///
+/// Unlike [`try_with_capacity_in`], which may allocate more than requested
+/// (e.g., to round small requests up to a minimum size), this uses
+/// `try_reserve_exact`, so the capacity matches the request exactly. This is
+/// useful for long-lived buffers in memory-constrained systems.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let vec = try_with_exact_capacity_in::<u8, _>(3, System)?;
+/// assert_eq!(vec.capacity(), 3);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_with_exact_capacity_in<T, A: Allocator>(
+    size: usize,
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut vec: Vec<T, A> = Vec::new_in(alloc);
+    vec.try_reserve_exact(size)?;
+    Ok(vec)
+}
+
+/// Constructs a new, empty `Vec<T>` with a capacity of exactly `size`
+/// elements.
+///
+/// Unlike [`try_with_capacity`], which may allocate more than requested
+/// (e.g., to round small requests up to a minimum size), this uses
+/// `try_reserve_exact`, so the capacity matches the request exactly. This is
+/// useful for long-lived buffers in memory-constrained systems.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let vec = try_with_exact_capacity::<u8>(3)?;
+/// assert_eq!(vec.capacity(), 3);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_with_exact_capacity<T>(size: usize) -> Result<Vec<T>, TryReserveError> {
+    let mut vec: Vec<T> = Vec::new();
+    vec.try_reserve_exact(size)?;
+    Ok(vec)
+}
+
+/// Creates a `Vec<T, A>` of length `len` using the provided allocator, where
+/// each item is the result of calling `f` with its index.
+///
+/// This is the fallible `Vec` equivalent of [`core::array::from_fn`]: the
+/// `Vec` is allocated once, up front, and then filled in index order.
+///
+/// # Panic safety
+///
+/// If `f` panics, then the items that it had already returned are dropped
+/// and the `Vec` is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let squares = try_from_fn_in(5, |i| i * i, System)?;
+/// assert_eq!(squares, [0, 1, 4, 9, 16]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_from_fn_in<T, F: FnMut(usize) -> T, A: Allocator>(
+    len: usize,
+    f: F,
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut vec = try_with_capacity_in(len, alloc)?;
+    // Cannot reallocate as the space has already been reserved.
+    vec.try_extend((0..len).map(f))?;
+    Ok(vec)
+}
+
+/// Creates a `Vec<T>` of length `len`, where each item is the result of
+/// calling `f` with its index.
+///
+/// This is the fallible `Vec` equivalent of [`core::array::from_fn`]: the
+/// `Vec` is allocated once, up front, and then filled in index order.
+///
+/// # Panic safety
+///
+/// If `f` panics, then the items that it had already returned are dropped
+/// and the `Vec` is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let table = try_from_fn(4, |i| 1u32 << i)?;
+/// assert_eq!(table, [1, 2, 4, 8]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_from_fn<T, F: FnMut(usize) -> T>(len: usize, f: F) -> Result<Vec<T>, TryReserveError> {
+    let mut vec = try_with_capacity(len)?;
+    // Cannot reallocate as the space has already been reserved.
+    vec.try_extend((0..len).map(f))?;
+    Ok(vec)
+}
+
+/// Creates a `Vec<T, A>` containing `n` clones of `item`, using the provided
+/// allocator.
+///
+/// This is the fallible equivalent of `vec![item; n]` (and is what
+/// [`try_vec_in!`] uses for that form): the `Vec` is allocated once, up front,
+/// with space for exactly `n` items.
+///
+/// # Panic safety
+///
+/// If a call to `clone` for `item` panics, then the clones that had already
+/// been made are dropped and the `Vec` is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let vec = try_from_elem_in("hi", 3, System)?;
+/// assert_eq!(vec, ["hi", "hi", "hi"]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_from_elem_in<T: Clone, A: Allocator>(
+    item: T,
+    n: usize,
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    Vec::new_in(alloc).try_new_repeat_item_internal(item, n)
+}
+
+/// Creates a `Vec<T>` containing `n` clones of `item`.
+///
+/// This is the fallible equivalent of `vec![item; n]` (and is what
+/// [`try_vec!`] uses for that form): the `Vec` is allocated once, up front,
+/// with space for exactly `n` items.
+///
+/// # Panic safety
+///
+/// If a call to `clone` for `item` panics, then the clones that had already
+/// been made are dropped and the `Vec` is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let vec = try_from_elem(0u8, 4)?;
+/// assert_eq!(vec, [0, 0, 0, 0]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_from_elem<T: Clone>(item: T, n: usize) -> Result<Vec<T>, TryReserveError> {
+    Vec::new().try_new_repeat_item_internal(item, n)
+}
+
 #[doc(hidden)]
 #[cfg(feature = "allocator_api")]
+#[deprecated(note = "use `try_from_elem_in` instead")]
 pub fn try_new_repeat_item_in<T: Clone, A: Allocator>(
     item: T,
     size: usize,
     alloc: A,
 ) -> Result<Vec<T, A>, TryReserveError> {
-    Vec::new_in(alloc).try_new_repeat_item_internal(item, size)
+    try_from_elem_in(item, size, alloc)
 }
 
 #[doc(hidden)]
+#[deprecated(note = "use `try_from_elem` instead")]
 pub fn try_new_repeat_item<T: Clone>(item: T, size: usize) -> Result<Vec<T>, TryReserveError> {
-    Vec::new().try_new_repeat_item_internal(item, size)
+    try_from_elem(item, size)
+}
+
+/// Converts `range` into a `Range` for a slice of length `len`.
+///
+/// This is a stable equivalent of `core::slice::range`.
+///
+/// # Panics
+///
+/// Panics if the starting point is greater than the end point or if the end
+/// point is greater than `len`.
+fn slice_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "slice index starts at {start} but ends at {end}"
+    );
+    assert!(
+        end <= len,
+        "range end index {end} out of range for slice of length {len}"
+    );
+    start..end
+}
+
+/// Converts `range` into a `Range` for a slice of length `len`, or returns
+/// `None` if the starting point is greater than the end point or if the end
+/// point is greater than `len`.
+#[cfg(all(feature = "allocator_api", feature = "use_unstable_apis"))]
+fn checked_slice_range(range: impl RangeBounds<usize>, len: usize) -> Option<Range<usize>> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => end.checked_add(1)?,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    (start <= end && end <= len).then_some(start..end)
 }
 
 trait ImplementationDetails<T>: Sized {
@@ -704,6 +3214,16 @@ trait ImplementationDetails<T>: Sized {
         T: Clone;
 
     fn move_tail(&mut self, index: usize, by: usize) -> Result<(), TryReserveError>;
+
+    unsafe fn move_range_to(&mut self, range: Range<usize>, destination: *mut T);
+
+    unsafe fn try_append_raw(
+        &mut self,
+        source: *const T,
+        count: usize,
+    ) -> Result<(), TryReserveError>;
+
+    fn try_shrink_to_internal(&mut self, min_capacity: usize) -> Result<(), TryReserveError>;
 }
 
 impl_trait_for_vec! {
@@ -716,7 +3236,7 @@ impl_trait_for_vec! {
         ) -> Result<Self, TryReserveError>
         where T: Clone {
             if size > 0 {
-                self.try_reserve(size)?;
+                self.try_reserve_exact(size)?;
                 let ptr = self.as_mut_ptr();
                 let mut local_len = SetLenOnDrop::new(&mut self);
                 loop {
@@ -749,6 +3269,106 @@ impl_trait_for_vec! {
             }
             Ok(())
         }
+
+        /// Moves the items in `range` to `destination` and then moves the items
+        /// after `range` down to fill the gap, reducing the `len` of the `vec`.
+        ///
+        /// # Safety
+        ///
+        /// `range` must be within `0..len` and `destination` must be valid for
+        /// writes of `range.len()` items and must not overlap with the `vec`.
+        unsafe fn move_range_to(&mut self, range: Range<usize>, destination: *mut T) {
+            let len = self.len();
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                core::ptr::copy_nonoverlapping(ptr.add(range.start), destination, range.len());
+                core::ptr::copy(ptr.add(range.end), ptr.add(range.start), len - range.end);
+                self.set_len(len - range.len());
+            }
+        }
+
+        /// Reserves space for `count` items, then moves the items at `source`
+        /// to the end of the `vec`.
+        ///
+        /// NOTE: Nothing is moved if reserving fails.
+        ///
+        /// # Safety
+        ///
+        /// `source` must be valid for reads of `count` items and must not
+        /// overlap with the `vec`. If this succeeds, then the items at `source`
+        /// have been moved and must not be used or dropped.
+        unsafe fn try_append_raw(
+            &mut self,
+            source: *const T,
+            count: usize,
+        ) -> Result<(), TryReserveError> {
+            self.try_reserve(count)?;
+            let len = self.len();
+            unsafe {
+                core::ptr::copy_nonoverlapping(source, self.as_mut_ptr().add(len), count);
+                self.set_len(len + count);
+            }
+            Ok(())
+        }
+
+        /// Reallocates the `vec` so that its capacity is the larger of its
+        /// `len` and `min_capacity`, if that is smaller than its current
+        /// capacity.
+        ///
+        /// NOTE: The `vec` is unchanged if the reallocation fails.
+        fn try_shrink_to_internal(&mut self, min_capacity: usize) -> Result<(), TryReserveError> {
+            let len = self.len();
+            let capacity = self.capacity();
+            let new_capacity = len.max(min_capacity);
+            if core::mem::size_of::<T>() == 0 || new_capacity >= capacity {
+                return Ok(());
+            }
+
+            // Neither can overflow since they are no larger than the existing
+            // allocation.
+            let old_layout = Layout::array::<T>(capacity).map_err(|_| capacity_overflow())?;
+            let new_layout = Layout::array::<T>(new_capacity).map_err(|_| capacity_overflow())?;
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                // Build the replacement `Vec` and then write it over the
+                // original, since dropping the original would free the
+                // (already freed or reallocated) buffer.
+                #[cfg(not(feature = "allocator_api"))]
+                let vec = if new_capacity == 0 {
+                    alloc::alloc::dealloc(ptr.cast(), old_layout);
+                    Vec::new()
+                } else {
+                    let new_ptr = alloc::alloc::realloc(ptr.cast(), old_layout, new_layout.size());
+                    if new_ptr.is_null() {
+                        return Err(alloc_error(new_layout));
+                    }
+                    Vec::from_raw_parts(new_ptr.cast(), len, new_capacity)
+                };
+
+                #[cfg(feature = "allocator_api")]
+                let vec = {
+                    let old_ptr = core::ptr::NonNull::new_unchecked(ptr).cast();
+                    if new_capacity == 0 {
+                        self.allocator().deallocate(old_ptr, old_layout);
+                        Vec::new_in(core::ptr::read(self.allocator()))
+                    } else {
+                        let new_ptr = self
+                            .allocator()
+                            .shrink(old_ptr, old_layout, new_layout)
+                            .map_err(|_| alloc_error(new_layout))?;
+                        Vec::from_raw_parts_in(
+                            new_ptr.as_ptr().cast(),
+                            len,
+                            new_capacity,
+                            core::ptr::read(self.allocator()),
+                        )
+                    }
+                };
+
+                core::ptr::write(self, vec);
+            }
+            Ok(())
+        }
     }
 }
 
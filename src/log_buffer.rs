@@ -0,0 +1,208 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::FallibleVec;
+use alloc::vec::Vec;
+use core::{iter::Chain, slice::Iter};
+
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// What a [`BoundedLogBuffer`] does with a new entry when it is full or
+/// cannot allocate space for the entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Overwrite the oldest entry with the new entry.
+    ///
+    /// If the buffer is empty (and so has nothing to evict), the new entry is
+    /// dropped instead.
+    EvictOldest,
+
+    /// Drop the new entry, keeping the existing entries.
+    DropNewest,
+}
+
+macro_rules! struct_bounded_log_buffer {
+    { $(#[doc = $doc:expr])+ pub struct BoundedLogBuffer $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct BoundedLogBuffer<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct BoundedLogBuffer<T, A: Allocator = Global> $impl
+    }
+}
+
+struct_bounded_log_buffer! {
+    /// A bounded, in-order buffer of entries that degrades instead of failing.
+    ///
+    /// Entries are added with [`push`](BoundedLogBuffer::push), which never
+    /// returns an error: if the buffer has reached its maximum length, or an
+    /// allocation to grow it fails, then an entry is lost according to the
+    /// buffer's [`OverflowPolicy`] and the loss is recorded in
+    /// [`lost_count`](BoundedLogBuffer::lost_count). This makes it suitable
+    /// for capturing diagnostics that must keep working under memory pressure.
+    ///
+    /// Space is allocated lazily, so an unused buffer does not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fallible_vec::*;
+    ///
+    /// let mut log = BoundedLogBuffer::new(3, OverflowPolicy::EvictOldest);
+    /// for line in ["a", "b", "c", "d"] {
+    ///     log.push(line);
+    /// }
+    /// assert!(log.iter().eq(&["b", "c", "d"]));
+    /// assert_eq!(log.lost_count(), 1);
+    /// ```
+    pub struct BoundedLogBuffer {
+        #[cfg(not(feature = "allocator_api"))]
+        entries: Vec<T>,
+        #[cfg(feature = "allocator_api")]
+        entries: Vec<T, A>,
+        // Index of the oldest entry; always 0 unless the buffer is full and
+        // has started overwriting entries.
+        head: usize,
+        max_len: usize,
+        policy: OverflowPolicy,
+        lost_count: usize,
+    }
+}
+
+macro_rules! impl_bounded_log_buffer {
+    { impl BoundedLogBuffer $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> BoundedLogBuffer<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> BoundedLogBuffer<T, A> $impl
+    }
+}
+
+impl<T> BoundedLogBuffer<T> {
+    /// Creates a new, empty buffer that holds at most `max_len` entries.
+    pub const fn new(max_len: usize, policy: OverflowPolicy) -> Self {
+        BoundedLogBuffer {
+            entries: Vec::new(),
+            head: 0,
+            max_len,
+            policy,
+            lost_count: 0,
+        }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> BoundedLogBuffer<T, A> {
+    /// Creates a new, empty buffer that holds at most `max_len` entries, using
+    /// the provided allocator.
+    pub fn new_in(max_len: usize, policy: OverflowPolicy, alloc: A) -> Self {
+        BoundedLogBuffer {
+            entries: Vec::new_in(alloc),
+            head: 0,
+            max_len,
+            policy,
+            lost_count: 0,
+        }
+    }
+}
+
+impl_bounded_log_buffer! {
+    impl BoundedLogBuffer {
+        /// Adds `entry` as the newest entry in the buffer.
+        ///
+        /// If the buffer is full, or space for the entry cannot be allocated,
+        /// then either the oldest entry is overwritten or `entry` is dropped
+        /// (depending on the buffer's [`OverflowPolicy`]) and the lost count
+        /// is incremented.
+        pub fn push(&mut self, entry: T) {
+            let len = self.entries.len();
+            if len < self.max_len {
+                if self.head != 0 {
+                    // Restore the entries to order so that the new entry can
+                    // be appended.
+                    self.entries.rotate_left(self.head);
+                    self.head = 0;
+                }
+
+                if len < self.entries.capacity() || self.try_grow() {
+                    // Cannot fail as there is spare capacity.
+                    let _ = self.entries.try_push(entry);
+                    return;
+                }
+            }
+
+            self.lost_count = self.lost_count.saturating_add(1);
+            if self.policy == OverflowPolicy::EvictOldest && len > 0 {
+                self.entries[self.head] = entry;
+                self.head = (self.head + 1) % len;
+            }
+        }
+
+        /// Attempts to grow the buffer's capacity, doubling it (up to the
+        /// maximum length) if possible, otherwise growing it by a single entry.
+        fn try_grow(&mut self) -> bool {
+            let len = self.entries.len();
+            let additional = len.max(4).min(self.max_len - len);
+            self.entries.try_reserve_exact(additional).is_ok()
+                || self.entries.try_reserve_exact(1).is_ok()
+        }
+
+        /// Returns the number of entries that have been lost, either because
+        /// they were evicted or dropped, since the buffer was created or the
+        /// count was last reset.
+        pub fn lost_count(&self) -> usize {
+            self.lost_count
+        }
+
+        /// Resets the lost count to zero, returning its previous value.
+        pub fn reset_lost_count(&mut self) -> usize {
+            core::mem::replace(&mut self.lost_count, 0)
+        }
+
+        /// Returns the maximum number of entries the buffer can hold.
+        pub fn max_len(&self) -> usize {
+            self.max_len
+        }
+
+        /// Returns the [`OverflowPolicy`] of the buffer.
+        pub fn policy(&self) -> OverflowPolicy {
+            self.policy
+        }
+
+        /// Returns the number of entries in the buffer.
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        /// Returns `true` if the buffer contains no entries.
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        /// Returns the entries in the buffer as a pair of slices which, when
+        /// concatenated, are ordered from oldest to newest.
+        pub fn as_slices(&self) -> (&[T], &[T]) {
+            let (newer, older) = self.entries.split_at(self.head);
+            (older, newer)
+        }
+
+        /// Returns an iterator over the entries, from oldest to newest.
+        pub fn iter(&self) -> Chain<Iter<'_, T>, Iter<'_, T>> {
+            let (older, newer) = self.as_slices();
+            older.iter().chain(newer.iter())
+        }
+
+        /// Removes all entries from the buffer, keeping its allocated
+        /// capacity. The lost count is not changed.
+        pub fn clear(&mut self) {
+            self.entries.clear();
+            self.head = 0;
+        }
+    }
+}
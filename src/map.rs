@@ -0,0 +1,326 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{FallibleVec, TryReserveError};
+use alloc::{alloc::Layout, vec::Vec};
+use core::{
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ptr,
+};
+
+#[cfg(feature = "allocator_api")]
+use core::{alloc::Allocator, ptr::NonNull};
+
+/// Maps each item of `vec` with `f`, returning a `Vec` of the results.
+///
+/// If `T` and `U` have the same size and alignment, then the items are mapped
+/// in place and the allocation of `vec` is reused, so this cannot fail.
+/// Otherwise a new allocation of exactly `vec.len()` items is made before `f`
+/// is first called: if that fails then `vec` is dropped and the error is
+/// returned.
+///
+/// # Panic safety
+///
+/// If `f` panics when mapping in place, then all of the items (both those
+/// already mapped and those not yet mapped) are dropped and the allocation is
+/// freed. Otherwise the items not yet mapped and the allocation of `vec` may
+/// be leaked.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vec = try_vec![1u32, 2, 3]?;
+/// let capacity = vec.capacity();
+/// let mapped = try_map(vec, |x| x as f32 * 0.5)?;
+/// assert_eq!(mapped, [0.5, 1.0, 1.5]);
+/// // The allocation was reused.
+/// assert_eq!(mapped.capacity(), capacity);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+pub fn try_map<T, U, F: FnMut(T) -> U>(vec: Vec<T>, mut f: F) -> Result<Vec<U>, TryReserveError> {
+    if Layout::new::<T>() == Layout::new::<U>() {
+        return Ok(unsafe { map_in_place(vec, f) });
+    }
+
+    let mut vec = ManuallyDrop::new(vec);
+    let (ptr, len) = (vec.as_mut_ptr(), vec.len());
+
+    let mut mapped = Vec::new();
+    if let Err(error) = mapped.try_reserve_exact(len) {
+        drop(ManuallyDrop::into_inner(vec));
+        return Err(error);
+    }
+    for index in 0..len {
+        let item = unsafe { ptr.add(index).read() };
+        // Cannot fail as the space has already been reserved.
+        let _ = mapped.try_push(f(item));
+    }
+    unsafe {
+        // The items have all been moved out, so only free the allocation.
+        vec.set_len(0);
+        ManuallyDrop::drop(&mut vec);
+    }
+    Ok(mapped)
+}
+
+/// Maps each item of `vec` with `f`, returning a `Vec` of the results that
+/// uses the same allocator.
+///
+/// If `T` and `U` have the same size and alignment, then the items are mapped
+/// in place and the allocation of `vec` is reused, so this cannot fail.
+/// Otherwise a new allocation of exactly `vec.len()` items is made before `f`
+/// is first called: if that fails then `vec` is dropped and the error is
+/// returned.
+///
+/// # Panic safety
+///
+/// If `f` panics when mapping in place, then all of the items (both those
+/// already mapped and those not yet mapped) are dropped and the allocation is
+/// freed. Otherwise the items not yet mapped and the allocation of `vec` may
+/// be leaked.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vec = try_vec![1u32, 2, 3]?;
+/// let capacity = vec.capacity();
+/// let mapped = try_map(vec, |x| x as f32 * 0.5)?;
+/// assert_eq!(mapped, [0.5, 1.0, 1.5]);
+/// // The allocation was reused.
+/// assert_eq!(mapped.capacity(), capacity);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_map<T, U, F: FnMut(T) -> U, A: Allocator>(
+    vec: Vec<T, A>,
+    mut f: F,
+) -> Result<Vec<U, A>, TryReserveError> {
+    if Layout::new::<T>() == Layout::new::<U>() {
+        return Ok(unsafe { map_in_place(vec, f) });
+    }
+
+    let mut vec = ManuallyDrop::new(vec);
+    let (ptr, len, capacity) = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+
+    // Move the allocator into the new `Vec`: the copy left in `vec` is never
+    // used again (or dropped).
+    let mut mapped = Vec::new_in(unsafe { ptr::read(vec.allocator()) });
+    if let Err(error) = mapped.try_reserve_exact(len) {
+        // Nothing was allocated, so move the allocator back to drop `vec`.
+        let mapped = ManuallyDrop::new(mapped);
+        unsafe {
+            let alloc = ptr::read(mapped.allocator());
+            drop(Vec::from_raw_parts_in(ptr, len, capacity, alloc));
+        }
+        return Err(error);
+    }
+    for index in 0..len {
+        let item = unsafe { ptr.add(index).read() };
+        // Cannot fail as the space has already been reserved.
+        let _ = mapped.try_push(f(item));
+    }
+    // The items have all been moved out, so only free the allocation.
+    if let Ok(layout) = Layout::array::<T>(capacity) {
+        if layout.size() != 0 {
+            unsafe {
+                mapped
+                    .allocator()
+                    .deallocate(NonNull::new_unchecked(ptr).cast(), layout);
+            }
+        }
+    }
+    Ok(mapped)
+}
+
+/// Maps each item of `vec` with `f` in place, reusing the allocation of `vec`
+/// for the results.
+///
+/// This never allocates: `T` and `U` must have the same size and alignment,
+/// which is checked at compile time.
+///
+/// # Panic safety
+///
+/// If `f` panics, then all of the items (both those already mapped and those
+/// not yet mapped) are dropped and the allocation is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vec = try_vec![1u32, 2, 3]?;
+/// let ptr = vec.as_ptr() as usize;
+/// let mapped = try_map_in_place(vec, |x| x as i32 - 2);
+/// assert_eq!(mapped, [-1, 0, 1]);
+/// assert_eq!(mapped.as_ptr() as usize, ptr);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+///
+/// Types with different layouts are rejected:
+///
+/// ```compile_fail
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mapped = try_map_in_place(vec![1u32, 2, 3], |x| x as u64);
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+pub fn try_map_in_place<T, U, F: FnMut(T) -> U>(vec: Vec<T>, f: F) -> Vec<U> {
+    #[allow(clippy::let_unit_value)]
+    let () = SameLayout::<T, U>::ASSERT;
+    unsafe { map_in_place(vec, f) }
+}
+
+/// Maps each item of `vec` with `f` in place, reusing the allocation of `vec`
+/// for the results.
+///
+/// This never allocates: `T` and `U` must have the same size and alignment,
+/// which is checked at compile time.
+///
+/// # Panic safety
+///
+/// If `f` panics, then all of the items (both those already mapped and those
+/// not yet mapped) are dropped and the allocation is freed.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let vec = try_vec![1u32, 2, 3]?;
+/// let ptr = vec.as_ptr() as usize;
+/// let mapped = try_map_in_place(vec, |x| x as i32 - 2);
+/// assert_eq!(mapped, [-1, 0, 1]);
+/// assert_eq!(mapped.as_ptr() as usize, ptr);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+///
+/// Types with different layouts are rejected:
+///
+/// ```compile_fail
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mapped = try_map_in_place(vec![1u32, 2, 3], |x| x as u64);
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_map_in_place<T, U, F: FnMut(T) -> U, A: Allocator>(vec: Vec<T, A>, f: F) -> Vec<U, A> {
+    #[allow(clippy::let_unit_value)]
+    let () = SameLayout::<T, U>::ASSERT;
+    unsafe { map_in_place(vec, f) }
+}
+
+struct SameLayout<T, U>(PhantomData<(T, U)>);
+
+impl<T, U> SameLayout<T, U> {
+    /// Evaluating this fails to compile if `T` and `U` have different layouts.
+    const ASSERT: () = assert!(
+        mem::size_of::<T>() == mem::size_of::<U>() && mem::align_of::<T>() == mem::align_of::<U>(),
+        "the types must have the same size and alignment"
+    );
+}
+
+/// Maps each item of `vec` with `f` in place.
+///
+/// # Safety
+///
+/// `T` and `U` must have the same size and alignment.
+#[cfg(not(feature = "allocator_api"))]
+unsafe fn map_in_place<T, U, F: FnMut(T) -> U>(mut vec: Vec<T>, f: F) -> Vec<U> {
+    let len = vec.len();
+    unsafe {
+        // If `f` panics, then the guard drops the items and then `vec` frees
+        // the allocation.
+        vec.set_len(0);
+        map_items_in_place(vec.as_mut_ptr(), len, f);
+    }
+    let mut vec = ManuallyDrop::new(vec);
+    unsafe { Vec::from_raw_parts(vec.as_mut_ptr().cast(), len, vec.capacity()) }
+}
+
+/// Maps each item of `vec` with `f` in place.
+///
+/// # Safety
+///
+/// `T` and `U` must have the same size and alignment.
+#[cfg(feature = "allocator_api")]
+unsafe fn map_in_place<T, U, F: FnMut(T) -> U, A: Allocator>(
+    mut vec: Vec<T, A>,
+    f: F,
+) -> Vec<U, A> {
+    let len = vec.len();
+    unsafe {
+        // If `f` panics, then the guard drops the items and then `vec` frees
+        // the allocation.
+        vec.set_len(0);
+        map_items_in_place(vec.as_mut_ptr(), len, f);
+    }
+    let mut vec = ManuallyDrop::new(vec);
+    unsafe {
+        let alloc = ptr::read(vec.allocator());
+        Vec::from_raw_parts_in(vec.as_mut_ptr().cast(), len, vec.capacity(), alloc)
+    }
+}
+
+/// Replaces each of the `len` items at `ptr` with the result of calling `f`
+/// on it.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads and writes of `len` items, and `T` and `U`
+/// must have the same size and alignment.
+unsafe fn map_items_in_place<T, U, F: FnMut(T) -> U>(ptr: *mut T, len: usize, mut f: F) {
+    let mut guard = MapInPlaceGuard::<T, U> {
+        ptr,
+        len,
+        mapped: 0,
+        marker: PhantomData,
+    };
+    while guard.mapped < len {
+        unsafe {
+            let item = ptr.add(guard.mapped).read();
+            ptr.cast::<U>().add(guard.mapped).write(f(item));
+        }
+        guard.mapped += 1;
+    }
+    mem::forget(guard);
+}
+
+/// Drops the items of a partially mapped buffer if mapping an item panics.
+struct MapInPlaceGuard<T, U> {
+    ptr: *mut T,
+    len: usize,
+    /// The number of items that have been mapped: the item after them has
+    /// been moved out to be mapped.
+    mapped: usize,
+    marker: PhantomData<(T, U)>,
+}
+
+impl<T, U> Drop for MapInPlaceGuard<T, U> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.ptr.cast::<U>(),
+                self.mapped,
+            ));
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.ptr.add(self.mapped + 1),
+                self.len - self.mapped - 1,
+            ));
+        }
+    }
+}
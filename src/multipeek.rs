@@ -0,0 +1,281 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::{fmt, iter::FusedIterator, ptr};
+
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Fallible allocation equivalent of an iterator adapter that allows peeking
+/// at any number of upcoming items.
+pub trait TryMultiPeek: Iterator + Sized {
+    /// Creates an iterator that allows peeking at an arbitrary number of
+    /// upcoming items, buffering them in a lookahead buffer that grows
+    /// fallibly.
+    ///
+    /// This does not allocate: the buffer is only allocated (and grown) as
+    /// items are peeked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut iter = [1, 2, 3].into_iter().try_multipeek();
+    /// assert_eq!(iter.peek()?, Some(&1));
+    /// assert_eq!(iter.peek()?, Some(&2));
+    /// assert_eq!(iter.next(), Some(1));
+    /// // Calling `next` resets the peek cursor.
+    /// assert_eq!(iter.peek()?, Some(&2));
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_multipeek(self) -> TryMultiPeekIter<Self>;
+
+    /// Creates an iterator that allows peeking at an arbitrary number of
+    /// upcoming items, buffering them in a lookahead buffer that grows
+    /// fallibly using the provided allocator.
+    ///
+    /// See [`try_multipeek`](TryMultiPeek::try_multipeek) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let mut iter = "abc".chars().try_multipeek_in(System);
+    /// assert_eq!(iter.peek()?, Some(&'a'));
+    /// assert_eq!(iter.peek()?, Some(&'b'));
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_multipeek_in<A: Allocator>(self, alloc: A) -> TryMultiPeekIter<Self, A>;
+}
+
+impl<I: Iterator> TryMultiPeek for I {
+    fn try_multipeek(self) -> TryMultiPeekIter<Self> {
+        TryMultiPeekIter {
+            iter: self.fuse(),
+            buffer: Vec::new(),
+            head: 0,
+            index: 0,
+        }
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn try_multipeek_in<A: Allocator>(self, alloc: A) -> TryMultiPeekIter<Self, A> {
+        TryMultiPeekIter {
+            iter: self.fuse(),
+            buffer: Vec::new_in(alloc),
+            head: 0,
+            index: 0,
+        }
+    }
+}
+
+/// An iterator that allows peeking at an arbitrary number of upcoming items,
+/// with a lookahead buffer that grows fallibly.
+///
+/// This `struct` is created by [`TryMultiPeek::try_multipeek`]. See its
+/// documentation for more.
+#[cfg(not(feature = "allocator_api"))]
+pub struct TryMultiPeekIter<I: Iterator> {
+    iter: core::iter::Fuse<I>,
+    /// Items that have been peeked but not yet returned from `next`, starting
+    /// at `head`: the items before `head` have already been moved out.
+    buffer: Vec<I::Item>,
+    /// The index in `buffer` of the item that the next call to `next` will
+    /// return, which is 0 (rather than `buffer.len()`) if `buffer` is empty.
+    head: usize,
+    /// The index in `buffer` of the item that the next call to `peek` will
+    /// return.
+    index: usize,
+}
+
+/// An iterator that allows peeking at an arbitrary number of upcoming items,
+/// with a lookahead buffer that grows fallibly.
+///
+/// This `struct` is created by [`TryMultiPeek::try_multipeek`] and
+/// [`TryMultiPeek::try_multipeek_in`]. See their documentation for more.
+#[cfg(feature = "allocator_api")]
+pub struct TryMultiPeekIter<I: Iterator, A: Allocator = Global> {
+    iter: core::iter::Fuse<I>,
+    /// Items that have been peeked but not yet returned from `next`, starting
+    /// at `head`: the items before `head` have already been moved out.
+    buffer: Vec<I::Item, A>,
+    /// The index in `buffer` of the item that the next call to `next` will
+    /// return, which is 0 (rather than `buffer.len()`) if `buffer` is empty.
+    head: usize,
+    /// The index in `buffer` of the item that the next call to `peek` will
+    /// return.
+    index: usize,
+}
+
+macro_rules! impl_try_multipeek_iter {
+    { impl TryMultiPeekIter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<I: Iterator> TryMultiPeekIter<I> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<I: Iterator, A: Allocator> TryMultiPeekIter<I, A> $impl
+    };
+    { impl $trait:ident for TryMultiPeekIter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<I: Iterator> $trait for TryMultiPeekIter<I> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<I: Iterator, A: Allocator> $trait for TryMultiPeekIter<I, A> $impl
+    };
+}
+
+impl_try_multipeek_iter! {
+    impl TryMultiPeekIter {
+        /// Returns a reference to the next item that has not yet been peeked,
+        /// and advances the peek cursor past it.
+        ///
+        /// The first call after creating the iterator (or after a call to
+        /// `next` or [`reset_peek`](TryMultiPeekIter::reset_peek)) returns the
+        /// item that `next` would return, the second call returns the item
+        /// after that, and so on.
+        ///
+        /// If the item has to be pulled from the underlying iterator and the
+        /// lookahead buffer is full, then the buffer is grown first: if that
+        /// fails, then the error is returned, no item is consumed and the peek
+        /// cursor is not advanced. Nothing is allocated if the underlying
+        /// iterator reports that it has no more items.
+        pub fn peek(&mut self) -> Result<Option<&I::Item>, TryReserveError> {
+            let len = self.buffer.len();
+            if self.index == len {
+                if len == self.buffer.capacity() && self.head > 0 {
+                    // Reuse the space of the items that were already returned
+                    // from `next` before growing the buffer.
+                    unsafe {
+                        let ptr = self.buffer.as_mut_ptr();
+                        ptr::copy(ptr.add(self.head), ptr, len - self.head);
+                        self.buffer.set_len(len - self.head);
+                    }
+                    self.index -= self.head;
+                    self.head = 0;
+                }
+                if self.buffer.len() == self.buffer.capacity() {
+                    if self.iter.size_hint().1 == Some(0) {
+                        return Ok(None);
+                    }
+                    // Reserve before pulling the item so that it isn't lost if
+                    // reserving fails.
+                    self.buffer.try_reserve(1)?;
+                }
+                match self.iter.next() {
+                    // Cannot fail as the space has already been reserved.
+                    Some(item) => {
+                        let _ = self.buffer.try_push(item);
+                    }
+                    None => return Ok(None),
+                }
+            }
+            let item = &self.buffer[self.index];
+            self.index += 1;
+            Ok(Some(item))
+        }
+
+        /// Resets the peek cursor, so that the next call to
+        /// [`peek`](TryMultiPeekIter::peek) returns the item that `next` would
+        /// return.
+        pub fn reset_peek(&mut self) {
+            self.index = self.head;
+        }
+
+        /// Returns the items that have been peeked but not yet returned from
+        /// `next`.
+        fn buffered(&self) -> &[I::Item] {
+            &self.buffer[self.head..]
+        }
+    }
+}
+
+impl_try_multipeek_iter! {
+    impl Iterator for TryMultiPeekIter {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<I::Item> {
+            if self.head == self.buffer.len() {
+                self.index = 0;
+                return self.iter.next();
+            }
+            // SAFETY: The item at `head` hasn't been moved out yet, and moving
+            // `head` past it means that it won't be read or dropped again.
+            let item = unsafe { ptr::read(self.buffer.as_ptr().add(self.head)) };
+            self.head += 1;
+            if self.head == self.buffer.len() {
+                // All of the items have been moved out, so start again from
+                // the front of the buffer.
+                unsafe { self.buffer.set_len(0) };
+                self.head = 0;
+            }
+            self.index = self.head;
+            Some(item)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let (lower, upper) = self.iter.size_hint();
+            let buffered = self.buffered().len();
+            (
+                lower.saturating_add(buffered),
+                upper.and_then(|upper| upper.checked_add(buffered)),
+            )
+        }
+    }
+}
+
+impl_try_multipeek_iter! {
+    impl FusedIterator for TryMultiPeekIter {}
+}
+
+impl_try_multipeek_iter! {
+    impl Drop for TryMultiPeekIter {
+        fn drop(&mut self) {
+            let buffered: *mut [I::Item] = &mut self.buffer[self.head..];
+            // Forget the items that were already moved out before dropping the
+            // rest, so that they're leaked rather than double-dropped if a
+            // drop panics.
+            unsafe {
+                self.buffer.set_len(0);
+                ptr::drop_in_place(buffered);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<I: Iterator + fmt::Debug> fmt::Debug for TryMultiPeekIter<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryMultiPeekIter")
+            .field("iter", &self.iter)
+            .field("buffer", &self.buffered())
+            .field("index", &(self.index - self.head))
+            .finish()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<I: Iterator + fmt::Debug, A: Allocator> fmt::Debug for TryMultiPeekIter<I, A>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryMultiPeekIter")
+            .field("iter", &self.iter)
+            .field("buffer", &self.buffered())
+            .field("index", &(self.index - self.head))
+            .finish()
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{alloc_error, FallibleVec, TryReserveError};
+use alloc::{alloc::Layout, rc::Rc, vec::Vec};
+use core::fmt;
+
+/// The number of bits of an index that are consumed by each level of the
+/// tree.
+const BITS: u32 = 5;
+
+/// The maximum number of children of a branch, or items in a leaf.
+const WIDTH: usize = 1 << BITS;
+
+const MASK: usize = WIDTH - 1;
+
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+/// An immutable vector that shares structure between versions, where every
+/// allocation is fallible.
+///
+/// Operations that "modify" a `PersistentVec` (such as
+/// [`try_push`](PersistentVec::try_push)) leave it unchanged and return a new
+/// `PersistentVec` instead. The new version shares all of its unmodified
+/// nodes with the old version, so only `O(log n)` nodes are allocated by each
+/// operation, and cloning a `PersistentVec` is `O(1)`. This makes it cheap to
+/// keep snapshots of a vector as it changes.
+///
+/// The items are stored in a tree with a branching factor of 32 (a "bitmapped
+/// vector trie"), so a tree holding a billion items is only 6 levels deep.
+/// Nodes are reference-counted with [`Rc`], and a node (and the items that it
+/// holds) is only freed once no version refers to it.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let empty = PersistentVec::new();
+/// let v1 = empty.try_push(1)?;
+/// let v2 = v1.try_push(2)?;
+/// let v3 = v2.try_update(0, 10)?;
+/// assert_eq!(empty.len(), 0);
+/// assert!(v1.iter().eq(&[1]));
+/// assert!(v2.iter().eq(&[1, 2]));
+/// assert!(v3.iter().eq(&[10, 2]));
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub struct PersistentVec<T> {
+    root: Option<Rc<Node<T>>>,
+    len: usize,
+    /// The number of index bits below the root (i.e., `BITS` times the depth
+    /// of the leaves).
+    shift: u32,
+}
+
+impl<T> PersistentVec<T> {
+    /// Creates a new, empty `PersistentVec`.
+    ///
+    /// This does not allocate.
+    pub const fn new() -> Self {
+        PersistentVec {
+            root: None,
+            len: 0,
+            shift: 0,
+        }
+    }
+
+    /// Returns the number of items in the `PersistentVec`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the `PersistentVec` contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the item at `index`, or `None` if it is out of
+    /// bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.leaf(index).get(index & MASK)
+    }
+
+    /// Returns an iterator over the items in the `PersistentVec`.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        // Walk to each leaf once, rather than looking up each index.
+        (0..self.len)
+            .step_by(WIDTH)
+            .flat_map(move |index| self.leaf(index))
+    }
+
+    /// Returns the items in the leaf that holds `index`, which must be in
+    /// bounds.
+    fn leaf(&self, index: usize) -> &[T] {
+        let mut node = self.root.as_deref().unwrap();
+        let mut shift = self.shift;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(index >> shift) & MASK];
+                    shift -= BITS;
+                }
+                Node::Leaf(items) => return items,
+            }
+        }
+    }
+}
+
+impl<T: Clone> PersistentVec<T> {
+    /// Returns a new `PersistentVec` with `item` appended to the back of this
+    /// one.
+    ///
+    /// `self` is unchanged. If allocating the new nodes fails, then an error
+    /// is returned and `item` is dropped.
+    pub fn try_push(&self, item: T) -> Result<Self, TryReserveError> {
+        let Some(root) = &self.root else {
+            return Ok(PersistentVec {
+                root: Some(try_rc(Node::Leaf(try_vec_of(item)?))?),
+                len: 1,
+                shift: 0,
+            });
+        };
+
+        let len = self.len;
+        let is_full = (len >> BITS) >> self.shift != 0;
+        let (root, shift) = if is_full {
+            // There's no room left under the current root, so add a new root
+            // above it.
+            let shift = self.shift + BITS;
+            let path = try_rc(new_path(self.shift, item)?)?;
+            let mut children = Vec::new();
+            children.try_reserve_exact(2)?;
+            // Cannot fail as the space has already been reserved.
+            let _ = children.try_push(Rc::clone(root));
+            let _ = children.try_push(path);
+            (Node::Branch(children), shift)
+        } else {
+            (push_into(root, self.shift, len, item)?, self.shift)
+        };
+        Ok(PersistentVec {
+            root: Some(try_rc(root)?),
+            len: len + 1,
+            shift,
+        })
+    }
+
+    /// Returns a new `PersistentVec` with the item at `index` replaced by
+    /// `item`.
+    ///
+    /// `self` is unchanged. If allocating the new nodes fails, then an error
+    /// is returned and `item` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn try_update(&self, index: usize, item: T) -> Result<Self, TryReserveError> {
+        assert!(
+            index < self.len,
+            "index out of bounds: the len is {} but the index is {index}",
+            self.len
+        );
+        let root = self.root.as_ref().unwrap();
+        Ok(PersistentVec {
+            root: Some(try_rc(update_in(root, self.shift, index, item)?)?),
+            len: self.len,
+            shift: self.shift,
+        })
+    }
+}
+
+impl<T> Clone for PersistentVec<T> {
+    /// Creates another handle to the same version of the `PersistentVec`.
+    ///
+    /// This does not allocate.
+    fn clone(&self) -> Self {
+        PersistentVec {
+            root: self.root.clone(),
+            len: self.len,
+            shift: self.shift,
+        }
+    }
+}
+
+impl<T> Default for PersistentVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Returns a copy of `node` (at the level given by `shift`) with `item`
+/// appended at `index`, which must be the number of items under `node`.
+fn push_into<T: Clone>(
+    node: &Node<T>,
+    shift: u32,
+    index: usize,
+    item: T,
+) -> Result<Node<T>, TryReserveError> {
+    match node {
+        Node::Leaf(items) => Ok(Node::Leaf(try_copy_with(items, None, Some(item))?)),
+        Node::Branch(children) => {
+            let child_index = (index >> shift) & MASK;
+            let children = if child_index < children.len() {
+                let child = push_into(&children[child_index], shift - BITS, index, item)?;
+                try_copy_with(children, Some((child_index, try_rc(child)?)), None)?
+            } else {
+                let child = new_path(shift - BITS, item)?;
+                try_copy_with(children, None, Some(try_rc(child)?))?
+            };
+            Ok(Node::Branch(children))
+        }
+    }
+}
+
+/// Returns a copy of `node` (at the level given by `shift`) with the item at
+/// `index` replaced by `item`.
+fn update_in<T: Clone>(
+    node: &Node<T>,
+    shift: u32,
+    index: usize,
+    item: T,
+) -> Result<Node<T>, TryReserveError> {
+    match node {
+        Node::Leaf(items) => Ok(Node::Leaf(try_copy_with(
+            items,
+            Some((index & MASK, item)),
+            None,
+        )?)),
+        Node::Branch(children) => {
+            let child_index = (index >> shift) & MASK;
+            let child = update_in(&children[child_index], shift - BITS, index, item)?;
+            Ok(Node::Branch(try_copy_with(
+                children,
+                Some((child_index, try_rc(child)?)),
+                None,
+            )?))
+        }
+    }
+}
+
+/// Creates a chain of nodes from the level given by `shift` down to a leaf
+/// holding only `item`.
+fn new_path<T>(shift: u32, item: T) -> Result<Node<T>, TryReserveError> {
+    let mut node = Node::Leaf(try_vec_of(item)?);
+    for _ in 0..shift / BITS {
+        node = Node::Branch(try_vec_of(try_rc(node)?)?);
+    }
+    Ok(node)
+}
+
+/// Clones `items` into a new `Vec` of exactly the required size, optionally
+/// replacing the item at one index and appending one item.
+fn try_copy_with<T: Clone>(
+    items: &[T],
+    replace: Option<(usize, T)>,
+    append: Option<T>,
+) -> Result<Vec<T>, TryReserveError> {
+    let mut copy = Vec::new();
+    copy.try_reserve_exact(items.len() + usize::from(append.is_some()))?;
+    // Cannot fail as the space has already been reserved.
+    let _ = copy.try_extend_from_slice(items);
+    if let Some((index, item)) = replace {
+        copy[index] = item;
+    }
+    if let Some(item) = append {
+        let _ = copy.try_push(item);
+    }
+    Ok(copy)
+}
+
+fn try_vec_of<T>(item: T) -> Result<Vec<T>, TryReserveError> {
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(1)?;
+    // Cannot fail as the space has already been reserved.
+    let _ = vec.try_push(item);
+    Ok(vec)
+}
+
+fn try_rc<T>(node: Node<T>) -> Result<Rc<Node<T>>, TryReserveError> {
+    // The layout of the reference-counted allocation isn't public, so report
+    // the layout of the value instead.
+    Rc::try_new(node).map_err(|_| alloc_error(Layout::new::<Node<T>>()))
+}
@@ -0,0 +1,157 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{ImplementationDetails, TryReserveError};
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// A collection whose capacity can be reserved as part of
+/// [`try_reserve_all`].
+pub trait Reservable {
+    /// Returns the number of elements the collection can hold without
+    /// reallocating.
+    fn reserved_capacity(&self) -> usize;
+
+    /// Tries to reserve capacity for at least `additional` more elements.
+    fn try_reserve_additional(&mut self, additional: usize) -> Result<(), TryReserveError>;
+
+    /// Tries to shrink the capacity of the collection back down to
+    /// `capacity`, which was previously returned by
+    /// [`reserved_capacity`](Reservable::reserved_capacity).
+    fn try_restore_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError>;
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T> Reservable for Vec<T> {
+    fn reserved_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn try_reserve_additional(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve(additional)
+    }
+
+    fn try_restore_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        self.try_shrink_to_internal(capacity)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> Reservable for Vec<T, A> {
+    fn reserved_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn try_reserve_additional(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve(additional)
+    }
+
+    fn try_restore_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        self.try_shrink_to_internal(capacity)
+    }
+}
+
+impl Reservable for String {
+    fn reserved_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn try_reserve_additional(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve(additional)
+    }
+
+    fn try_restore_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        // SAFETY: Shrinking the capacity doesn't modify the contents.
+        unsafe { self.as_mut_vec() }.try_shrink_to_internal(capacity)
+    }
+}
+
+impl<R: Reservable + ?Sized> Reservable for &mut R {
+    fn reserved_capacity(&self) -> usize {
+        (**self).reserved_capacity()
+    }
+
+    fn try_reserve_additional(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        (**self).try_reserve_additional(additional)
+    }
+
+    fn try_restore_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        (**self).try_restore_capacity(capacity)
+    }
+}
+
+/// Reserves capacity across several collections as a single transaction:
+/// either every reservation succeeds, or none of them take effect.
+///
+/// Each entry in `reservations` is a collection along with the number of
+/// additional elements to reserve for it. If any reservation fails, then the
+/// collections that had already been grown are shrunk back to their original
+/// capacity before the error is returned. Restoring capacity is itself
+/// fallible, but shrinking an allocation is not expected to fail with any
+/// reasonable allocator: if it does, the affected collection keeps its larger
+/// capacity (its contents are never changed).
+///
+/// The [`try_reserve_all!`](crate::try_reserve_all!) macro provides a more
+/// concise syntax for calling this function.
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// let mut keys: Vec<u32> = Vec::new();
+/// let mut names = String::new();
+/// try_reserve_all(&mut [(&mut keys, 10), (&mut names, 64)])?;
+/// assert!(keys.capacity() >= 10);
+/// assert!(names.capacity() >= 64);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_reserve_all(
+    reservations: &mut [(&mut dyn Reservable, usize)],
+) -> Result<(), TryReserveError> {
+    // Recurse rather than loop so that each collection's original capacity is
+    // kept on the stack, without needing to allocate somewhere to store them.
+    let Some(((collection, additional), rest)) = reservations.split_first_mut() else {
+        return Ok(());
+    };
+    let original_capacity = collection.reserved_capacity();
+    collection.try_reserve_additional(*additional)?;
+    if let Err(error) = try_reserve_all(rest) {
+        // Ignore any failure to roll back, the original error is the one that
+        // the caller needs to know about.
+        let _ = collection.try_restore_capacity(original_capacity);
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Reserves capacity across several collections as a single transaction:
+/// either every reservation succeeds, or none of them take effect.
+///
+/// Each argument is a collection that implements [`Reservable`] followed by
+/// `=>` and the number of additional elements to reserve. See
+/// [`try_reserve_all`](crate::try_reserve_all()) for details.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate fallible_vec;
+/// let mut keys: Vec<u32> = Vec::new();
+/// let mut values: Vec<u64> = Vec::new();
+/// let mut names = String::new();
+/// try_reserve_all!(keys => 10, values => 10, names => 64)?;
+/// assert!(keys.capacity() >= 10);
+/// assert!(values.capacity() >= 10);
+/// assert!(names.capacity() >= 64);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[macro_export]
+macro_rules! try_reserve_all {
+    ($($collection:expr => $additional:expr),+ $(,)?) => (
+        $crate::try_reserve_all(&mut [
+            $((&mut $collection as &mut dyn $crate::Reservable, $additional)),+
+        ])
+    );
+}
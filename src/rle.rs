@@ -0,0 +1,177 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, try_with_capacity, FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+
+#[cfg(feature = "allocator_api")]
+use crate::try_with_capacity_in;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Run-length encodes a slice: each run of consecutive equal items is replaced
+/// by a single `(item, run_length)` pair.
+///
+/// The number of runs is counted before anything is allocated, so the returned
+/// `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `eq` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let encoded = try_rle_encode(&[1, 1, 1, 2, 3, 3])?;
+/// assert_eq!(encoded, [(1, 3), (2, 1), (3, 2)]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_rle_encode<T: Clone + PartialEq>(
+    slice: &[T],
+) -> Result<Vec<(T, usize)>, TryReserveError> {
+    let mut encoded = try_with_capacity(run_count(slice))?;
+    encode_into(&mut encoded, slice)?;
+    Ok(encoded)
+}
+
+/// Run-length encodes a slice into a `Vec` with the provided allocator: each
+/// run of consecutive equal items is replaced by a single `(item, run_length)`
+/// pair.
+///
+/// The number of runs is counted before anything is allocated, so the returned
+/// `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `eq` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let encoded = try_rle_encode_in(&[1, 1, 1, 2, 3, 3], System)?;
+/// assert_eq!(encoded, [(1, 3), (2, 1), (3, 2)]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_rle_encode_in<T: Clone + PartialEq, A: Allocator>(
+    slice: &[T],
+    alloc: A,
+) -> Result<Vec<(T, usize), A>, TryReserveError> {
+    let mut encoded = try_with_capacity_in(run_count(slice), alloc)?;
+    encode_into(&mut encoded, slice)?;
+    Ok(encoded)
+}
+
+/// Decodes a run-length encoded slice (as produced by [`try_rle_encode`]) by
+/// repeating each item for the length of its run.
+///
+/// The decoded length is calculated (with overflow checking) before anything
+/// is allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let decoded = try_rle_decode(&[('a', 3), ('b', 1), ('c', 2)])?;
+/// assert_eq!(decoded, ['a', 'a', 'a', 'b', 'c', 'c']);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_rle_decode<T: Clone>(encoded: &[(T, usize)]) -> Result<Vec<T>, TryReserveError> {
+    let mut decoded = try_with_capacity(decoded_len(encoded)?)?;
+    decode_into(&mut decoded, encoded)?;
+    Ok(decoded)
+}
+
+/// Decodes a run-length encoded slice (as produced by [`try_rle_encode`]) into
+/// a `Vec` with the provided allocator by repeating each item for the length
+/// of its run.
+///
+/// The decoded length is calculated (with overflow checking) before anything
+/// is allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let decoded = try_rle_decode_in(&[('a', 3), ('b', 1), ('c', 2)], System)?;
+/// assert_eq!(decoded, ['a', 'a', 'a', 'b', 'c', 'c']);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_rle_decode_in<T: Clone, A: Allocator>(
+    encoded: &[(T, usize)],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut decoded = try_with_capacity_in(decoded_len(encoded)?, alloc)?;
+    decode_into(&mut decoded, encoded)?;
+    Ok(decoded)
+}
+
+/// Counts the number of runs of consecutive equal items in `slice`.
+fn run_count<T: PartialEq>(slice: &[T]) -> usize {
+    if slice.is_empty() {
+        0
+    } else {
+        1 + slice.windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+}
+
+/// Calculates the total length of the runs in `encoded`.
+fn decoded_len<T>(encoded: &[(T, usize)]) -> Result<usize, TryReserveError> {
+    encoded
+        .iter()
+        .try_fold(0usize, |len, (_, run_length)| len.checked_add(*run_length))
+        .ok_or_else(capacity_overflow)
+}
+
+/// Appends the run-length encoding of `slice` to `encoded`.
+fn encode_into<T: Clone + PartialEq>(
+    encoded: &mut impl FallibleVec<(T, usize)>,
+    mut slice: &[T],
+) -> Result<(), TryReserveError> {
+    while let Some(first) = slice.first() {
+        // Always consume the first item, even if it isn't equal to itself
+        // (e.g., NaN).
+        let run_length = 1 + slice[1..].iter().take_while(|item| *item == first).count();
+        encoded.try_push((first.clone(), run_length))?;
+        slice = &slice[run_length..];
+    }
+    Ok(())
+}
+
+/// Appends the items decoded from `encoded` to `decoded`.
+fn decode_into<T: Clone>(
+    decoded: &mut impl FallibleVec<T>,
+    encoded: &[(T, usize)],
+) -> Result<(), TryReserveError> {
+    for (item, run_length) in encoded {
+        for _ in 0..*run_length {
+            decoded.try_push(item.clone())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{alloc_error, TryClone, TryReserveError};
+use alloc::{alloc::Layout, rc::Rc, vec::Vec};
+use core::alloc::Allocator;
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+/// Fallible allocation equivalents for `make_mut` on reference-counted
+/// pointers.
+///
+/// Like [`Rc::make_mut`] and [`Arc::make_mut`], these are associated
+/// functions rather than methods so that they don't shadow methods on the
+/// inner value: call them as `Rc::try_make_mut(&mut rc)`.
+///
+/// NOTE: There is no implementation for `Rc<[T]>` or `Arc<[T]>` as the
+/// Standard Library does not provide a fallible way to allocate them.
+pub trait TryMakeMut {
+    /// The type of the value behind the pointer.
+    type Target: ?Sized;
+
+    /// Makes a mutable reference into the given pointer.
+    ///
+    /// If there are other pointers (strong or weak) to the same allocation,
+    /// then the inner value is cloned into a new allocation to ensure unique
+    /// ownership (clone-on-write).
+    ///
+    /// If allocating the clone fails, then an error is returned and `this` is
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::sync::Arc;
+    ///
+    /// let mut data = Arc::new(vec![1, 2]);
+    /// let other_data = Arc::clone(&data);
+    /// Arc::try_make_mut(&mut data)?.try_push(3)?;
+    /// assert_eq!(*data, [1, 2, 3]);
+    /// assert_eq!(*other_data, [1, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_make_mut(this: &mut Self) -> Result<&mut Self::Target, TryReserveError>;
+}
+
+impl<T: Clone, A: Allocator + Clone> TryMakeMut for Rc<Vec<T, A>> {
+    type Target = Vec<T, A>;
+
+    fn try_make_mut(this: &mut Self) -> Result<&mut Self::Target, TryReserveError> {
+        if Rc::get_mut(this).is_none() {
+            let clone = (**this).try_clone()?;
+            // The layout of the reference-counted allocation isn't public, so
+            // report the layout of the value instead.
+            *this = Rc::try_new(clone).map_err(|_| alloc_error(Layout::new::<Vec<T, A>>()))?;
+        }
+        Ok(Rc::get_mut(this).unwrap())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Clone, A: Allocator + Clone> TryMakeMut for Arc<Vec<T, A>> {
+    type Target = Vec<T, A>;
+
+    fn try_make_mut(this: &mut Self) -> Result<&mut Self::Target, TryReserveError> {
+        if Arc::get_mut(this).is_none() {
+            let clone = (**this).try_clone()?;
+            // The layout of the reference-counted allocation isn't public, so
+            // report the layout of the value instead.
+            *this = Arc::try_new(clone).map_err(|_| alloc_error(Layout::new::<Vec<T, A>>()))?;
+        }
+        Ok(Arc::get_mut(this).unwrap())
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{ImplementationDetails, TryReserveError};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+macro_rules! struct_shrink_on_drop {
+    { $(#[doc = $doc:expr])+ pub struct ShrinkOnDrop $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct ShrinkOnDrop<'a, T, F: FnOnce(TryReserveError) = fn(TryReserveError)> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct ShrinkOnDrop<
+            'a,
+            T,
+            A: Allocator,
+            F: FnOnce(TryReserveError) = fn(TryReserveError),
+        > $impl
+    }
+}
+
+struct_shrink_on_drop! {
+    /// A guard that attempts to shrink the capacity of a [`Vec`] when it goes
+    /// out of scope.
+    ///
+    /// The guard dereferences to the `Vec`, so it can be used in its place for
+    /// the duration of the scope. When dropped, the `Vec` is reallocated so
+    /// that its capacity is the larger of its length and the target capacity.
+    /// Shrinking is fallible: if the reallocation fails then the `Vec` is left
+    /// unchanged and the error is either ignored or passed to a callback.
+    ///
+    /// Created by [`shrink_on_drop`] or [`shrink_on_drop_with`].
+    pub struct ShrinkOnDrop {
+        #[cfg(not(feature = "allocator_api"))]
+        vec: &'a mut Vec<T>,
+        #[cfg(feature = "allocator_api")]
+        vec: &'a mut Vec<T, A>,
+        target_capacity: usize,
+        on_error: Option<F>,
+    }
+}
+
+macro_rules! impl_shrink_on_drop {
+    { impl $trait:ident for ShrinkOnDrop $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T, F: FnOnce(TryReserveError)> $trait for ShrinkOnDrop<'_, T, F> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator, F: FnOnce(TryReserveError)> $trait for ShrinkOnDrop<'_, T, A, F> $impl
+    }
+}
+
+impl_shrink_on_drop! {
+    impl Deref for ShrinkOnDrop {
+        #[cfg(not(feature = "allocator_api"))]
+        type Target = Vec<T>;
+        #[cfg(feature = "allocator_api")]
+        type Target = Vec<T, A>;
+
+        fn deref(&self) -> &Self::Target {
+            self.vec
+        }
+    }
+}
+
+impl_shrink_on_drop! {
+    impl DerefMut for ShrinkOnDrop {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            self.vec
+        }
+    }
+}
+
+impl_shrink_on_drop! {
+    impl Drop for ShrinkOnDrop {
+        fn drop(&mut self) {
+            if let Err(error) = self.vec.try_shrink_to_internal(self.target_capacity) {
+                if let Some(on_error) = self.on_error.take() {
+                    on_error(error);
+                }
+            }
+        }
+    }
+}
+
+/// Creates a guard that attempts to shrink the capacity of `vec` to
+/// `target_capacity` (or its length, if that is larger) when the guard goes out
+/// of scope, ignoring any allocation failure.
+///
+/// This is useful for long-lived buffers that may temporarily grow very large,
+/// for example while handling a single request.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut buffer = Vec::new();
+/// {
+///     let mut buffer = shrink_on_drop(&mut buffer, 16);
+///     buffer.try_resize(1024, 0u8)?;
+///     // ... process the request ...
+///     buffer.clear();
+/// }
+/// assert_eq!(buffer.capacity(), 16);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+pub fn shrink_on_drop<T>(vec: &mut Vec<T>, target_capacity: usize) -> ShrinkOnDrop<'_, T> {
+    ShrinkOnDrop {
+        vec,
+        target_capacity,
+        on_error: None,
+    }
+}
+
+/// Creates a guard that attempts to shrink the capacity of `vec` to
+/// `target_capacity` (or its length, if that is larger) when the guard goes out
+/// of scope, ignoring any allocation failure.
+///
+/// This is useful for long-lived buffers that may temporarily grow very large,
+/// for example while handling a single request.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut buffer = Vec::new();
+/// {
+///     let mut buffer = shrink_on_drop(&mut buffer, 16);
+///     buffer.try_resize(1024, 0u8)?;
+///     // ... process the request ...
+///     buffer.clear();
+/// }
+/// assert_eq!(buffer.capacity(), 16);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn shrink_on_drop<T, A: Allocator>(
+    vec: &mut Vec<T, A>,
+    target_capacity: usize,
+) -> ShrinkOnDrop<'_, T, A> {
+    ShrinkOnDrop {
+        vec,
+        target_capacity,
+        on_error: None,
+    }
+}
+
+/// Creates a guard that attempts to shrink the capacity of `vec` to
+/// `target_capacity` (or its length, if that is larger) when the guard goes out
+/// of scope, calling `on_error` if the reallocation fails.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut buffer = Vec::new();
+/// let mut shrink_failed = false;
+/// {
+///     let mut buffer = shrink_on_drop_with(&mut buffer, 0, |_| shrink_failed = true);
+///     buffer.try_push(1)?;
+///     buffer.clear();
+/// }
+/// assert_eq!(buffer.capacity(), 0);
+/// assert!(!shrink_failed);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(not(feature = "allocator_api"))]
+pub fn shrink_on_drop_with<T, F: FnOnce(TryReserveError)>(
+    vec: &mut Vec<T>,
+    target_capacity: usize,
+    on_error: F,
+) -> ShrinkOnDrop<'_, T, F> {
+    ShrinkOnDrop {
+        vec,
+        target_capacity,
+        on_error: Some(on_error),
+    }
+}
+
+/// Creates a guard that attempts to shrink the capacity of `vec` to
+/// `target_capacity` (or its length, if that is larger) when the guard goes out
+/// of scope, calling `on_error` if the reallocation fails.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let mut buffer = Vec::new();
+/// let mut shrink_failed = false;
+/// {
+///     let mut buffer = shrink_on_drop_with(&mut buffer, 0, |_| shrink_failed = true);
+///     buffer.try_push(1)?;
+///     buffer.clear();
+/// }
+/// assert_eq!(buffer.capacity(), 0);
+/// assert!(!shrink_failed);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn shrink_on_drop_with<T, A: Allocator, F: FnOnce(TryReserveError)>(
+    vec: &mut Vec<T, A>,
+    target_capacity: usize,
+    on_error: F,
+) -> ShrinkOnDrop<'_, T, A, F> {
+    ShrinkOnDrop {
+        vec,
+        target_capacity,
+        on_error: Some(on_error),
+    }
+}
@@ -0,0 +1,867 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, try_with_capacity, FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::ptr;
+
+#[cfg(feature = "allocator_api")]
+use crate::try_with_capacity_in;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Fallible allocation methods for slices.
+pub trait FallibleSlice<T> {
+    /// Divides the slice into two at an index and clones each half into its
+    /// own `Vec`.
+    ///
+    /// The first `Vec` will contain all items from `[0, mid)` (excluding the
+    /// index `mid` itself) and the second will contain all items from
+    /// `[mid, len)` (excluding the index `len` itself).
+    ///
+    /// Both `Vec`s are allocated before any item is cloned: if either
+    /// allocation fails then any allocation already made is freed and no items
+    /// are cloned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the `Vec`s and any items already
+    /// cloned into them are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let (left, right) = [1, 2, 3, 4, 5].try_split_at_owned(2)?;
+    /// assert_eq!(left, [1, 2]);
+    /// assert_eq!(right, [3, 4, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_split_at_owned(&self, mid: usize) -> Result<(Vec<T>, Vec<T>), TryReserveError>
+    where
+        T: Clone;
+
+    /// Divides the slice into two at an index and clones each half into its
+    /// own `Vec` with the provided allocator.
+    ///
+    /// The first `Vec` will contain all items from `[0, mid)` (excluding the
+    /// index `mid` itself) and the second will contain all items from
+    /// `[mid, len)` (excluding the index `len` itself).
+    ///
+    /// Both `Vec`s are allocated before any item is cloned: if either
+    /// allocation fails then any allocation already made is freed and no items
+    /// are cloned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the `Vec`s and any items already
+    /// cloned into them are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let (left, right) = [1, 2, 3, 4, 5].try_split_at_owned_in(2, System)?;
+    /// assert_eq!(left, [1, 2]);
+    /// assert_eq!(right, [3, 4, 5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    #[allow(clippy::type_complexity)]
+    fn try_split_at_owned_in<A: Allocator + Clone>(
+        &self,
+        mid: usize,
+        alloc: A,
+    ) -> Result<(Vec<T, A>, Vec<T, A>), TryReserveError>
+    where
+        T: Clone;
+
+    /// Clones the slice into a new `Vec`, skipping consecutive repeated items
+    /// (i.e., the fallible equivalent of `to_vec` followed by `dedup`).
+    ///
+    /// The slice is unchanged. Space in the returned `Vec` is reserved as
+    /// items are added, so if reserving fails then the `Vec` (and the items
+    /// already cloned into it) are dropped and the error is returned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` or `eq` panics, then the returned `Vec` and any
+    /// items already cloned into it are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let values = [1, 2, 2, 3, 2];
+    /// assert_eq!(values.try_dedup_to_vec()?, [1, 2, 3, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_dedup_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: PartialEq + Clone;
+
+    /// Clones the slice into a new `Vec`, skipping consecutive items that
+    /// satisfy the given equality relation.
+    ///
+    /// As with `Vec::dedup_by`, `same_bucket` is passed each item and the
+    /// last item that was kept, and the item is skipped if it returns `true`.
+    ///
+    /// The slice is unchanged. Space in the returned `Vec` is reserved as
+    /// items are added, so if reserving fails then the `Vec` (and the items
+    /// already cloned into it) are dropped and the error is returned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` or `same_bucket` panics, then the returned `Vec`
+    /// and any items already cloned into it are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let values = ["foo", "FOO", "bar", "Bar", "baz", "bar"];
+    /// let deduped = values.try_dedup_by_to_vec(|a, b| a.eq_ignore_ascii_case(b))?;
+    /// assert_eq!(deduped, ["foo", "bar", "baz", "bar"]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_dedup_by_to_vec<F: FnMut(&T, &T) -> bool>(
+        &self,
+        same_bucket: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Splits the slice into runs of consecutive items and clones each run
+    /// into its own `Vec` (the fallible, owned equivalent of
+    /// `slice::chunk_by`).
+    ///
+    /// `pred` is passed each pair of adjacent items, and a new run is started
+    /// between them if it returns `false`. Each run is allocated with exactly
+    /// its length; if any allocation fails then everything allocated so far is
+    /// dropped and the error is returned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` or `pred` panics, then the runs and any items
+    /// already cloned into them are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let values = [1, 1, 2, 3, 3, 3, 1];
+    /// let runs = values.try_chunk_by_collect(|a, b| a == b)?;
+    /// assert_eq!(runs, [&[1, 1][..], &[2], &[3, 3, 3], &[1]]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_chunk_by_collect<F: FnMut(&T, &T) -> bool>(
+        &self,
+        pred: F,
+    ) -> Result<Vec<Vec<T>>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Splits the slice at each item that matches `pred` and clones each piece
+    /// into its own `Vec` (the fallible, owned equivalent of `slice::split`).
+    ///
+    /// The matched items are not included in any piece. As with
+    /// `slice::split`, matches at the start or end of the slice (or that are
+    /// adjacent to each other) produce empty pieces, and an empty slice
+    /// produces a single empty piece. Each piece is allocated with exactly its
+    /// length; if any allocation fails then everything allocated so far is
+    /// dropped and the error is returned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` or `pred` panics, then the pieces and any items
+    /// already cloned into them are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let line = b"GET /index.html HTTP/1.1";
+    /// let parts = line.try_split_collect(|&b| b == b' ')?;
+    /// assert_eq!(parts, [&b"GET"[..], b"/index.html", b"HTTP/1.1"]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_split_collect<F: FnMut(&T) -> bool>(
+        &self,
+        pred: F,
+    ) -> Result<Vec<Vec<T>>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Creates a `Vec` by copying the slice `n` times (i.e., the fallible
+    /// equivalent of `slice::repeat`).
+    ///
+    /// The `Vec` is allocated with exactly the required capacity, and the
+    /// items are then copied in, doubling the copied region each time. If the
+    /// length would overflow or the allocation fails, then the error is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// assert_eq!([1, 2].try_repeat(3)?, [1, 2, 1, 2, 1, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_repeat(&self, n: usize) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Copy;
+
+    /// Creates a `Vec` that uses the provided allocator by copying the slice
+    /// `n` times.
+    ///
+    /// See [`try_repeat`](FallibleSlice::try_repeat) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// assert_eq!(b"ab".try_repeat_in(2, System)?, b"abab");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_repeat_in<A: Allocator>(&self, n: usize, alloc: A) -> Result<Vec<T, A>, TryReserveError>
+    where
+        T: Copy;
+}
+
+impl<T> FallibleSlice<T> for [T] {
+    fn try_split_at_owned(&self, mid: usize) -> Result<(Vec<T>, Vec<T>), TryReserveError>
+    where
+        T: Clone,
+    {
+        let (left, right) = self.split_at(mid);
+        let mut left_vec = try_with_capacity(left.len())?;
+        let mut right_vec = try_with_capacity(right.len())?;
+        left_vec.try_extend_from_slice(left)?;
+        right_vec.try_extend_from_slice(right)?;
+        Ok((left_vec, right_vec))
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[allow(clippy::type_complexity)]
+    fn try_split_at_owned_in<A: Allocator + Clone>(
+        &self,
+        mid: usize,
+        alloc: A,
+    ) -> Result<(Vec<T, A>, Vec<T, A>), TryReserveError>
+    where
+        T: Clone,
+    {
+        let (left, right) = self.split_at(mid);
+        let mut left_vec = try_with_capacity_in(left.len(), alloc.clone())?;
+        let mut right_vec = try_with_capacity_in(right.len(), alloc)?;
+        left_vec.try_extend_from_slice(left)?;
+        right_vec.try_extend_from_slice(right)?;
+        Ok((left_vec, right_vec))
+    }
+
+    fn try_dedup_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: PartialEq + Clone,
+    {
+        self.try_dedup_by_to_vec(|a, b| a == b)
+    }
+
+    fn try_dedup_by_to_vec<F: FnMut(&T, &T) -> bool>(
+        &self,
+        mut same_bucket: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut deduped = Vec::new();
+        let Some((first, rest)) = self.split_first() else {
+            return Ok(deduped);
+        };
+        deduped.try_push(first.clone())?;
+        let mut last = first;
+        for item in rest {
+            if !same_bucket(item, last) {
+                deduped.try_push(item.clone())?;
+                last = item;
+            }
+        }
+        Ok(deduped)
+    }
+
+    fn try_chunk_by_collect<F: FnMut(&T, &T) -> bool>(
+        &self,
+        mut pred: F,
+    ) -> Result<Vec<Vec<T>>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        for end in 1..=self.len() {
+            if end == self.len() || !pred(&self[end - 1], &self[end]) {
+                let run = &self[start..end];
+                let mut vec = Vec::new();
+                vec.try_reserve_exact(run.len())?;
+                vec.try_extend_from_slice(run)?;
+                runs.try_push(vec)?;
+                start = end;
+            }
+        }
+        Ok(runs)
+    }
+
+    fn try_split_collect<F: FnMut(&T) -> bool>(
+        &self,
+        pred: F,
+    ) -> Result<Vec<Vec<T>>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut pieces = Vec::new();
+        for piece in self.split(pred) {
+            let mut vec = Vec::new();
+            vec.try_reserve_exact(piece.len())?;
+            vec.try_extend_from_slice(piece)?;
+            pieces.try_push(vec)?;
+        }
+        Ok(pieces)
+    }
+
+    fn try_repeat(&self, n: usize) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Copy,
+    {
+        let total = self.len().checked_mul(n).ok_or_else(capacity_overflow)?;
+        let mut vec = Vec::new();
+        vec.try_reserve_exact(total)?;
+        unsafe {
+            repeat_into(self, vec.as_mut_ptr(), total);
+            vec.set_len(total);
+        }
+        Ok(vec)
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn try_repeat_in<A: Allocator>(&self, n: usize, alloc: A) -> Result<Vec<T, A>, TryReserveError>
+    where
+        T: Copy,
+    {
+        let total = self.len().checked_mul(n).ok_or_else(capacity_overflow)?;
+        let mut vec = Vec::new_in(alloc);
+        vec.try_reserve_exact(total)?;
+        unsafe {
+            repeat_into(self, vec.as_mut_ptr(), total);
+            vec.set_len(total);
+        }
+        Ok(vec)
+    }
+}
+
+/// Fallible allocation equivalents for copying a slice into a new [`Vec`]
+/// (i.e., `slice::to_vec`).
+pub trait TryToVec<T> {
+    /// Clones the slice into a new `Vec` with a capacity of exactly the
+    /// slice's length.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the new `Vec` and any items already
+    /// cloned into it are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let names = ["a".to_string(), "b".to_string()];
+    /// let vec = names.try_to_vec()?;
+    /// assert_eq!(vec, names);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Clones the slice into a new `Vec` that uses the provided allocator,
+    /// with a capacity of exactly the slice's length.
+    ///
+    /// # Panic safety
+    ///
+    /// If a call to `clone` panics, then the new `Vec` and any items already
+    /// cloned into it are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    /// use std::alloc::System;
+    ///
+    /// let vec = [1, 2, 3].try_to_vec_in(System)?;
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    fn try_to_vec_in<A: Allocator>(&self, alloc: A) -> Result<Vec<T, A>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Copies the slice into a new `Vec` with a capacity of exactly the
+    /// slice's length.
+    ///
+    /// This is the same as [`try_to_vec`](TryToVec::try_to_vec), except that
+    /// it requires `T: Copy` so that the items are copied with a single
+    /// `memcpy` rather than being cloned one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let packet = b"header+payload";
+    /// let vec = packet.try_copy_to_vec()?;
+    /// assert_eq!(vec, packet);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_copy_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Copy;
+}
+
+impl<T> TryToVec<T> for [T] {
+    fn try_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut vec = Vec::new();
+        vec.try_reserve_exact(self.len())?;
+        vec.try_extend_from_slice(self)?;
+        Ok(vec)
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn try_to_vec_in<A: Allocator>(&self, alloc: A) -> Result<Vec<T, A>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut vec = Vec::new_in(alloc);
+        vec.try_reserve_exact(self.len())?;
+        vec.try_extend_from_slice(self)?;
+        Ok(vec)
+    }
+
+    fn try_copy_to_vec(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Copy,
+    {
+        let mut vec = Vec::new();
+        vec.try_reserve_exact(self.len())?;
+        vec.try_extend_from_copy_slice(self)?;
+        Ok(vec)
+    }
+}
+
+/// Transposes a matrix stored as a slice of rows, cloning the items into a new
+/// `Vec` of rows.
+///
+/// Every row must have the same length: this is validated before anything is
+/// allocated.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the transposed rows and any items already
+/// cloned into them are dropped.
+///
+/// # Panics
+///
+/// Panics if the rows do not all have the same length.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let matrix = [try_vec![1, 2, 3]?, try_vec![4, 5, 6]?];
+/// let transposed = try_transpose(&matrix)?;
+/// assert_eq!(transposed, [[1, 4], [2, 5], [3, 6]]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_transpose<T: Clone, R: AsRef<[T]>>(rows: &[R]) -> Result<Vec<Vec<T>>, TryReserveError> {
+    let columns = column_count(rows);
+    let mut transposed = try_with_capacity(columns)?;
+    for column in 0..columns {
+        let mut new_row = try_with_capacity(rows.len())?;
+        for row in rows {
+            new_row.try_push(row.as_ref()[column].clone())?;
+        }
+        transposed.try_push(new_row)?;
+    }
+    Ok(transposed)
+}
+
+/// Transposes a matrix stored as a slice of rows, cloning the items into a new
+/// `Vec` of rows with the provided allocator.
+///
+/// Every row must have the same length: this is validated before anything is
+/// allocated.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the transposed rows and any items already
+/// cloned into them are dropped.
+///
+/// # Panics
+///
+/// Panics if the rows do not all have the same length.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let matrix = [try_vec![1, 2, 3]?, try_vec![4, 5, 6]?];
+/// let transposed = try_transpose_in(&matrix, System)?;
+/// assert_eq!(transposed, [[1, 4], [2, 5], [3, 6]]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_transpose_in<T: Clone, R: AsRef<[T]>, A: Allocator + Clone>(
+    rows: &[R],
+    alloc: A,
+) -> Result<Vec<Vec<T, A>, A>, TryReserveError> {
+    let columns = column_count(rows);
+    let mut transposed = try_with_capacity_in(columns, alloc.clone())?;
+    for column in 0..columns {
+        let mut new_row = try_with_capacity_in(rows.len(), alloc.clone())?;
+        for row in rows {
+            new_row.try_push(row.as_ref()[column].clone())?;
+        }
+        transposed.try_push(new_row)?;
+    }
+    Ok(transposed)
+}
+
+/// Interleaves the items from several slices into a new `Vec`, taking one
+/// item from each slice in turn (i.e., round-robin).
+///
+/// Once a slice has run out of items it is skipped, so the returned `Vec`
+/// contains every item from every slice. The total length is calculated (with
+/// overflow checking) before anything is allocated, so the returned `Vec` is
+/// allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let left = [1, 3, 5];
+/// let right = [2, 4, 6, 8];
+/// let interleaved = try_interleave(&[&left[..], &right[..]])?;
+/// assert_eq!(interleaved, [1, 2, 3, 4, 5, 6, 8]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_interleave<T: Clone, S: AsRef<[T]>>(slices: &[S]) -> Result<Vec<T>, TryReserveError> {
+    let mut interleaved = try_with_capacity(total_len(slices)?)?;
+    interleave_into(&mut interleaved, slices)?;
+    Ok(interleaved)
+}
+
+/// Interleaves the items from several slices into a new `Vec` with the
+/// provided allocator, taking one item from each slice in turn (i.e.,
+/// round-robin).
+///
+/// Once a slice has run out of items it is skipped, so the returned `Vec`
+/// contains every item from every slice. The total length is calculated (with
+/// overflow checking) before anything is allocated, so the returned `Vec` is
+/// allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let left = [1, 3, 5];
+/// let right = [2, 4, 6, 8];
+/// let interleaved = try_interleave_in(&[&left[..], &right[..]], System)?;
+/// assert_eq!(interleaved, [1, 2, 3, 4, 5, 6, 8]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_interleave_in<T: Clone, S: AsRef<[T]>, A: Allocator>(
+    slices: &[S],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut interleaved = try_with_capacity_in(total_len(slices)?, alloc)?;
+    interleave_into(&mut interleaved, slices)?;
+    Ok(interleaved)
+}
+
+/// Concatenates several slices into a new `Vec` (i.e., the fallible equivalent
+/// of `slice::concat`).
+///
+/// The total length is calculated (with overflow checking) before anything is
+/// allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let header = b"GET ";
+/// let path = b"/index.html";
+/// let joined = try_concat(&[&header[..], &path[..]])?;
+/// assert_eq!(joined, b"GET /index.html");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_concat<T: Clone, S: AsRef<[T]>>(slices: &[S]) -> Result<Vec<T>, TryReserveError> {
+    let mut concatenated = try_with_capacity(total_len(slices)?)?;
+    for slice in slices {
+        concatenated.try_extend_from_slice(slice.as_ref())?;
+    }
+    Ok(concatenated)
+}
+
+/// Concatenates several slices into a new `Vec` with the provided allocator.
+///
+/// The total length is calculated (with overflow checking) before anything is
+/// allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let joined = try_concat_in(&[[1, 2], [3, 4]], System)?;
+/// assert_eq!(joined, [1, 2, 3, 4]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_concat_in<T: Clone, S: AsRef<[T]>, A: Allocator>(
+    slices: &[S],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut concatenated = try_with_capacity_in(total_len(slices)?, alloc)?;
+    for slice in slices {
+        concatenated.try_extend_from_slice(slice.as_ref())?;
+    }
+    Ok(concatenated)
+}
+
+/// Concatenates several slices into a new `Vec`, placing `separator` between
+/// each of them (i.e., the fallible equivalent of `slice::join`).
+///
+/// To separate the slices with a single item, pass a one-item slice (e.g.,
+/// `&[b',']`). The total length is calculated (with overflow checking) before
+/// anything is allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #[macro_use] extern crate fallible_vec;
+/// use fallible_vec::*;
+///
+/// let fields = [try_vec![b'a']?, try_vec![b'b', b'c']?];
+/// let line = try_join(&fields, b", ")?;
+/// assert_eq!(line, b"a, bc");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_join<T: Clone, S: AsRef<[T]>>(
+    slices: &[S],
+    separator: &[T],
+) -> Result<Vec<T>, TryReserveError> {
+    let mut joined = try_with_capacity(joined_len(slices, separator)?)?;
+    join_into(&mut joined, slices, separator)?;
+    Ok(joined)
+}
+
+/// Concatenates several slices into a new `Vec` with the provided allocator,
+/// placing `separator` between each of them.
+///
+/// To separate the slices with a single item, pass a one-item slice (e.g.,
+/// `&[b',']`). The total length is calculated (with overflow checking) before
+/// anything is allocated, so the returned `Vec` is allocated exactly once.
+///
+/// # Panic safety
+///
+/// If a call to `clone` panics, then the returned `Vec` and any items already
+/// cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let joined = try_join_in(&[&[1, 2][..], &[3]], &[0], System)?;
+/// assert_eq!(joined, [1, 2, 0, 3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_join_in<T: Clone, S: AsRef<[T]>, A: Allocator>(
+    slices: &[S],
+    separator: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut joined = try_with_capacity_in(joined_len(slices, separator)?, alloc)?;
+    join_into(&mut joined, slices, separator)?;
+    Ok(joined)
+}
+
+/// Calculates the sum of the lengths of `slices`.
+fn total_len<T, S: AsRef<[T]>>(slices: &[S]) -> Result<usize, TryReserveError> {
+    slices
+        .iter()
+        .try_fold(0usize, |len, slice| len.checked_add(slice.as_ref().len()))
+        .ok_or_else(capacity_overflow)
+}
+
+/// Calculates the length of `slices` joined with `separator`.
+fn joined_len<T, S: AsRef<[T]>>(slices: &[S], separator: &[T]) -> Result<usize, TryReserveError> {
+    let separators_len = separator
+        .len()
+        .checked_mul(slices.len().saturating_sub(1))
+        .ok_or_else(capacity_overflow)?;
+    separators_len
+        .checked_add(total_len(slices)?)
+        .ok_or_else(capacity_overflow)
+}
+
+/// Clones the items from `slices` into `joined`, with `separator` between each
+/// slice.
+fn join_into<T: Clone, S: AsRef<[T]>>(
+    joined: &mut impl FallibleVec<T>,
+    slices: &[S],
+    separator: &[T],
+) -> Result<(), TryReserveError> {
+    for (index, slice) in slices.iter().enumerate() {
+        if index != 0 {
+            joined.try_extend_from_slice(separator)?;
+        }
+        joined.try_extend_from_slice(slice.as_ref())?;
+    }
+    Ok(())
+}
+
+/// Clones the items from `slices` into `interleaved`, taking one item from each
+/// slice in turn.
+fn interleave_into<T: Clone, S: AsRef<[T]>>(
+    interleaved: &mut impl FallibleVec<T>,
+    slices: &[S],
+) -> Result<(), TryReserveError> {
+    let longest = slices
+        .iter()
+        .map(|slice| slice.as_ref().len())
+        .max()
+        .unwrap_or(0);
+    for index in 0..longest {
+        for slice in slices {
+            if let Some(item) = slice.as_ref().get(index) {
+                interleaved.try_push(item.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes copies of `src` to `dest` until `total` items have been written,
+/// doubling the size of each copy.
+///
+/// # Safety
+///
+/// `dest` must be valid for writes of `total` items, and `total` must be a
+/// multiple of the length of `src`.
+unsafe fn repeat_into<T: Copy>(src: &[T], dest: *mut T, total: usize) {
+    if total == 0 {
+        return;
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(src.as_ptr(), dest, src.len());
+        let mut filled = src.len();
+        while filled <= total - filled {
+            ptr::copy_nonoverlapping(dest, dest.add(filled), filled);
+            filled *= 2;
+        }
+        ptr::copy_nonoverlapping(dest, dest.add(filled), total - filled);
+    }
+}
+
+/// Gets the number of columns in a matrix, validating that every row has the
+/// same length.
+fn column_count<T, R: AsRef<[T]>>(rows: &[R]) -> usize {
+    let columns = rows.first().map_or(0, |row| row.as_ref().len());
+    for (index, row) in rows.iter().enumerate() {
+        let len = row.as_ref().len();
+        assert!(
+            len == columns,
+            "row {index} has length {len}, but the first row has length {columns}"
+        );
+    }
+    columns
+}
@@ -0,0 +1,281 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Marks the end of the free list.
+const NO_FREE_SLOT: usize = usize::MAX;
+
+/// A key for a value stored in a [`SlotMap`].
+///
+/// A key records the generation of its slot as well as its index, so a key
+/// for a value that has been removed will not access a value that was later
+/// inserted into the same slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SlotKey {
+    index: usize,
+    generation: u32,
+}
+
+enum Entry<T> {
+    Occupied(T),
+    Vacant { next_free: usize },
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+macro_rules! struct_slot_map {
+    { $(#[doc = $doc:expr])+ pub struct SlotMap $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct SlotMap<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct SlotMap<T, A: Allocator = Global> $impl
+    }
+}
+
+struct_slot_map! {
+    /// A collection that stores values in slots and returns a [`SlotKey`] for
+    /// each inserted value (also known as a generational arena).
+    ///
+    /// Inserting, removing and accessing values are all `O(1)`. The slots of
+    /// removed values are reused by later insertions, but each slot has a
+    /// generation that is incremented when its value is removed, so stale
+    /// keys are detected rather than accessing the wrong value. Once a slot's
+    /// generation is exhausted the slot is retired instead of being reused, so
+    /// a stale key can never match.
+    ///
+    /// Space for the slots is allocated fallibly: if it cannot be allocated,
+    /// then [`try_insert`](SlotMap::try_insert) returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut entities = SlotMap::new();
+    /// let player = entities.try_insert("player")?;
+    /// let enemy = entities.try_insert("enemy")?;
+    /// assert_eq!(entities.remove(enemy), Some("enemy"));
+    ///
+    /// // The slot is reused, but the old key doesn't access the new value.
+    /// let item = entities.try_insert("item")?;
+    /// assert_eq!(entities.get(enemy), None);
+    /// assert_eq!(entities.get(item), Some(&"item"));
+    /// assert_eq!(entities.get(player), Some(&"player"));
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    pub struct SlotMap {
+        #[cfg(not(feature = "allocator_api"))]
+        slots: Vec<Slot<T>>,
+        #[cfg(feature = "allocator_api")]
+        slots: Vec<Slot<T>, A>,
+        /// The index of the first vacant slot to reuse, or `NO_FREE_SLOT`.
+        free_head: usize,
+        len: usize,
+    }
+}
+
+macro_rules! impl_slot_map {
+    { impl SlotMap $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> SlotMap<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> SlotMap<T, A> $impl
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Creates a new, empty `SlotMap`.
+    ///
+    /// This does not allocate.
+    pub const fn new() -> Self {
+        SlotMap {
+            slots: Vec::new(),
+            free_head: NO_FREE_SLOT,
+            len: 0,
+        }
+    }
+
+    /// Creates a new, empty `SlotMap` with space for at least `capacity`
+    /// values.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        let mut map = Self::new();
+        map.try_reserve(capacity)?;
+        Ok(map)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> SlotMap<T, A> {
+    /// Creates a new, empty `SlotMap` that will use the provided allocator.
+    ///
+    /// This does not allocate.
+    pub fn new_in(alloc: A) -> Self {
+        SlotMap {
+            slots: Vec::new_in(alloc),
+            free_head: NO_FREE_SLOT,
+            len: 0,
+        }
+    }
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_slot_map! {
+    impl SlotMap {
+        /// Returns the number of values in the `SlotMap`.
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        /// Returns `true` if the `SlotMap` contains no values.
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// Returns the number of slots that the `SlotMap` can hold without
+        /// reallocating (including slots that are currently in use).
+        pub fn capacity(&self) -> usize {
+            self.slots.capacity()
+        }
+
+        /// Reserves space for at least `additional` more values to be
+        /// inserted, taking vacant slots that will be reused into account.
+        pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+            let vacant = self.slots.len() - self.len;
+            self.slots.try_reserve(additional.saturating_sub(vacant))
+        }
+
+        /// Inserts `value` into the `SlotMap`, returning the key to access
+        /// it.
+        ///
+        /// If there is no vacant slot to reuse and allocating a new slot
+        /// fails, then an error is returned and `value` is dropped.
+        pub fn try_insert(&mut self, value: T) -> Result<SlotKey, TryReserveError> {
+            let index = if self.free_head != NO_FREE_SLOT {
+                let index = self.free_head;
+                let slot = &mut self.slots[index];
+                let Entry::Vacant { next_free } = slot.entry else {
+                    unreachable!("slot on the free list is occupied");
+                };
+                self.free_head = next_free;
+                slot.entry = Entry::Occupied(value);
+                index
+            } else {
+                self.slots.try_push(Slot {
+                    generation: 0,
+                    entry: Entry::Occupied(value),
+                })?;
+                self.slots.len() - 1
+            };
+            self.len += 1;
+            Ok(SlotKey {
+                index,
+                generation: self.slots[index].generation,
+            })
+        }
+
+        /// Removes the value for `key` from the `SlotMap` and returns it, or
+        /// returns `None` if `key` is stale.
+        pub fn remove(&mut self, key: SlotKey) -> Option<T> {
+            self.get(key)?;
+            let slot = &mut self.slots[key.index];
+            // Retire the slot if its generation is exhausted, otherwise put
+            // it on the free list to be reused.
+            let (generation, next_free) = match slot.generation.checked_add(1) {
+                Some(generation) => (
+                    generation,
+                    core::mem::replace(&mut self.free_head, key.index),
+                ),
+                None => (slot.generation, NO_FREE_SLOT),
+            };
+            slot.generation = generation;
+            let Entry::Occupied(value) =
+                core::mem::replace(&mut slot.entry, Entry::Vacant { next_free })
+            else {
+                unreachable!("slot was checked to be occupied");
+            };
+            self.len -= 1;
+            Some(value)
+        }
+
+        /// Returns `true` if the `SlotMap` contains a value for `key`.
+        pub fn contains_key(&self, key: SlotKey) -> bool {
+            self.get(key).is_some()
+        }
+
+        /// Returns a reference to the value for `key`, or `None` if `key` is
+        /// stale.
+        pub fn get(&self, key: SlotKey) -> Option<&T> {
+            match self.slots.get(key.index)? {
+                Slot {
+                    generation,
+                    entry: Entry::Occupied(value),
+                } if *generation == key.generation => Some(value),
+                _ => None,
+            }
+        }
+
+        /// Returns a mutable reference to the value for `key`, or `None` if
+        /// `key` is stale.
+        pub fn get_mut(&mut self, key: SlotKey) -> Option<&mut T> {
+            match self.slots.get_mut(key.index)? {
+                Slot {
+                    generation,
+                    entry: Entry::Occupied(value),
+                } if *generation == key.generation => Some(value),
+                _ => None,
+            }
+        }
+
+        /// Returns an iterator over the keys and values in the `SlotMap`, in
+        /// slot order.
+        pub fn iter(&self) -> impl Iterator<Item = (SlotKey, &T)> + '_ {
+            self.slots
+                .iter()
+                .enumerate()
+                .filter_map(|(index, slot)| match &slot.entry {
+                    Entry::Occupied(value) => Some((
+                        SlotKey {
+                            index,
+                            generation: slot.generation,
+                        },
+                        value,
+                    )),
+                    Entry::Vacant { .. } => None,
+                })
+        }
+
+        /// Removes all values from the `SlotMap`, invalidating all existing
+        /// keys.
+        ///
+        /// This does not free the slots: they remain available for reuse.
+        pub fn clear(&mut self) {
+            for index in 0..self.slots.len() {
+                let key = SlotKey {
+                    index,
+                    generation: self.slots[index].generation,
+                };
+                self.remove(key);
+            }
+        }
+    }
+}
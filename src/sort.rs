@@ -0,0 +1,442 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+// Based on the merge sort from the Rust Standard Library: library/alloc/src/slice.rs
+
+use crate::{try_with_capacity, FallibleVec, TryReserveError, TryToVec};
+use alloc::vec::Vec;
+use core::{cmp::Ordering, mem::ManuallyDrop, ptr};
+
+/// Slices of up to this length are sorted using insertion sort.
+const MAX_INSERTION: usize = 20;
+
+/// Fallible allocation equivalents for the stable sorting methods on slices.
+///
+/// The standard library's stable sort allocates a scratch buffer and aborts if
+/// that allocation fails. These methods instead return an error and leave the
+/// slice unchanged.
+pub trait FallibleSliceSort<T> {
+    /// Sorts the slice, preserving the order of equal items.
+    ///
+    /// This is a merge sort that allocates a scratch buffer of half the
+    /// slice's length (slices of up to 20 items are sorted without
+    /// allocating). If allocating the buffer fails, then the slice is left
+    /// unchanged and the error is returned.
+    ///
+    /// # Panic safety
+    ///
+    /// If a comparison panics, then the slice will contain all of its
+    /// original items, but in an unspecified order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = [-5, 4, 1, -3, 2];
+    /// v.try_sort()?;
+    /// assert_eq!(v, [-5, -3, 1, 2, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sort(&mut self) -> Result<(), TryReserveError>
+    where
+        T: Ord;
+
+    /// Sorts the slice with a comparator function, preserving the order of
+    /// equal items.
+    ///
+    /// See [`try_sort`](FallibleSliceSort::try_sort) for details of the
+    /// allocation behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = [5, 4, 1, 3, 2];
+    /// v.try_sort_by(|a, b| b.cmp(a))?;
+    /// assert_eq!(v, [5, 4, 3, 2, 1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sort_by<F: FnMut(&T, &T) -> Ordering>(
+        &mut self,
+        compare: F,
+    ) -> Result<(), TryReserveError>;
+
+    /// Sorts the slice with a key extraction function, preserving the order
+    /// of equal items.
+    ///
+    /// The key function is called for both items on every comparison. See
+    /// [`try_sort`](FallibleSliceSort::try_sort) for details of the
+    /// allocation behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = vec![-5i32, 4, 1, -3, 2];
+    /// v.try_sort_by_key(|k| k.abs())?;
+    /// assert_eq!(v, [1, 2, -3, 4, -5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, f: F) -> Result<(), TryReserveError>;
+
+    /// Sorts the slice with a key extraction function, preserving the order
+    /// of equal items, and calling the key function only once per item.
+    ///
+    /// The keys are cached in a `Vec` (along with each item's index) that is
+    /// allocated fallibly before the key function is first called. If the
+    /// allocation fails, then the slice is left unchanged and the error is
+    /// returned. Sorting the cache and then moving the items into place
+    /// doesn't allocate.
+    ///
+    /// # Panic safety
+    ///
+    /// If the key function or a comparison panics, then the slice is left
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut v = [-5i32, 4, 32, -3, 2];
+    /// v.try_sort_by_cached_key(|k| k.to_string())?;
+    /// assert_eq!(v, [-3, -5, 2, 32, 4]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sort_by_cached_key<K: Ord, F: FnMut(&T) -> K>(
+        &mut self,
+        f: F,
+    ) -> Result<(), TryReserveError>;
+
+    /// Clones the slice into a new `Vec` and sorts it, preserving the order of
+    /// equal items.
+    ///
+    /// The copy is allocated with exactly the slice's length and then sorted
+    /// with [`try_sort`](FallibleSliceSort::try_sort). If either allocation
+    /// fails, then the copy is dropped and the error is returned; the slice is
+    /// never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let v = [-5, 4, 1, -3, 2];
+    /// assert_eq!(v.try_sorted_copy()?, [-5, -3, 1, 2, 4]);
+    /// assert_eq!(v, [-5, 4, 1, -3, 2]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sorted_copy(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Ord + Clone;
+
+    /// Clones the slice into a new `Vec` and sorts it with a comparator
+    /// function, preserving the order of equal items.
+    ///
+    /// See [`try_sorted_copy`](FallibleSliceSort::try_sorted_copy) for
+    /// details of the allocation behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let v = [5, 4, 1, 3, 2];
+    /// assert_eq!(v.try_sorted_copy_by(|a, b| b.cmp(a))?, [5, 4, 3, 2, 1]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sorted_copy_by<F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        compare: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone;
+
+    /// Clones the slice into a new `Vec` and sorts it with a key extraction
+    /// function, preserving the order of equal items.
+    ///
+    /// See [`try_sorted_copy`](FallibleSliceSort::try_sorted_copy) for
+    /// details of the allocation behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let v = [-5i32, 4, 1, -3, 2];
+    /// assert_eq!(v.try_sorted_copy_by_key(|k| k.abs())?, [1, 2, -3, 4, -5]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_sorted_copy_by_key<K: Ord, F: FnMut(&T) -> K>(
+        &self,
+        f: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone;
+}
+
+impl<T> FallibleSliceSort<T> for [T] {
+    fn try_sort(&mut self) -> Result<(), TryReserveError>
+    where
+        T: Ord,
+    {
+        try_merge_sort(self, T::lt)
+    }
+
+    fn try_sort_by<F: FnMut(&T, &T) -> Ordering>(
+        &mut self,
+        mut compare: F,
+    ) -> Result<(), TryReserveError> {
+        try_merge_sort(self, |a, b| compare(a, b) == Ordering::Less)
+    }
+
+    fn try_sort_by_key<K: Ord, F: FnMut(&T) -> K>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), TryReserveError> {
+        try_merge_sort(self, |a, b| f(a).lt(&f(b)))
+    }
+
+    fn try_sort_by_cached_key<K: Ord, F: FnMut(&T) -> K>(
+        &mut self,
+        f: F,
+    ) -> Result<(), TryReserveError> {
+        let len = self.len();
+        if len < 2 {
+            return Ok(());
+        }
+
+        let mut indices: Vec<(K, usize)> = Vec::new();
+        indices.try_reserve_exact(len)?;
+        // Cannot fail as the space has already been reserved.
+        let _ = indices.try_extend(self.iter().map(f).enumerate().map(|(i, k)| (k, i)));
+        // The indices are unique, so an unstable sort of the cache is a
+        // stable sort of the items.
+        indices.sort_unstable();
+
+        // Move each item into place, following the chain of earlier swaps to
+        // find where the item that belongs at `i` has been moved to.
+        for i in 0..len {
+            let mut index = indices[i].1;
+            while index < i {
+                index = indices[index].1;
+            }
+            indices[i].1 = index;
+            self.swap(i, index);
+        }
+        Ok(())
+    }
+
+    fn try_sorted_copy(&self) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Ord + Clone,
+    {
+        let mut copy = self.try_to_vec()?;
+        copy.try_sort()?;
+        Ok(copy)
+    }
+
+    fn try_sorted_copy_by<F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        compare: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut copy = self.try_to_vec()?;
+        copy.try_sort_by(compare)?;
+        Ok(copy)
+    }
+
+    fn try_sorted_copy_by_key<K: Ord, F: FnMut(&T) -> K>(
+        &self,
+        f: F,
+    ) -> Result<Vec<T>, TryReserveError>
+    where
+        T: Clone,
+    {
+        let mut copy = self.try_to_vec()?;
+        copy.try_sort_by_key(f)?;
+        Ok(copy)
+    }
+}
+
+/// Sorts `v` using a stable merge sort, fallibly allocating the scratch buffer
+/// that the sort requires.
+///
+/// If allocating the scratch buffer fails, then `v` is left unchanged.
+///
+/// # Panic safety
+///
+/// If `is_less` panics, then `v` will contain all of its original items, but
+/// in an unspecified order.
+pub(crate) fn try_merge_sort<T, F: FnMut(&T, &T) -> bool>(
+    v: &mut [T],
+    mut is_less: F,
+) -> Result<(), TryReserveError> {
+    // Sorting has no meaningful behavior on zero-sized types.
+    if core::mem::size_of::<T>() == 0 || v.len() < 2 {
+        return Ok(());
+    }
+
+    if v.len() <= MAX_INSERTION {
+        insertion_sort(v, &mut is_less);
+        return Ok(());
+    }
+
+    // The scratch buffer is only used to hold bitwise copies of items that are
+    // still owned by `v`, so its `len` is left as 0 to avoid dropping them.
+    let mut scratch: Vec<T> = try_with_capacity(v.len() / 2)?;
+    unsafe {
+        merge_sort(v, scratch.as_mut_ptr(), &mut is_less);
+    }
+    Ok(())
+}
+
+/// Sorts `v` using a stable merge sort.
+///
+/// # Safety
+///
+/// `scratch` must be valid for writes of `v.len() / 2` items and must not
+/// overlap with `v`.
+unsafe fn merge_sort<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], scratch: *mut T, is_less: &mut F) {
+    let len = v.len();
+    if len <= MAX_INSERTION {
+        insertion_sort(v, is_less);
+        return;
+    }
+
+    let mid = len / 2;
+    unsafe {
+        merge_sort(&mut v[..mid], scratch, is_less);
+        merge_sort(&mut v[mid..], scratch, is_less);
+    }
+
+    // Skip the merge if the two runs are already in order.
+    if is_less(&v[mid], &v[mid - 1]) {
+        unsafe {
+            merge(v, mid, scratch, is_less);
+        }
+    }
+}
+
+/// Merges the sorted runs `v[..mid]` and `v[mid..]`.
+///
+/// # Safety
+///
+/// `mid` must be within `1..v.len()` and `scratch` must be valid for writes of
+/// `mid` items and must not overlap with `v`.
+unsafe fn merge<T, F: FnMut(&T, &T) -> bool>(
+    v: &mut [T],
+    mid: usize,
+    scratch: *mut T,
+    is_less: &mut F,
+) {
+    let v = v.as_mut_ptr_range();
+    unsafe {
+        // Move the left run into the scratch buffer, and then merge it with the
+        // right run by writing into the gap that it left behind.
+        ptr::copy_nonoverlapping(v.start, scratch, mid);
+        let mut hole = MergeHole {
+            start: scratch,
+            end: scratch.add(mid),
+            dest: v.start,
+        };
+        let mut right = v.start.add(mid);
+        while hole.start < hole.end && right < v.end {
+            let to_copy = if is_less(&*right, &*hole.start) {
+                let to_copy = right;
+                right = right.add(1);
+                to_copy
+            } else {
+                let to_copy = hole.start;
+                hole.start = hole.start.add(1);
+                to_copy
+            };
+            ptr::copy_nonoverlapping(to_copy, hole.dest, 1);
+            hole.dest = hole.dest.add(1);
+        }
+    }
+    // `hole` is dropped here, which moves any remaining items from the left run
+    // into place. If `is_less` panics, then this also ensures that `v` contains
+    // every item exactly once.
+}
+
+/// Sorts `v` using insertion sort.
+fn insertion_sort<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    for i in 1..v.len() {
+        insert_tail(&mut v[..=i], is_less);
+    }
+}
+
+/// Inserts the last item of `v` into the already sorted `v[..v.len() - 1]`.
+fn insert_tail<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+    unsafe {
+        let last = v.add(len - 1);
+        if !is_less(&*last, &*last.sub(1)) {
+            return;
+        }
+
+        // Take the last item out, and then shift items to the right until its
+        // position is found.
+        let tmp = ManuallyDrop::new(ptr::read(last));
+        let mut hole = InsertionHole {
+            src: &*tmp,
+            dest: last.sub(1),
+        };
+        ptr::copy_nonoverlapping(hole.dest, last, 1);
+        for i in (0..len - 2).rev() {
+            let next = v.add(i);
+            if !is_less(&*tmp, &*next) {
+                break;
+            }
+            ptr::copy_nonoverlapping(next, hole.dest, 1);
+            hole.dest = next;
+        }
+    }
+    // `hole` is dropped here, which moves `tmp` into the remaining hole. If
+    // `is_less` panics, then this also ensures that `v` contains every item
+    // exactly once.
+}
+
+/// When dropped, moves the item at `src` into `dest`.
+struct InsertionHole<T> {
+    src: *const T,
+    dest: *mut T,
+}
+
+impl<T> Drop for InsertionHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.src, self.dest, 1);
+        }
+    }
+}
+
+/// When dropped, moves the items in `start..end` into `dest`.
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
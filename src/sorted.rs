@@ -0,0 +1,540 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, try_with_capacity, FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+#[cfg(feature = "allocator_api")]
+use crate::try_with_capacity_in;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Merges two sorted slices into a new, sorted `Vec`.
+///
+/// The merge is stable: if an item in `a` and an item in `b` are equal, then
+/// the item from `a` will be placed first.
+///
+/// Space for the merged `Vec` is reserved exactly once, before any items are
+/// cloned. If `a` or `b` is not sorted, then the order of the items in the
+/// returned `Vec` is unspecified (but will still contain every item).
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the merged `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let merged = try_merge_sorted(&[1, 3, 5], &[2, 3, 4, 6])?;
+/// assert_eq!(merged, [1, 2, 3, 3, 4, 5, 6]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_merge_sorted<T: Ord + Clone>(a: &[T], b: &[T]) -> Result<Vec<T>, TryReserveError> {
+    let mut merged = try_with_capacity(merged_len(a, b)?)?;
+    merge_into(&mut merged, a, b, T::lt)?;
+    Ok(merged)
+}
+
+/// Merges two sorted slices into a new, sorted `Vec` with the provided
+/// allocator.
+///
+/// The merge is stable: if an item in `a` and an item in `b` are equal, then
+/// the item from `a` will be placed first.
+///
+/// Space for the merged `Vec` is reserved exactly once, before any items are
+/// cloned. If `a` or `b` is not sorted, then the order of the items in the
+/// returned `Vec` is unspecified (but will still contain every item).
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the merged `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let merged = try_merge_sorted_in(&[1, 3, 5], &[2, 3, 4, 6], System)?;
+/// assert_eq!(merged, [1, 2, 3, 3, 4, 5, 6]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_merge_sorted_in<T: Ord + Clone, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut merged = try_with_capacity_in(merged_len(a, b)?, alloc)?;
+    merge_into(&mut merged, a, b, T::lt)?;
+    Ok(merged)
+}
+
+/// Merges two slices that are sorted by the key extraction function `f` into a
+/// new `Vec`, which will also be sorted by `f`.
+///
+/// The merge is stable: if an item in `a` and an item in `b` have equal keys,
+/// then the item from `a` will be placed first.
+///
+/// Space for the merged `Vec` is reserved exactly once, before any items are
+/// cloned. If `a` or `b` is not sorted, then the order of the items in the
+/// returned `Vec` is unspecified (but will still contain every item).
+///
+/// # Panic safety
+///
+/// If a call to `clone`, `f` or `cmp` panics, then the merged `Vec` and any
+/// items already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let merged = try_merge_sorted_by_key(&[-1i32, 4], &[2, -3], |i| i.abs())?;
+/// assert_eq!(merged, [-1, 2, -3, 4]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_merge_sorted_by_key<T: Clone, K: Ord, F: FnMut(&T) -> K>(
+    a: &[T],
+    b: &[T],
+    mut f: F,
+) -> Result<Vec<T>, TryReserveError> {
+    let mut merged = try_with_capacity(merged_len(a, b)?)?;
+    merge_into(&mut merged, a, b, |x, y| f(x).lt(&f(y)))?;
+    Ok(merged)
+}
+
+/// Merges two slices that are sorted by the key extraction function `f` into a
+/// new `Vec` with the provided allocator, which will also be sorted by `f`.
+///
+/// The merge is stable: if an item in `a` and an item in `b` have equal keys,
+/// then the item from `a` will be placed first.
+///
+/// Space for the merged `Vec` is reserved exactly once, before any items are
+/// cloned. If `a` or `b` is not sorted, then the order of the items in the
+/// returned `Vec` is unspecified (but will still contain every item).
+///
+/// # Panic safety
+///
+/// If a call to `clone`, `f` or `cmp` panics, then the merged `Vec` and any
+/// items already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let merged = try_merge_sorted_by_key_in(&[-1i32, 4], &[2, -3], |i| i.abs(), System)?;
+/// assert_eq!(merged, [-1, 2, -3, 4]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_merge_sorted_by_key_in<T: Clone, K: Ord, F: FnMut(&T) -> K, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    mut f: F,
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let mut merged = try_with_capacity_in(merged_len(a, b)?, alloc)?;
+    merge_into(&mut merged, a, b, |x, y| f(x).lt(&f(y)))?;
+    Ok(merged)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` or `b` (or
+/// both).
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// If an item is in both `a` and `b`, then the item from `a` is cloned.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let result = try_sorted_union(&[1, 2, 3], &[3, 4, 5])?;
+/// assert_eq!(result, [1, 2, 3, 4, 5]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_sorted_union<T: Ord + Clone>(a: &[T], b: &[T]) -> Result<Vec<T>, TryReserveError> {
+    let operation = SetOperation::Union;
+    let mut result = try_with_capacity(operation.max_len(a, b)?)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` or `b` (or
+/// both) with the provided allocator.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// If an item is in both `a` and `b`, then the item from `a` is cloned.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let result = try_sorted_union_in(&[1, 2, 3], &[3, 4, 5], System)?;
+/// assert_eq!(result, [1, 2, 3, 4, 5]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_sorted_union_in<T: Ord + Clone, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let operation = SetOperation::Union;
+    let mut result = try_with_capacity_in(operation.max_len(a, b)?, alloc)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in both `a` and
+/// `b`.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Items are cloned from `a`.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let result = try_sorted_intersection(&[1, 2, 3], &[3, 4, 5])?;
+/// assert_eq!(result, [3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_sorted_intersection<T: Ord + Clone>(
+    a: &[T],
+    b: &[T],
+) -> Result<Vec<T>, TryReserveError> {
+    let operation = SetOperation::Intersection;
+    let mut result = try_with_capacity(operation.max_len(a, b)?)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in both `a` and
+/// `b` with the provided allocator.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Items are cloned from `a`.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let result = try_sorted_intersection_in(&[1, 2, 3], &[3, 4, 5], System)?;
+/// assert_eq!(result, [3]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_sorted_intersection_in<T: Ord + Clone, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let operation = SetOperation::Intersection;
+    let mut result = try_with_capacity_in(operation.max_len(a, b)?, alloc)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` but not in
+/// `b`.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let result = try_sorted_difference(&[1, 2, 3], &[3, 4, 5])?;
+/// assert_eq!(result, [1, 2]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_sorted_difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Result<Vec<T>, TryReserveError> {
+    let operation = SetOperation::Difference;
+    let mut result = try_with_capacity(operation.max_len(a, b)?)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` but not in
+/// `b` with the provided allocator.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let result = try_sorted_difference_in(&[1, 2, 3], &[3, 4, 5], System)?;
+/// assert_eq!(result, [1, 2]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_sorted_difference_in<T: Ord + Clone, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let operation = SetOperation::Difference;
+    let mut result = try_with_capacity_in(operation.max_len(a, b)?, alloc)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` or `b`, but
+/// not in both.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let result = try_sorted_symmetric_difference(&[1, 2, 3], &[3, 4, 5])?;
+/// assert_eq!(result, [1, 2, 4, 5]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_sorted_symmetric_difference<T: Ord + Clone>(
+    a: &[T],
+    b: &[T],
+) -> Result<Vec<T>, TryReserveError> {
+    let operation = SetOperation::SymmetricDifference;
+    let mut result = try_with_capacity(operation.max_len(a, b)?)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Creates a new, sorted `Vec` containing the items that are in `a` or `b`, but
+/// not in both with the provided allocator.
+///
+/// `a` and `b` must be sorted and must not contain duplicates (i.e., like the
+/// items in a `BTreeSet`), otherwise the contents of the returned `Vec` are
+/// unspecified.
+///
+/// Space for the returned `Vec` is reserved once, before any items are
+/// cloned, using the maximum possible length of the result.
+///
+/// # Panic safety
+///
+/// If a call to `clone` or `cmp` panics, then the returned `Vec` and any items
+/// already cloned into it are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let result = try_sorted_symmetric_difference_in(&[1, 2, 3], &[3, 4, 5], System)?;
+/// assert_eq!(result, [1, 2, 4, 5]);
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_sorted_symmetric_difference_in<T: Ord + Clone, A: Allocator>(
+    a: &[T],
+    b: &[T],
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let operation = SetOperation::SymmetricDifference;
+    let mut result = try_with_capacity_in(operation.max_len(a, b)?, alloc)?;
+    operation.apply_into(&mut result, a, b)?;
+    Ok(result)
+}
+
+/// Calculates the length of the result of merging `a` and `b`.
+fn merged_len<T>(a: &[T], b: &[T]) -> Result<usize, TryReserveError> {
+    a.len().checked_add(b.len()).ok_or_else(capacity_overflow)
+}
+
+/// Clones the items from `a` and `b` into `merged`, taking the next item from
+/// `b` only if it is less than the next item from `a`.
+fn merge_into<T: Clone, F: FnMut(&T, &T) -> bool>(
+    merged: &mut impl FallibleVec<T>,
+    mut a: &[T],
+    mut b: &[T],
+    mut is_less: F,
+) -> Result<(), TryReserveError> {
+    while let (Some(next_a), Some(next_b)) = (a.first(), b.first()) {
+        if is_less(next_b, next_a) {
+            merged.try_push(next_b.clone())?;
+            b = &b[1..];
+        } else {
+            merged.try_push(next_a.clone())?;
+            a = &a[1..];
+        }
+    }
+    merged.try_extend_from_slice(a)?;
+    merged.try_extend_from_slice(b)
+}
+
+/// Set operations over sorted slices.
+#[derive(Clone, Copy)]
+enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl SetOperation {
+    /// The maximum number of items that applying this operation to `a` and `b`
+    /// can produce.
+    fn max_len<T>(self, a: &[T], b: &[T]) -> Result<usize, TryReserveError> {
+        match self {
+            SetOperation::Union | SetOperation::SymmetricDifference => merged_len(a, b),
+            SetOperation::Intersection => Ok(a.len().min(b.len())),
+            SetOperation::Difference => Ok(a.len()),
+        }
+    }
+
+    /// Clones the items produced by applying this operation to `a` and `b` into
+    /// `result`.
+    fn apply_into<T: Ord + Clone>(
+        self,
+        result: &mut impl FallibleVec<T>,
+        mut a: &[T],
+        mut b: &[T],
+    ) -> Result<(), TryReserveError> {
+        let keep_only_in_a = !matches!(self, SetOperation::Intersection);
+        let keep_only_in_b = matches!(
+            self,
+            SetOperation::Union | SetOperation::SymmetricDifference
+        );
+        let keep_in_both = matches!(self, SetOperation::Union | SetOperation::Intersection);
+
+        while let (Some(next_a), Some(next_b)) = (a.first(), b.first()) {
+            match next_a.cmp(next_b) {
+                Ordering::Less => {
+                    if keep_only_in_a {
+                        result.try_push(next_a.clone())?;
+                    }
+                    a = &a[1..];
+                }
+                Ordering::Greater => {
+                    if keep_only_in_b {
+                        result.try_push(next_b.clone())?;
+                    }
+                    b = &b[1..];
+                }
+                Ordering::Equal => {
+                    if keep_in_both {
+                        result.try_push(next_a.clone())?;
+                    }
+                    a = &a[1..];
+                    b = &b[1..];
+                }
+            }
+        }
+
+        if keep_only_in_a {
+            result.try_extend_from_slice(a)?;
+        }
+        if keep_only_in_b {
+            result.try_extend_from_slice(b)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::vec::Vec;
+use core::{
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+macro_rules! struct_spare_capacity_writer {
+    { $(#[doc = $doc:expr])+ pub struct SpareCapacityWriter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct SpareCapacityWriter<'a, T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct SpareCapacityWriter<'a, T, A: Allocator> $impl
+    }
+}
+
+struct_spare_capacity_writer! {
+    /// Reserved, uninitialized space at the end of a [`Vec`] that can be
+    /// written to directly (e.g., by reading from a socket or a DMA buffer)
+    /// before being committed to the `Vec`.
+    ///
+    /// The writer dereferences to a `[MaybeUninit<T>]` covering exactly the
+    /// reserved space. The length of the `Vec` is only changed by
+    /// [`commit`](SpareCapacityWriter::commit): if the writer is dropped
+    /// without being committed, then the `Vec` is unchanged (and any items
+    /// written are leaked).
+    ///
+    /// Created by [`try_spare_capacity`](crate::ReserveGuard::try_spare_capacity).
+    pub struct SpareCapacityWriter {
+        #[cfg(not(feature = "allocator_api"))]
+        vec: &'a mut Vec<T>,
+        #[cfg(feature = "allocator_api")]
+        vec: &'a mut Vec<T, A>,
+        len: usize,
+    }
+}
+
+macro_rules! impl_spare_capacity_writer {
+    { impl SpareCapacityWriter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<'a, T> SpareCapacityWriter<'a, T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<'a, T, A: Allocator> SpareCapacityWriter<'a, T, A> $impl
+    };
+    { impl $trait:ident for SpareCapacityWriter $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> $trait for SpareCapacityWriter<'_, T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> $trait for SpareCapacityWriter<'_, T, A> $impl
+    };
+}
+
+impl_spare_capacity_writer! {
+    impl SpareCapacityWriter {
+        /// Creates a writer for the first `len` items of the spare capacity
+        /// of `vec`, which must already be reserved.
+        #[cfg(not(feature = "allocator_api"))]
+        pub(crate) fn new(vec: &'a mut Vec<T>, len: usize) -> Self {
+            debug_assert!(vec.capacity() - vec.len() >= len);
+            SpareCapacityWriter { vec, len }
+        }
+
+        /// Creates a writer for the first `len` items of the spare capacity
+        /// of `vec`, which must already be reserved.
+        #[cfg(feature = "allocator_api")]
+        pub(crate) fn new(vec: &'a mut Vec<T, A>, len: usize) -> Self {
+            debug_assert!(vec.capacity() - vec.len() >= len);
+            SpareCapacityWriter { vec, len }
+        }
+
+        /// Adds the first `written` items of the reserved space to the `Vec`.
+        ///
+        /// # Safety
+        ///
+        /// The first `written` items of the reserved space must have been
+        /// initialized.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `written` is greater than the length of the reserved
+        /// space.
+        pub unsafe fn commit(self, written: usize) {
+            assert!(
+                written <= self.len,
+                "committed more items ({written}) than were reserved ({})",
+                self.len
+            );
+            let len = self.vec.len();
+            unsafe { self.vec.set_len(len + written) };
+        }
+    }
+}
+
+impl_spare_capacity_writer! {
+    impl Deref for SpareCapacityWriter {
+        type Target = [MaybeUninit<T>];
+
+        fn deref(&self) -> &Self::Target {
+            unsafe {
+                core::slice::from_raw_parts(
+                    self.vec.as_ptr().add(self.vec.len()).cast(),
+                    self.len,
+                )
+            }
+        }
+    }
+}
+
+impl_spare_capacity_writer! {
+    impl DerefMut for SpareCapacityWriter {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.vec.spare_capacity_mut()[..self.len]
+        }
+    }
+}
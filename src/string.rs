@@ -0,0 +1,137 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{bytes::write_display, error::capacity_overflow, TryReserveError};
+use alloc::string::String;
+use core::fmt::Display;
+
+/// Fallible allocation methods for [`String`].
+pub trait FallibleString {
+    /// Appends the [`Display`] representation of `value` to the `String`.
+    ///
+    /// The value is formatted directly into the `String`'s buffer, growing it
+    /// fallibly as needed, so no intermediate `String` is allocated. If an
+    /// allocation fails, the `String` is restored to its original length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Display` implementation of `value` returns an error
+    /// that was not caused by a failed allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fallible_vec::*;
+    ///
+    /// let mut line = String::new();
+    /// line.try_extend_from_display("pi=")?;
+    /// line.try_extend_from_display(3.5)?;
+    /// assert_eq!(line, "pi=3.5");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_extend_from_display(&mut self, value: impl Display) -> Result<(), TryReserveError>;
+
+    /// Appends `s` to the `String`, escaped as by [`str::escape_default`].
+    ///
+    /// The exact length of the escaped output is computed up front so that at
+    /// most one allocation is made. If it fails, the `String` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fallible_vec::*;
+    ///
+    /// let mut line = String::from("msg=");
+    /// line.try_push_escape_default("a\tb")?;
+    /// assert_eq!(line, "msg=a\\tb");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_push_escape_default(&mut self, s: &str) -> Result<(), TryReserveError>;
+
+    /// Appends `s` to the `String`, escaped as by [`str::escape_debug`].
+    ///
+    /// The exact length of the escaped output is computed up front so that at
+    /// most one allocation is made. If it fails, the `String` is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fallible_vec::*;
+    ///
+    /// let mut line = String::from("msg=");
+    /// line.try_push_escape_debug("caf\u{e9}\n")?;
+    /// assert_eq!(line, "msg=caf\u{e9}\\n");
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    fn try_push_escape_debug(&mut self, s: &str) -> Result<(), TryReserveError>;
+}
+
+impl FallibleString for String {
+    fn try_extend_from_display(&mut self, value: impl Display) -> Result<(), TryReserveError> {
+        // SAFETY: only complete `str`s are written to the buffer, and on
+        // failure it is truncated back to its original (valid) length.
+        let buf = unsafe { self.as_mut_vec() };
+        let original_len = buf.len();
+        let result = write_display(buf, value);
+        if result.is_err() {
+            buf.truncate(original_len);
+        }
+        result
+    }
+
+    fn try_push_escape_default(&mut self, s: &str) -> Result<(), TryReserveError> {
+        push_escaped(self, s.escape_default())
+    }
+
+    fn try_push_escape_debug(&mut self, s: &str) -> Result<(), TryReserveError> {
+        push_escaped(self, s.escape_debug())
+    }
+}
+
+/// Creates a new `String` containing `s` escaped as by
+/// [`str::escape_default`].
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_escape_default("\"hi\"\n")?, "\\\"hi\\\"\\n");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_escape_default(s: &str) -> Result<String, TryReserveError> {
+    let mut escaped = String::new();
+    escaped.try_push_escape_default(s)?;
+    Ok(escaped)
+}
+
+/// Creates a new `String` containing `s` escaped as by [`str::escape_debug`].
+///
+/// # Examples
+///
+/// ```
+/// use fallible_vec::*;
+///
+/// assert_eq!(try_escape_debug("\u{e9}\0")?, "\u{e9}\\0");
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+pub fn try_escape_debug(s: &str) -> Result<String, TryReserveError> {
+    let mut escaped = String::new();
+    escaped.try_push_escape_debug(s)?;
+    Ok(escaped)
+}
+
+/// Appends the characters produced by `escaped` to `string`, reserving the
+/// exact number of bytes required first.
+fn push_escaped<E>(string: &mut String, escaped: E) -> Result<(), TryReserveError>
+where
+    E: Iterator<Item = char> + Display + Clone,
+{
+    let len = escaped
+        .clone()
+        .try_fold(0usize, |len, c| len.checked_add(c.len_utf8()))
+        .ok_or_else(capacity_overflow)?;
+    string.try_reserve_exact(len)?;
+    // Cannot fail to allocate as the space has already been reserved.
+    string.try_extend_from_display(escaped)
+}
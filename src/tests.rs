@@ -2,9 +2,13 @@
 // Licensed under the MIT license.
 
 use crate::*;
-use alloc::{alloc::Global, vec::Vec};
+use alloc::{
+    alloc::{AllocError, Global, Layout},
+    vec::Vec,
+};
+use core::ptr::NonNull;
 use core::sync::atomic::{AtomicI32, Ordering};
-use std::{alloc::System, cell::Cell};
+use std::{alloc::System, cell::Cell, rc::Rc};
 
 #[derive(Default)]
 struct ExplodingCloner<'a> {
@@ -19,7 +23,7 @@ impl Clone for ExplodingCloner<'_> {
         }
         Self {
             clone_panics: Default::default(),
-            drop_counter: self.drop_counter.clone(),
+            drop_counter: self.drop_counter,
         }
     }
 }
@@ -55,6 +59,47 @@ impl Iterator for ExplodingIterator {
     }
 }
 
+/// An allocator that fails any allocation larger than `limit` bytes.
+struct LimitedAllocator {
+    limit: usize,
+}
+
+unsafe impl Allocator for LimitedAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() > self.limit {
+            Err(AllocError)
+        } else {
+            System.allocate(layout)
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { System.deallocate(ptr, layout) }
+    }
+}
+
+/// An allocator that cannot shrink allocations.
+struct NoShrinkAllocator;
+
+unsafe impl Allocator for NoShrinkAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        System.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { System.deallocate(ptr, layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+}
+
 #[test]
 fn test_push() {
     let mut v = Vec::new();
@@ -114,6 +159,139 @@ fn test_splice_inclusive_out_of_bounds() {
     v.try_splice_in(5..=5, a, Global).unwrap();
 }
 
+#[test]
+fn test_checked_splice_in() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    v.checked_splice_in(2..=3, [10, 11, 12], Global).unwrap();
+    assert_eq!(v, &[1, 2, 10, 11, 12, 5]);
+
+    for range in [
+        (Bound::Included(5), Bound::Included(6)),
+        (Bound::Included(7), Bound::Unbounded),
+    ] {
+        assert_eq!(
+            v.checked_splice_in(range, [13], Global),
+            Err(FallibleVecError::RangeOutOfBounds)
+        );
+    }
+    assert_eq!(
+        v.checked_splice_in(
+            (Bound::Excluded(usize::MAX), Bound::Unbounded),
+            [13],
+            Global
+        ),
+        Err(FallibleVecError::RangeOutOfBounds)
+    );
+    assert_eq!(
+        v.checked_splice_in(..=usize::MAX, [13], Global),
+        Err(FallibleVecError::RangeOutOfBounds)
+    );
+    assert_eq!(v, &[1, 2, 10, 11, 12, 5]);
+}
+
+#[test]
+fn test_try_splice_out_in() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let removed = v.try_splice_out_in(1..4, [10, 11], Global).unwrap();
+    assert_eq!(v, [1, 10, 11, 5]);
+    assert_eq!(removed, [2, 3, 4]);
+    assert_eq!(removed.capacity(), 3);
+
+    let removed = v.try_splice_out_in(2..2, [12], Global).unwrap();
+    assert_eq!(v, [1, 10, 12, 11, 5]);
+    assert!(removed.is_empty());
+
+    let removed = v.try_splice_out_in(.., None, Global).unwrap();
+    assert!(v.is_empty());
+    assert_eq!(removed, [1, 10, 12, 11, 5]);
+
+    // If allocating the removed items fails, then the `Vec` is unchanged.
+    let mut v = try_vec![1u32, 2, 3, 4, 5].unwrap();
+    assert!(v
+        .try_splice_out_in(.., None, LimitedAllocator { limit: 16 })
+        .is_err());
+    assert_eq!(v, [1, 2, 3, 4, 5]);
+
+    // If inserting the replacement fails part way, then the removed items are
+    // moved back.
+    let mut v = try_vec![1u32, 2, 3, 4, 5].unwrap();
+    assert!(v
+        .try_splice_out_in(
+            1..3,
+            (10..20).filter(|_| true),
+            LimitedAllocator { limit: 16 }
+        )
+        .is_err());
+    assert_eq!(v, [1, 2, 3, 4, 5]);
+}
+
+/// An iterator that claims to have more items than it produces.
+struct ShortIterator<I>(I, usize);
+
+impl<I: Iterator> Iterator for ShortIterator<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.1, Some(self.1))
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for ShortIterator<I> {}
+
+#[test]
+fn test_try_splice_exact() {
+    let mut v = try_vec![Rc::new(1), Rc::new(2), Rc::new(3), Rc::new(4)].unwrap();
+    let removed = Rc::clone(&v[1]);
+    v.try_splice_exact(1..3, [Rc::new(7), Rc::new(8), Rc::new(9)])
+        .unwrap();
+    assert_eq!(v, [1, 7, 8, 9, 4].map(Rc::new));
+    assert_eq!(Rc::strong_count(&removed), 1);
+
+    v.try_splice_exact(1..4, None).unwrap();
+    assert_eq!(v, [1, 4].map(Rc::new));
+    v.try_splice_exact(2.., [Rc::new(5)]).unwrap();
+    assert_eq!(v, [1, 4, 5].map(Rc::new));
+
+    // A short iterator leaves no gap.
+    let mut v = try_vec![1, 2, 3].unwrap();
+    v.try_splice_exact(1..2, ShortIterator([10, 11].into_iter(), 4))
+        .unwrap();
+    assert_eq!(v, [1, 10, 11, 3]);
+
+    // If reserving fails, then the `Vec` is unchanged.
+    let mut v = try_vec_in![1u32, 2, 3, 4 => LimitedAllocator { limit: 16 }].unwrap();
+    assert!(v.try_splice_exact(1..2, [5, 6]).is_err());
+    assert_eq!(v, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_try_splice() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    v.try_splice(2..4, [10, 11, 12]).unwrap();
+    assert_eq!(v, [1, 2, 10, 11, 12, 5]);
+    v.try_splice(1..3, Some(20)).unwrap();
+    assert_eq!(v, [1, 20, 11, 12, 5]);
+    v.try_splice(1..=1, (30..).take_while(|i| *i < 35)).unwrap();
+    assert_eq!(v, [1, 30, 31, 32, 33, 34, 11, 12, 5]);
+    v.try_splice(.., None).unwrap();
+    assert!(v.is_empty());
+
+    // If reserving fails, then the `Vec` is unchanged.
+    let alloc = LimitedAllocator { limit: 16 };
+    let mut v = try_vec_in![1u32, 2, 3, 4 => &alloc].unwrap();
+    assert!(v.try_splice(1..2, [5, 6]).is_err());
+    assert_eq!(v, [1, 2, 3, 4]);
+
+    // If collecting the replacement fails, then the `Vec` is unchanged.
+    let mut v = try_vec_in![1u32, 2 => &alloc].unwrap();
+    assert!(v.try_splice(1..2, (5..10).filter(|_| true)).is_err());
+    assert_eq!(v, [1, 2]);
+}
+
 #[test]
 fn test_splice_items_zero_sized() {
     let mut vec = try_vec![(); 3].unwrap();
@@ -313,3 +491,2370 @@ fn test_panic_during_try_vec_runs_drop() {
     // Should have dropped the original ExplodingCloner AND the one that was inserted.
     assert_eq!(drop_counter.load(Ordering::Relaxed), 2);
 }
+
+#[test]
+fn test_remove_indices() {
+    let mut v = try_vec![1, 2, 3, 4, 5, 6, 7].unwrap();
+    let removed = v.try_remove_indices(&[1, 2, 6]).unwrap();
+    assert_eq!(v, [1, 4, 5, 6]);
+    assert_eq!(removed, [2, 3, 7]);
+
+    let removed = v.try_remove_indices(&[]).unwrap();
+    assert_eq!(v, [1, 4, 5, 6]);
+    assert_eq!(removed, []);
+
+    let removed = v.try_remove_indices(&[0, 1, 2, 3]).unwrap();
+    assert_eq!(v, []);
+    assert_eq!(removed, [1, 4, 5, 6]);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let mut v = try_vec_in![1, 2, 3 => System].unwrap();
+    let _removed: Vec<i32, System> = v.try_remove_indices(&[1]).unwrap();
+}
+
+#[test]
+#[should_panic]
+fn test_remove_indices_unsorted() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let _ = v.try_remove_indices(&[3, 1]);
+}
+
+#[test]
+#[should_panic]
+fn test_remove_indices_out_of_bounds() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let _ = v.try_remove_indices(&[1, 5]);
+}
+
+#[test]
+fn test_try_clone() {
+    let vec = try_vec_in![Rc::new(1), Rc::new(2) => System].unwrap();
+    let clone = vec.try_clone().unwrap();
+    assert_eq!(clone, vec);
+    assert_eq!(clone.capacity(), 2);
+    assert!(Rc::ptr_eq(&clone[0], &vec[0]));
+
+    let empty: Vec<u32> = Vec::new();
+    assert_eq!(empty.try_clone().unwrap().capacity(), 0);
+}
+
+#[test]
+fn test_try_clone_in() {
+    let vec = try_vec![1u32, 2, 3, 4, 5].unwrap();
+    let clone = vec.try_clone_in(LimitedAllocator { limit: 20 }).unwrap();
+    assert_eq!(clone, vec);
+    assert_eq!(clone.capacity(), 5);
+
+    assert!(vec.try_clone_in(LimitedAllocator { limit: 16 }).is_err());
+}
+
+#[test]
+fn test_try_clone_into() {
+    let source = try_vec![1u32, 2, 3].unwrap();
+    let mut dest: Vec<u32> = try_with_exact_capacity(8).unwrap();
+    dest.try_extend_from_slice(&[9; 5]).unwrap();
+    let ptr = dest.as_ptr();
+    source.try_clone_into(&mut dest).unwrap();
+    assert_eq!(dest, [1, 2, 3]);
+    assert_eq!(dest.as_ptr(), ptr);
+    assert_eq!(dest.capacity(), 8);
+
+    // Only grows if needed, and is unchanged if growing fails.
+    let source = try_vec_in![1u32, 2, 3, 4, 5 => LimitedAllocator { limit: 20 }].unwrap();
+    let mut dest = try_vec_in![9u32; 4 => LimitedAllocator { limit: 16 }].unwrap();
+    assert!(source.try_clone_into(&mut dest).is_err());
+    assert_eq!(dest, [9; 4]);
+}
+
+#[test]
+fn test_try_migrate_allocator() {
+    let vec = try_vec![Rc::new(1), Rc::new(2)].unwrap();
+    let first = Rc::clone(&vec[0]);
+    let migrated = vec.try_migrate_allocator(System).unwrap();
+    assert_eq!(migrated, [Rc::new(1), Rc::new(2)]);
+    assert_eq!(migrated.capacity(), 2);
+    assert_eq!(Rc::strong_count(&first), 2);
+
+    // If allocating fails, then the original `Vec` is returned.
+    let vec = try_vec![1u32, 2, 3, 4, 5].unwrap();
+    let (vec, _) = vec
+        .try_migrate_allocator(LimitedAllocator { limit: 16 })
+        .unwrap_err();
+    assert_eq!(vec, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_split_at_owned() {
+    let (left, right) = [1, 2, 3].try_split_at_owned(0).unwrap();
+    assert_eq!(left, []);
+    assert_eq!(right, [1, 2, 3]);
+
+    let (left, right) = [1, 2, 3].try_split_at_owned(3).unwrap();
+    assert_eq!(left, [1, 2, 3]);
+    assert_eq!(right, []);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let (left, right): (Vec<i32, System>, Vec<i32, System>) =
+        [1, 2, 3].try_split_at_owned_in(1, System).unwrap();
+    assert_eq!(left, [1]);
+    assert_eq!(right, [2, 3]);
+}
+
+#[test]
+fn test_try_dedup_to_vec() {
+    let empty: [i32; 0] = [];
+    assert_eq!(empty.try_dedup_to_vec().unwrap(), []);
+    let values = [1, 1, 2, 3, 3, 3, 1, 4, 4];
+    assert_eq!(values.try_dedup_to_vec().unwrap(), [1, 2, 3, 1, 4]);
+    assert_eq!(values, [1, 1, 2, 3, 3, 3, 1, 4, 4]);
+
+    // Each item is compared with the last item that was kept.
+    let values = [1, 2, 3, 4, 10, 11, 12];
+    assert_eq!(
+        values.try_dedup_by_to_vec(|a, b| a - b < 3).unwrap(),
+        [1, 4, 10]
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_split_at_owned_out_of_bounds() {
+    let _ = [1, 2, 3].try_split_at_owned(4);
+}
+
+#[test]
+fn test_drain_collect_in() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let drained: Vec<i32, System> = v.try_drain_collect_in(1..=2, System).unwrap();
+    assert_eq!(v, [1, 4, 5]);
+    assert_eq!(drained, [2, 3]);
+
+    let drained = v.try_drain_collect_in(3.., Global).unwrap();
+    assert_eq!(v, [1, 4, 5]);
+    assert_eq!(drained, []);
+
+    let drained = v.try_drain_collect_in(.., Global).unwrap();
+    assert_eq!(v, []);
+    assert_eq!(drained, [1, 4, 5]);
+}
+
+#[test]
+#[should_panic]
+fn test_drain_collect_in_out_of_bounds() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let _ = v.try_drain_collect_in(4..6, Global);
+}
+
+#[test]
+fn test_split_off_in() {
+    let mut v = try_vec![1, 2, 3, 4, 5, 6].unwrap();
+    let orig_capacity = v.capacity();
+    let split_off: Vec<i32, System> = v.try_split_off_in(4, System).unwrap();
+    assert_eq!(v, [1, 2, 3, 4]);
+    assert_eq!(split_off, [5, 6]);
+    assert_eq!(v.capacity(), orig_capacity);
+
+    let split_off = v.try_split_off_in(4, Global).unwrap();
+    assert_eq!(v, [1, 2, 3, 4]);
+    assert_eq!(split_off, []);
+
+    let split_off = v.try_split_off_in(0, Global).unwrap();
+    assert_eq!(v, []);
+    assert_eq!(split_off, [1, 2, 3, 4]);
+}
+
+#[test]
+#[should_panic]
+fn test_split_off_in_out_of_bounds() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let _ = v.try_split_off_in(6, Global);
+}
+
+#[test]
+fn test_merge_sorted() {
+    assert_eq!(try_merge_sorted::<i32>(&[], &[]).unwrap(), []);
+    assert_eq!(try_merge_sorted(&[1, 2], &[]).unwrap(), [1, 2]);
+    assert_eq!(try_merge_sorted(&[], &[1, 2]).unwrap(), [1, 2]);
+
+    let merged = try_merge_sorted(&[1, 4, 4, 9], &[0, 4, 10, 11]).unwrap();
+    assert_eq!(merged, [0, 1, 4, 4, 4, 9, 10, 11]);
+    assert_eq!(merged.capacity(), 8);
+
+    // Merging is stable: ties are taken from `a` first.
+    let merged =
+        try_merge_sorted_by_key(&[(1, 'a'), (2, 'a')], &[(1, 'b'), (2, 'b')], |i| i.0).unwrap();
+    assert_eq!(merged, [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let merged: Vec<i32, System> = try_merge_sorted_in(&[1, 3], &[2], System).unwrap();
+    assert_eq!(merged, [1, 2, 3]);
+}
+
+#[test]
+fn test_sorted_set_operations() {
+    let a = [1, 3, 5, 7, 9];
+    let b = [2, 3, 4, 5, 10];
+
+    let union = try_sorted_union(&a, &b).unwrap();
+    assert_eq!(union, [1, 2, 3, 4, 5, 7, 9, 10]);
+    let intersection = try_sorted_intersection(&a, &b).unwrap();
+    assert_eq!(intersection, [3, 5]);
+    assert!(intersection.capacity() <= 5);
+    assert_eq!(try_sorted_difference(&a, &b).unwrap(), [1, 7, 9]);
+    assert_eq!(try_sorted_difference(&b, &a).unwrap(), [2, 4, 10]);
+    assert_eq!(
+        try_sorted_symmetric_difference(&a, &b).unwrap(),
+        [1, 2, 4, 7, 9, 10]
+    );
+
+    assert_eq!(try_sorted_union(&a, &[]).unwrap(), a);
+    assert_eq!(try_sorted_intersection(&a, &[]).unwrap(), []);
+    assert_eq!(try_sorted_difference(&[], &b).unwrap(), []);
+    assert_eq!(try_sorted_symmetric_difference(&[], &b).unwrap(), b);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let _v: Vec<i32, System> = try_sorted_union_in(&a, &b, System).unwrap();
+    let _v: Vec<i32, System> = try_sorted_intersection_in(&a, &b, System).unwrap();
+    let _v: Vec<i32, System> = try_sorted_difference_in(&a, &b, System).unwrap();
+    let _v: Vec<i32, System> = try_sorted_symmetric_difference_in(&a, &b, System).unwrap();
+}
+
+/// Generates pseudo-random numbers for sorting tests.
+fn pseudo_random_values(len: usize) -> Vec<u32> {
+    let mut state = 0x2545_f491_u32;
+    let mut values = Vec::new();
+    for _ in 0..len {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        values.push(state % 100);
+    }
+    values
+}
+
+#[test]
+fn test_collect_sorted() {
+    for len in [0, 1, 2, 19, 20, 21, 100, 1000] {
+        let values = pseudo_random_values(len);
+        let mut expected = values.clone();
+        expected.sort();
+        assert_eq!(
+            values.iter().copied().try_collect_sorted().unwrap(),
+            expected
+        );
+
+        expected.reverse();
+        assert_eq!(
+            values
+                .iter()
+                .copied()
+                .try_collect_sorted_by(|a, b| b.cmp(a))
+                .unwrap(),
+            expected
+        );
+    }
+
+    // Sorting is stable.
+    let values = pseudo_random_values(500);
+    let sorted = values
+        .iter()
+        .copied()
+        .enumerate()
+        .try_collect_sorted_by_key(|(_, value)| *value / 10)
+        .unwrap();
+    for pair in sorted.windows(2) {
+        let (index_a, value_a) = pair[0];
+        let (index_b, value_b) = pair[1];
+        assert!(value_a / 10 < value_b / 10 || (value_a / 10 == value_b / 10 && index_a < index_b));
+    }
+}
+
+#[test]
+fn test_panic_during_collect_sorted() {
+    let drop_counter = AtomicI32::new(0);
+    assert!(
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let values = pseudo_random_values(100);
+            let mut compare_count = 0;
+            let _ = values
+                .iter()
+                .map(|value| {
+                    (
+                        *value,
+                        ExplodingCloner {
+                            clone_panics: Default::default(),
+                            drop_counter: Some(&drop_counter),
+                        },
+                    )
+                })
+                .try_collect_sorted_by(|a, b| {
+                    compare_count += 1;
+                    if compare_count == 300 {
+                        panic!("BOOM");
+                    }
+                    a.0.cmp(&b.0)
+                });
+        }))
+        .is_err(),
+        "Panic was not propagated"
+    );
+
+    // Every item should have been dropped exactly once.
+    assert_eq!(drop_counter.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn test_try_sort() {
+    for len in [0, 1, 2, 19, 20, 21, 100, 1000] {
+        let values = pseudo_random_values(len);
+        let mut expected = values.clone();
+        expected.sort();
+        let mut actual = values.clone();
+        actual.try_sort().unwrap();
+        assert_eq!(actual, expected);
+
+        expected.reverse();
+        let mut actual = values.clone();
+        actual.try_sort_by(|a, b| b.cmp(a)).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    // Sorting is stable.
+    let mut values: Vec<_> = pseudo_random_values(500).into_iter().enumerate().collect();
+    values.try_sort_by_key(|(_, value)| *value / 10).unwrap();
+    for pair in values.windows(2) {
+        let (index_a, value_a) = pair[0];
+        let (index_b, value_b) = pair[1];
+        assert!(value_a / 10 < value_b / 10 || (value_a / 10 == value_b / 10 && index_a < index_b));
+    }
+}
+
+#[test]
+fn test_try_sort_by_cached_key() {
+    for len in [0, 1, 2, 21, 100] {
+        let values = pseudo_random_values(len);
+        let mut expected: Vec<_> = values.iter().copied().enumerate().collect();
+        expected.sort_by_key(|(_, value)| *value / 10);
+        let mut actual: Vec<_> = values.iter().copied().enumerate().collect();
+        let mut key_calls = 0;
+        actual
+            .try_sort_by_cached_key(|(_, value)| {
+                key_calls += 1;
+                *value / 10
+            })
+            .unwrap();
+        assert_eq!(actual, expected);
+        if len > 1 {
+            assert_eq!(key_calls, len);
+        }
+    }
+
+    // If the key function panics, then the slice is unchanged.
+    let mut values = pseudo_random_values(50);
+    let original = values.clone();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut key_calls = 0;
+        let _ = values.try_sort_by_cached_key(|value| {
+            key_calls += 1;
+            if key_calls == 25 {
+                panic!("BOOM");
+            }
+            *value
+        });
+    }))
+    .is_err());
+    assert_eq!(values, original);
+}
+
+#[test]
+fn test_topk() {
+    for k in [0, 1, 2, 10, 99, 100, 200] {
+        let values = pseudo_random_values(100);
+        let mut expected = values.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.truncate(k);
+        let top = values.iter().copied().try_topk(k, |a, b| a.cmp(b)).unwrap();
+        assert_eq!(top, expected);
+        assert!(top.capacity() <= k);
+
+        let mut expected = values.clone();
+        expected.sort();
+        expected.truncate(k);
+        let bottom = values.into_iter().try_topk(k, |a, b| b.cmp(a)).unwrap();
+        assert_eq!(bottom, expected);
+    }
+
+    // Iterators without an upper bound only allocate space for `k` items.
+    let top = (0..)
+        .take_while(|i| *i < 1000)
+        .try_topk(5, |a, b| a.cmp(b))
+        .unwrap();
+    assert_eq!(top, [999, 998, 997, 996, 995]);
+    assert_eq!(top.capacity(), 5);
+}
+
+#[cfg(feature = "rand")]
+struct XorShiftRng(u64);
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for XorShiftRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn test_sample() {
+    let mut rng = XorShiftRng(0x1234_5678);
+    assert_eq!((0..10).try_sample(0, &mut rng).unwrap(), []);
+
+    // Fewer items than requested: everything is returned.
+    let mut sample = (0..5).try_sample(10, &mut rng).unwrap();
+    sample.sort();
+    assert_eq!(sample, [0, 1, 2, 3, 4]);
+    assert_eq!(sample.capacity(), 5);
+
+    // Every item should be selected roughly the same number of times.
+    let mut counts = [0; 10];
+    for _ in 0..10_000 {
+        let sample = (0..10).try_sample(3, &mut rng).unwrap();
+        assert_eq!(sample.len(), 3);
+        for i in sample {
+            counts[i] += 1;
+        }
+    }
+    for count in counts {
+        assert!((2_700..3_300).contains(&count), "{counts:?}");
+    }
+}
+
+#[test]
+#[cfg(feature = "hashbrown")]
+fn test_counts() {
+    use std::collections::hash_map::RandomState;
+
+    let counts: hashbrown::HashMap<_, _, RandomState> = [1, 2, 3, 2, 1, 2].try_counts().unwrap();
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts[&1], 2);
+    assert_eq!(counts[&2], 3);
+    assert_eq!(counts[&3], 1);
+
+    let counts: hashbrown::HashMap<i32, _, RandomState> = [].try_counts().unwrap();
+    assert!(counts.is_empty());
+}
+
+#[test]
+#[cfg(feature = "hashbrown")]
+fn test_into_group_map() {
+    use std::collections::hash_map::RandomState;
+
+    let pairs = [(1, 'a'), (2, 'b'), (1, 'c'), (3, 'd'), (1, 'e')];
+    let groups: hashbrown::HashMap<_, _, RandomState> = pairs.try_into_group_map().unwrap();
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[&1], ['a', 'c', 'e']);
+    assert_eq!(groups[&2], ['b']);
+    assert_eq!(groups[&3], ['d']);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let groups: hashbrown::HashMap<_, Vec<char, System>, RandomState> =
+        pairs.try_into_group_map_in(System).unwrap();
+    assert_eq!(groups[&1], ['a', 'c', 'e']);
+}
+
+#[test]
+fn test_transpose() {
+    let matrix = [[1, 2], [3, 4], [5, 6]];
+    assert_eq!(try_transpose(&matrix).unwrap(), [[1, 3, 5], [2, 4, 6]]);
+
+    let empty: [Vec<i32>; 0] = [];
+    assert!(try_transpose(&empty).unwrap().is_empty());
+    assert!(try_transpose(&[[0; 0]; 3]).unwrap().is_empty());
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let matrix = [
+        try_vec_in![1, 2 => System].unwrap(),
+        try_vec_in![3, 4 => System].unwrap(),
+    ];
+    let transposed: Vec<Vec<i32, System>, System> = try_transpose_in(&matrix, System).unwrap();
+    assert_eq!(transposed, [[1, 3], [2, 4]]);
+}
+
+#[test]
+#[should_panic]
+fn test_transpose_ragged() {
+    let matrix = [try_vec![1, 2].unwrap(), try_vec![3].unwrap()];
+    let _ = try_transpose(&matrix);
+}
+
+#[test]
+fn test_rle() {
+    let values = [0, 0, 1, 1, 1, 0, 2, 2, 2, 2];
+    let encoded = try_rle_encode(&values).unwrap();
+    assert_eq!(encoded, [(0, 2), (1, 3), (0, 1), (2, 4)]);
+    assert_eq!(encoded.capacity(), 4);
+    let decoded = try_rle_decode(&encoded).unwrap();
+    assert_eq!(decoded, values);
+    assert_eq!(decoded.capacity(), values.len());
+
+    assert_eq!(try_rle_encode::<i32>(&[]).unwrap(), []);
+    assert_eq!(try_rle_decode::<i32>(&[]).unwrap(), []);
+    assert_eq!(try_rle_decode(&[(1, 0), (2, 1)]).unwrap(), [2]);
+
+    // Overflowing the decoded length is reported as an error.
+    assert!(try_rle_decode(&[((), usize::MAX), ((), 1)]).is_err());
+
+    // Items that aren't equal to themselves are each their own run.
+    let encoded = try_rle_encode(&[f32::NAN, f32::NAN, 1.0]).unwrap();
+    assert_eq!(encoded.len(), 3);
+    assert!(encoded[..2]
+        .iter()
+        .all(|(item, run)| item.is_nan() && *run == 1));
+    assert_eq!(encoded[2], (1.0, 1));
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let encoded: Vec<(i32, usize), System> = try_rle_encode_in(&values, System).unwrap();
+    let _decoded: Vec<i32, System> = try_rle_decode_in(&encoded, System).unwrap();
+}
+
+#[test]
+fn test_interleave() {
+    let channels = [[1, 4, 7], [2, 5, 8], [3, 6, 9]];
+    let interleaved = try_interleave(&channels).unwrap();
+    assert_eq!(interleaved, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(interleaved.capacity(), 9);
+
+    let ragged: [&[i32]; 3] = [&[1], &[], &[2, 3, 4]];
+    assert_eq!(try_interleave(&ragged).unwrap(), [1, 2, 3, 4]);
+
+    let empty: [&[i32]; 0] = [];
+    assert_eq!(try_interleave(&empty).unwrap(), []);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let _v: Vec<i32, System> = try_interleave_in(&channels, System).unwrap();
+}
+
+#[test]
+fn test_put_bytes() {
+    let mut buf = Vec::new();
+    buf.try_put_u8(0xff).unwrap();
+    buf.try_put_i8(-1).unwrap();
+    buf.try_put_u16(0x0102).unwrap();
+    buf.try_put_u16_le(0x0102).unwrap();
+    buf.try_put_i32(-2).unwrap();
+    buf.try_put_u64_le(0x0102_0304_0506_0708).unwrap();
+    buf.try_put_f32(1.0).unwrap();
+    buf.try_put_u128_ne(7).unwrap();
+    buf.try_put_slice(&[]).unwrap();
+    buf.try_put_slice(b"end").unwrap();
+
+    let mut expected = vec![0xff, 0xff, 1, 2, 2, 1, 0xff, 0xff, 0xff, 0xfe];
+    expected.extend_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+    expected.extend_from_slice(&1.0f32.to_be_bytes());
+    expected.extend_from_slice(&7u128.to_ne_bytes());
+    expected.extend_from_slice(b"end");
+    assert_eq!(buf, expected);
+
+    // Explicit typing to ensure that the allocator is passed through.
+    let mut buf: Vec<u8, System> = try_vec_in![System].unwrap();
+    buf.try_put_u16_le(0x0102).unwrap();
+    assert_eq!(buf, [2, 1]);
+}
+
+#[test]
+fn test_extend_from_display() {
+    let mut buf = b"x=".to_vec();
+    buf.try_extend_from_display(format_args!("{}-{:?}", 12, "a"))
+        .unwrap();
+    assert_eq!(buf, b"x=12-\"a\"");
+
+    let mut s = String::from("y=");
+    s.try_extend_from_display(-7).unwrap();
+    s.try_extend_from_display("").unwrap();
+    assert_eq!(s, "y=-7");
+}
+
+#[test]
+#[should_panic(expected = "a Display implementation returned an error unexpectedly")]
+fn test_extend_from_display_error() {
+    struct Failing;
+    impl core::fmt::Display for Failing {
+        fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            Err(core::fmt::Error)
+        }
+    }
+
+    let mut buf = Vec::new();
+    let _ = buf.try_extend_from_display(Failing);
+}
+
+#[test]
+fn test_escape() {
+    let input = "\u{301}tab\there \"quoted\" caf\u{e9} \u{1f980}\r\n";
+    assert_eq!(
+        try_escape_default(input).unwrap(),
+        input.escape_default().to_string()
+    );
+    assert_eq!(
+        try_escape_debug(input).unwrap(),
+        input.escape_debug().to_string()
+    );
+    assert_eq!(try_escape_default("").unwrap(), "");
+
+    let mut s = String::from("prefix:");
+    s.try_push_escape_debug(input).unwrap();
+    assert_eq!(s, format!("prefix:{}", input.escape_debug()));
+    assert_eq!(s.capacity(), s.len());
+}
+
+#[test]
+fn test_splice_from_slice() {
+    let mut vec = try_vec![1, 2, 3, 4, 5].unwrap();
+    vec.try_splice_from_slice(1..1, &[10, 11]).unwrap();
+    assert_eq!(vec, [1, 10, 11, 2, 3, 4, 5]);
+    vec.try_splice_from_slice(2..=5, &[20]).unwrap();
+    assert_eq!(vec, [1, 10, 20, 5]);
+    vec.try_splice_from_slice(4.., &[30, 31]).unwrap();
+    assert_eq!(vec, [1, 10, 20, 5, 30, 31]);
+    vec.try_splice_from_slice(.., &[]).unwrap();
+    assert!(vec.is_empty());
+
+    // Removed items are dropped exactly once.
+    let item = Rc::new(0);
+    let mut vec = try_vec![item.clone(), item.clone(), item.clone()].unwrap();
+    vec.try_splice_from_slice(..2, core::slice::from_ref(&item))
+        .unwrap();
+    assert_eq!(vec.len(), 2);
+    assert_eq!(Rc::strong_count(&item), 3);
+}
+
+#[test]
+fn test_bounded_log_buffer() {
+    let mut log = BoundedLogBuffer::new(3, OverflowPolicy::EvictOldest);
+    assert!(log.is_empty());
+    for i in 0..5 {
+        log.push(i);
+    }
+    assert_eq!(log.as_slices(), (&[2][..], &[3, 4][..]));
+    assert_eq!(log.lost_count(), 2);
+
+    let mut log = BoundedLogBuffer::new(2, OverflowPolicy::DropNewest);
+    for i in 0..5 {
+        log.push(i);
+    }
+    assert!(log.iter().eq(&[0, 1]));
+    assert_eq!(log.reset_lost_count(), 3);
+    assert_eq!(log.lost_count(), 0);
+
+    // Nothing can be stored in a zero-length buffer.
+    let mut log = BoundedLogBuffer::new(0, OverflowPolicy::EvictOldest);
+    log.push(1);
+    assert!(log.is_empty());
+    assert_eq!(log.lost_count(), 1);
+}
+
+#[test]
+fn test_bounded_log_buffer_alloc_failure() {
+    // Room for 2 items, although the buffer may hold up to 4.
+    let alloc = LimitedAllocator {
+        limit: 2 * core::mem::size_of::<u32>(),
+    };
+    let mut log = BoundedLogBuffer::new_in(4, OverflowPolicy::EvictOldest, alloc);
+    for i in 0..3u32 {
+        log.push(i);
+    }
+    assert!(log.iter().eq(&[1, 2]));
+    assert_eq!(log.lost_count(), 1);
+
+    // After clearing, the existing capacity is reused and the entries are in
+    // order again.
+    log.clear();
+    log.push(5);
+    assert!(log.iter().eq(&[5]));
+}
+
+#[test]
+fn test_shrink_on_drop() {
+    let mut vec: Vec<u32> = try_with_capacity(100).unwrap();
+    {
+        let mut vec = shrink_on_drop(&mut vec, 10);
+        vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+    }
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(vec.capacity(), 10);
+
+    // Never shrinks below the length.
+    drop(shrink_on_drop(&mut vec, 0));
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(vec.capacity(), 3);
+
+    // Never grows.
+    drop(shrink_on_drop(&mut vec, 50));
+    assert_eq!(vec.capacity(), 3);
+
+    vec.clear();
+    drop(shrink_on_drop(&mut vec, 0));
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn test_shrink_on_drop_with_error() {
+    let mut vec = try_with_capacity_in(8, NoShrinkAllocator).unwrap();
+    let mut error = None;
+    {
+        let mut vec = shrink_on_drop_with(&mut vec, 2, |e| error = Some(e));
+        vec.try_push(1).unwrap();
+    }
+    assert!(error.is_some());
+    assert_eq!(vec, [1]);
+    assert_eq!(vec.capacity(), 8);
+}
+
+#[test]
+fn test_reserve_all() {
+    let mut a: Vec<u8> = Vec::new();
+    let mut b = String::new();
+    let mut c: &mut Vec<u8> = &mut try_with_capacity(4).unwrap();
+    try_reserve_all!(a => 8, b => 16, c => 100).unwrap();
+    assert!(a.capacity() >= 8);
+    assert!(b.capacity() >= 16);
+    assert!(c.capacity() >= 100);
+    try_reserve_all(&mut []).unwrap();
+}
+
+#[test]
+fn test_reserve_all_rolls_back() {
+    let alloc = LimitedAllocator { limit: 64 };
+    let mut a: Vec<u8, _> = try_with_capacity_in(2, &alloc).unwrap();
+    a.try_push(1).unwrap();
+    let mut b: Vec<u8> = try_with_capacity(3).unwrap();
+    let mut c: Vec<u8, _> = Vec::new_in(&alloc);
+    let (a_capacity, b_capacity) = (a.capacity(), b.capacity());
+
+    // The last reservation fails, so the first two are rolled back.
+    assert!(try_reserve_all!(a => 10, b => 1000, c => 1000).is_err());
+    assert_eq!(a, [1]);
+    assert_eq!(a.capacity(), a_capacity);
+    assert_eq!(b.capacity(), b_capacity);
+    assert_eq!(c.capacity(), 0);
+}
+
+#[test]
+fn test_get_or_push() {
+    let mut values = Vec::new();
+    let case_insensitive = |x: &&str, y: &&str| x.eq_ignore_ascii_case(y);
+    assert_eq!(values.try_get_or_push("a", case_insensitive).unwrap(), 0);
+    assert_eq!(values.try_get_or_push("B", case_insensitive).unwrap(), 1);
+    assert_eq!(values.try_get_or_push("b", case_insensitive).unwrap(), 1);
+    assert_eq!(values, ["a", "B"]);
+
+    let mut created = 0;
+    let mut values: Vec<i32> = Vec::new();
+    for value in [3, 1, 3, 3, 2, 1] {
+        values
+            .try_get_or_push_with(
+                |&existing| existing == value,
+                || {
+                    created += 1;
+                    value
+                },
+            )
+            .unwrap();
+    }
+    assert_eq!(values, [3, 1, 2]);
+    assert_eq!(created, 3);
+}
+
+#[test]
+fn test_try_make_mut() {
+    let mut data = Rc::new(try_vec![1, 2].unwrap());
+    Rc::try_make_mut(&mut data).unwrap().try_push(3).unwrap();
+    assert_eq!(*data, [1, 2, 3]);
+
+    // Cloned, disassociating from weak pointers, if there are any.
+    let original = Rc::as_ptr(&data);
+    let weak = Rc::downgrade(&data);
+    Rc::try_make_mut(&mut data).unwrap().try_push(4).unwrap();
+    assert_ne!(Rc::as_ptr(&data), original);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(*data, [1, 2, 3, 4]);
+
+    let mut data = std::sync::Arc::new(try_vec_in![1 => System].unwrap());
+    let other = data.clone();
+    std::sync::Arc::try_make_mut(&mut data).unwrap()[0] = 5;
+    assert_eq!(*data, [5]);
+    assert_eq!(*other, [1]);
+}
+
+#[test]
+fn test_boxed_slice_growth() {
+    let boxed: Box<[i32]> = Box::new([]);
+    let boxed = try_push_boxed(boxed, 1).unwrap();
+    let boxed = try_concat_boxed(boxed, &[2, 3]).unwrap();
+    let boxed = try_concat_boxed(boxed, &[]).unwrap();
+    assert_eq!(*boxed, [1, 2, 3]);
+
+    let boxed: Box<[()], System> = Box::new_in([(), ()], System);
+    let boxed = try_push_boxed(boxed, ()).unwrap();
+    assert_eq!(boxed.len(), 3);
+}
+
+#[test]
+fn test_boxed_slice_growth_failure() {
+    let alloc = LimitedAllocator {
+        limit: 2 * core::mem::size_of::<u32>(),
+    };
+    let boxed = Box::new_in([1u32, 2], &alloc);
+    let (boxed, _) = try_push_boxed(boxed, 3).unwrap_err();
+    let (boxed, _) = try_concat_boxed(boxed, &[3]).unwrap_err();
+    assert_eq!(*boxed, [1, 2]);
+}
+
+#[test]
+fn test_no_allocation_without_growth() {
+    /// An empty iterator that claims it will return items.
+    struct LyingIterator;
+
+    impl Iterator for LyingIterator {
+        type Item = u32;
+
+        fn next(&mut self) -> Option<u32> {
+            None
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (10, None)
+        }
+    }
+
+    // No allocation can succeed, so the `Vec` cannot grow.
+    let alloc = LimitedAllocator { limit: 0 };
+    let mut vec: Vec<u32, _> = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[]).unwrap();
+    vec.try_extend(LyingIterator).unwrap();
+    vec.try_extend(core::iter::empty()).unwrap();
+    vec.try_splice_in(.., LyingIterator, &alloc).unwrap();
+    vec.try_splice_from_slice(.., &[]).unwrap();
+    vec.try_resize(0, 1).unwrap();
+    vec.try_resize_with(0, || 1).unwrap();
+    vec.try_remove_indices(&[]).unwrap();
+    vec.try_drain_collect_in(.., &alloc).unwrap();
+    vec.try_split_off_in(0, &alloc).unwrap();
+    LyingIterator.try_collect_in(&alloc).unwrap();
+    try_with_capacity_in::<u32, _>(0, &alloc).unwrap();
+    try_from_elem_in(1, 0, &alloc).unwrap();
+
+    let mut bytes: Vec<u8, _> = Vec::new_in(&alloc);
+    bytes.try_put_slice(&[]).unwrap();
+    bytes.try_extend_from_display("").unwrap();
+
+    // Items that fit within the existing capacity don't allocate either.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec: Vec<u32, _> = try_with_capacity_in(4, &alloc).unwrap();
+    vec.try_extend([1, 2]).unwrap();
+    vec.try_splice_in(1..2, [3], &alloc).unwrap();
+    vec.try_insert(0, 4).unwrap();
+    vec.try_extend(LyingIterator.chain([5])).unwrap();
+    assert_eq!(vec, [4, 1, 3, 5]);
+}
+
+#[test]
+fn test_resize_with_fallible() {
+    let mut vec = try_vec![1].unwrap();
+    let mut next = 1;
+    let mut source = || {
+        next += 1;
+        if next > 4 {
+            Err(next)
+        } else {
+            Ok(next)
+        }
+    };
+    vec.try_resize_with_fallible(3, &mut source).unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(
+        vec.try_resize_with_fallible(10, &mut source),
+        Err(ItemOrAllocError::Item(5))
+    );
+    assert_eq!(vec, [1, 2, 3, 4]);
+    vec.try_resize_with_fallible(1, || Err(0)).unwrap();
+    assert_eq!(vec, [1]);
+
+    let mut vec: Vec<u32, _> = Vec::new_in(LimitedAllocator { limit: 0 });
+    assert!(matches!(
+        vec.try_resize_with_fallible(1, || Ok::<_, ()>(1)),
+        Err(ItemOrAllocError::Alloc(_))
+    ));
+}
+
+#[test]
+fn test_extend_fallible() {
+    let mut vec = try_vec![0].unwrap();
+    assert_eq!(
+        vec.try_extend_fallible(core::iter::empty::<Result<_, ()>>()),
+        Ok(0)
+    );
+    assert_eq!(vec.try_extend_fallible([Ok::<_, ()>(1), Ok(2)]), Ok(2));
+    assert_eq!(
+        vec.try_extend_fallible([Err(5), Ok(3)]),
+        Err((0, ItemOrAllocError::Item(5)))
+    );
+    assert_eq!(vec, [0, 1, 2]);
+
+    // Nothing is reserved if the first item is an error.
+    let mut vec: Vec<u32> = Vec::new();
+    assert_eq!(
+        vec.try_extend_fallible([Err(5), Ok(3), Ok(4)]),
+        Err((0, ItemOrAllocError::Item(5)))
+    );
+    assert_eq!(vec.capacity(), 0);
+
+    // Only room for 4 items.
+    let mut vec: Vec<u32, _> = Vec::new_in(LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    });
+    let (appended, error) = vec
+        .try_extend_fallible((0..10).map(Ok::<_, ()>).filter(|_| true))
+        .unwrap_err();
+    assert_eq!(appended, 4);
+    assert!(matches!(error, ItemOrAllocError::Alloc(_)));
+    assert_eq!(vec, [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_extend_guard() {
+    let mut vec: Vec<i32> = try_with_capacity(2).unwrap();
+    vec.try_push(1).unwrap();
+    let capacity = vec.capacity();
+    {
+        let mut guard = ExtendGuard::new(&mut vec);
+        assert_eq!(guard.len(), 1);
+        for i in 2.. {
+            if guard.push_within_capacity(i).is_err() {
+                break;
+            }
+        }
+        assert_eq!(guard.len(), capacity);
+        assert_eq!(guard.spare_capacity(), 0);
+    }
+    assert_eq!(vec.len(), capacity);
+    assert_eq!(vec.capacity(), capacity);
+
+    // Items written before a panic are committed to the `Vec`.
+    let drop_counter = AtomicI32::new(0);
+    let mut vec = Vec::new();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut guard = ExtendGuard::try_reserve(&mut vec, 3).unwrap();
+        for i in 0..3 {
+            if i == 2 {
+                panic!("BOOM");
+            }
+            let item = ExplodingCloner {
+                clone_panics: Cell::new(false),
+                drop_counter: Some(&drop_counter),
+            };
+            assert!(guard.push_within_capacity(item).is_ok());
+        }
+    }))
+    .is_err());
+    assert_eq!(vec.len(), 2);
+    drop(vec);
+    assert_eq!(drop_counter.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn test_try_reserve_guard() {
+    let mut vec = try_vec![1].unwrap();
+    {
+        let mut guard = vec.try_reserve_guard(3).unwrap();
+        for i in 2..5 {
+            guard.push(i);
+        }
+        assert_eq!(guard.len(), 4);
+    }
+    assert_eq!(vec, [1, 2, 3, 4]);
+
+    let mut vec: Vec<u32, _> = Vec::new_in(LimitedAllocator { limit: 16 });
+    assert!(vec.try_reserve_guard(5).map(|_| ()).is_err());
+    assert!(vec.try_reserve_guard(3).unwrap().spare_capacity() >= 3);
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_guard_push_beyond_capacity() {
+    let mut vec: Vec<u32> = Vec::new();
+    let mut guard = vec.try_reserve_guard(1).unwrap();
+    for i in 0..=guard.spare_capacity() as u32 {
+        guard.push(i);
+    }
+}
+
+#[test]
+fn test_try_spare_capacity() {
+    let mut vec = try_vec![1u8].unwrap();
+    {
+        let mut writer = vec.try_spare_capacity(3).unwrap();
+        assert_eq!(writer.len(), 3);
+        for (i, item) in writer.iter_mut().enumerate() {
+            item.write(i as u8 + 2);
+        }
+        unsafe { writer.commit(3) };
+    }
+    assert_eq!(vec, [1, 2, 3, 4]);
+
+    // Dropping the writer without committing leaves the `Vec` unchanged.
+    {
+        let mut writer = vec.try_spare_capacity(2).unwrap();
+        writer[0].write(5);
+    }
+    assert_eq!(vec, [1, 2, 3, 4]);
+
+    let mut vec: Vec<u8, _> = Vec::new_in(LimitedAllocator { limit: 16 });
+    assert!(vec.try_spare_capacity(17).map(|_| ()).is_err());
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn test_try_spare_capacity_growth() {
+    // Writing one item at a time doesn't reallocate on every call.
+    let mut vec: Vec<u8> = Vec::new();
+    let mut reallocations = 0;
+    for i in 0..64 {
+        let capacity = vec.capacity();
+        let mut writer = vec.try_spare_capacity(1).unwrap();
+        writer[0].write(i);
+        unsafe { writer.commit(1) };
+        if vec.capacity() != capacity {
+            reallocations += 1;
+        }
+    }
+    assert!(reallocations < 64);
+    assert_eq!(vec, (0..64).collect::<Vec<u8>>());
+
+    let mut vec = try_vec![1u8].unwrap();
+    assert_eq!(vec.try_spare_capacity_exact(3).unwrap().len(), 3);
+    assert_eq!(vec.capacity(), 4);
+
+    let mut vec: Vec<u8, _> = Vec::new_in(LimitedAllocator { limit: 16 });
+    assert!(vec.try_spare_capacity_exact(17).map(|_| ()).is_err());
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_spare_capacity_commit_too_many() {
+    let mut vec: Vec<u8> = Vec::new();
+    let writer = vec.try_spare_capacity(2).unwrap();
+    unsafe { writer.commit(3) };
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_hex() {
+    let data: Vec<u8> = (0..=255).collect();
+    let encoded = try_hex_encode(&data).unwrap();
+    assert_eq!(encoded.len(), 512);
+    assert_eq!(encoded.capacity(), 512);
+    assert!(encoded.starts_with("000102"));
+    assert_eq!(try_hex_decode(&encoded).unwrap(), data);
+    assert_eq!(try_hex_decode(encoded.to_uppercase()).unwrap(), data);
+    assert_eq!(try_hex_encode(&[]).unwrap(), "");
+
+    let mut out = try_vec![1].unwrap();
+    assert_eq!(
+        try_hex_decode_into(&mut out, "abcx"),
+        Err(DecodeError::InvalidByte { index: 3 })
+    );
+    assert_eq!(out, [1]);
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_base64() {
+    for (data, encoded) in [
+        (&b""[..], ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"foob", "Zm9vYg=="),
+        (b"fooba", "Zm9vYmE="),
+        (b"foobar", "Zm9vYmFy"),
+        (&[0xfb, 0xff], "+/8="),
+    ] {
+        let actual = try_base64_encode(data).unwrap();
+        assert_eq!(actual, encoded);
+        assert_eq!(actual.capacity(), encoded.len());
+        assert_eq!(try_base64_decode(encoded).unwrap(), data);
+    }
+
+    let mut out = String::from("token=");
+    try_base64_encode_into(&mut out, b"hi").unwrap();
+    assert_eq!(out, "token=aGk=");
+
+    assert_eq!(try_base64_decode("Zg="), Err(DecodeError::InvalidLength));
+    assert_eq!(
+        try_base64_decode("Z==="),
+        Err(DecodeError::InvalidByte { index: 1 })
+    );
+    assert_eq!(
+        try_base64_decode("Zg==Zg=="),
+        Err(DecodeError::InvalidByte { index: 2 })
+    );
+    let mut out = try_vec![1].unwrap();
+    assert!(try_base64_decode_into(&mut out, "Zm9v*m9v").is_err());
+    assert_eq!(out, [1]);
+}
+
+#[test]
+fn test_append_from() {
+    let mut vec = try_vec![Rc::new(1)].unwrap();
+    let mut other = try_vec_in![Rc::new(2), Rc::new(3) => System].unwrap();
+    vec.try_append_from(&mut other).unwrap();
+    assert!(other.is_empty());
+    assert_eq!(vec, [Rc::new(1), Rc::new(2), Rc::new(3)]);
+    vec.try_append_from(&mut other).unwrap();
+    assert_eq!(vec.len(), 3);
+
+    // Both are left untouched if the reservation fails.
+    let mut vec: Vec<u32, _> = Vec::new_in(LimitedAllocator { limit: 0 });
+    let mut other = try_vec![1, 2].unwrap();
+    assert!(vec.try_append_from(&mut other).is_err());
+    assert!(vec.is_empty());
+    assert_eq!(other, [1, 2]);
+}
+
+#[test]
+fn test_zeroizing_vec() {
+    /// An allocator that checks that memory has been wiped before it is freed.
+    #[derive(Clone, Copy)]
+    struct WipeCheckingAllocator<'a> {
+        deallocations: &'a Cell<usize>,
+    }
+
+    unsafe impl Allocator for WipeCheckingAllocator<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            System.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let memory = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+            assert!(memory.iter().all(|&b| b == 0), "memory was not wiped");
+            self.deallocations.set(self.deallocations.get() + 1);
+            unsafe { System.deallocate(ptr, layout) }
+        }
+    }
+
+    let deallocations = Cell::new(0);
+    let alloc = WipeCheckingAllocator {
+        deallocations: &deallocations,
+    };
+    let mut vec = ZeroizingVec::new_in(alloc);
+    for i in 1..=20u64 {
+        vec.try_push(i).unwrap();
+    }
+    vec.try_extend_from_slice(&[u64::MAX; 10]).unwrap();
+    assert_eq!(vec.len(), 30);
+    assert!(deallocations.get() > 0);
+
+    assert_eq!(vec.pop(), Some(u64::MAX));
+    vec.truncate(5);
+    assert_eq!(*vec, [1, 2, 3, 4, 5]);
+    vec[0] = 42;
+    let before_shrink = deallocations.get();
+    vec.try_shrink_to_fit().unwrap();
+    assert_eq!(vec.capacity(), 5);
+    assert_eq!(deallocations.get(), before_shrink + 1);
+    assert_eq!(*vec, [42, 2, 3, 4, 5]);
+
+    drop(vec);
+    assert_eq!(deallocations.get(), before_shrink + 2);
+
+    let mut vec: ZeroizingVec<u8> = ZeroizingVec::try_with_capacity(3).unwrap();
+    vec.clear();
+    assert!(vec.is_empty());
+    assert_eq!(vec.pop(), None);
+}
+
+#[test]
+#[cfg(feature = "libc")]
+fn test_errno() {
+    let mut vec: Vec<u8> = Vec::new();
+    let overflow = vec.try_reserve(usize::MAX).unwrap_err();
+    assert_eq!(overflow.to_errno(), libc::EOVERFLOW);
+    let alloc_failed = alloc_error(Layout::new::<[u8; 16]>());
+    assert_eq!(alloc_failed.to_errno(), libc::ENOMEM);
+
+    let error: ItemOrAllocError<TryReserveError> = overflow.into();
+    assert_eq!(error.to_errno(), libc::EOVERFLOW);
+
+    for errno in [libc::ENOMEM, libc::EOVERFLOW] {
+        let error = try_reserve_error_from_errno(errno).unwrap();
+        assert_eq!(error.to_errno(), errno);
+    }
+    assert!(try_reserve_error_from_errno(0).is_none());
+    assert!(try_reserve_error_from_errno(libc::EINVAL).is_none());
+}
+
+#[test]
+fn test_try_collect_into() {
+    let mut string = String::from("a");
+    ['b', 'é', '🦀'].try_collect_into(&mut string).unwrap();
+    ["cd", "", "ef"].try_collect_into(&mut string).unwrap();
+    assert_eq!(string, "abé🦀cdef");
+
+    let mut deque = std::collections::VecDeque::from([1, 2]);
+    deque.pop_front();
+    let deque = (3..6).try_collect_into(&mut deque).unwrap();
+    assert_eq!(*deque, [2, 3, 4, 5]);
+
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut deque = std::collections::VecDeque::new_in(alloc);
+    (0..3u32).try_collect_into(&mut deque).unwrap();
+    (3..10u32).try_collect_into(&mut deque).unwrap_err();
+    assert_eq!(deque, [0, 1, 2]);
+
+    // An empty iterator never allocates.
+    let mut deque = std::collections::VecDeque::new_in(LimitedAllocator { limit: 0 });
+    core::iter::empty::<u32>()
+        .try_collect_into(&mut deque)
+        .unwrap();
+}
+
+#[test]
+fn test_try_chunks() {
+    let chunks: Vec<_> = (0..7).try_chunks(3).map(Result::unwrap).collect();
+    assert_eq!(chunks, [vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+    assert_eq!((0..6).try_chunks(3).size_hint(), (1, Some(2)));
+    assert_eq!(core::iter::empty::<i32>().try_chunks(3).next(), None);
+
+    // Chunks that are too large for the allocator fail, which ends the
+    // iterator.
+    let alloc = LimitedAllocator {
+        limit: 2 * core::mem::size_of::<u32>(),
+    };
+    let mut chunks = [1u32, 2, 3].into_iter().try_chunks_in(2, &alloc);
+    assert_eq!(*chunks.next().unwrap().unwrap(), [1, 2]);
+    assert_eq!(*chunks.next().unwrap().unwrap(), [3]);
+    assert!(chunks.next().is_none());
+
+    let mut chunks = (0u32..).try_chunks_in(3, &alloc);
+    assert!(chunks.next().unwrap().is_err());
+    assert!(chunks.next().is_none());
+}
+
+#[test]
+fn test_try_multipeek() {
+    let mut iter = (1..=4).try_multipeek();
+    assert_eq!(iter.peek(), Ok(Some(&1)));
+    assert_eq!(iter.peek(), Ok(Some(&2)));
+    assert_eq!(iter.peek(), Ok(Some(&3)));
+    iter.reset_peek();
+    assert_eq!(iter.peek(), Ok(Some(&1)));
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.peek(), Ok(Some(&2)));
+    assert_eq!(iter.by_ref().collect::<Vec<_>>(), [2, 3, 4]);
+    assert_eq!(iter.peek(), Ok(None));
+
+    // If the lookahead buffer can't grow, then no item is consumed.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u64>(),
+    };
+    let mut iter = (0u64..10).try_multipeek_in(&alloc);
+    for i in 0..4 {
+        assert_eq!(iter.peek(), Ok(Some(&i)));
+    }
+    assert!(iter.peek().is_err());
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.count(), 9);
+}
+
+#[test]
+fn test_try_multipeek_reuses_buffer() {
+    // Only room for 4 items in the lookahead buffer.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<Rc<i32>>(),
+    };
+    let items: Vec<_> = (0..16).map(Rc::new).collect();
+    let mut iter = items.iter().cloned().try_multipeek_in(&alloc);
+    // Keep one item buffered while moving through the rest, so the space of
+    // the items that were returned from `next` has to be reused.
+    assert_eq!(iter.peek(), Ok(Some(&items[0])));
+    for i in 0..14 {
+        assert_eq!(iter.peek(), Ok(Some(&items[i + 1])));
+        assert_eq!(iter.next().as_ref(), Some(&items[i]));
+        assert_eq!(iter.peek(), Ok(Some(&items[i + 1])));
+    }
+
+    // Dropping the iterator drops the buffered items exactly once.
+    drop(iter);
+    assert!(items.iter().all(|item| Rc::strong_count(item) == 1));
+}
+
+#[test]
+fn test_try_multipeek_exhausted_does_not_allocate() {
+    let mut iter = core::iter::empty::<u64>().try_multipeek_in(LimitedAllocator { limit: 0 });
+    assert_eq!(iter.peek(), Ok(None));
+
+    // Peeking past the end of a full lookahead buffer doesn't grow it.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u64>(),
+    };
+    let mut iter = (0u64..4).try_multipeek_in(&alloc);
+    for i in 0..4 {
+        assert_eq!(iter.peek(), Ok(Some(&i)));
+    }
+    assert_eq!(iter.peek(), Ok(None));
+    assert_eq!(iter.count(), 4);
+}
+
+#[test]
+fn test_persistent_vec() {
+    // Enough items for a tree with three levels.
+    const LEN: usize = 32 * 32 + 5;
+    let mut versions = Vec::new();
+    let mut vec = PersistentVec::new();
+    for i in 0..LEN {
+        versions.push(vec.clone());
+        vec = vec.try_push(i).unwrap();
+    }
+    assert_eq!(vec.len(), LEN);
+    assert!(vec.iter().copied().eq(0..LEN));
+    assert_eq!(vec.get(LEN), None);
+
+    // Older versions are unchanged.
+    for (len, version) in versions.iter().enumerate().step_by(97) {
+        assert!(version.iter().copied().eq(0..len));
+    }
+
+    let updated = vec.try_update(1000, 42).unwrap();
+    assert_eq!(updated.get(1000), Some(&42));
+    assert_eq!(vec.get(1000), Some(&1000));
+    assert_eq!(updated.get(999), Some(&999));
+    assert_eq!(
+        format!("{:?}", PersistentVec::new().try_push(1).unwrap()),
+        "[1]"
+    );
+}
+
+#[test]
+fn test_persistent_vec_shares_items() {
+    let item = Rc::new(());
+    let mut vec = PersistentVec::new();
+    for _ in 0..100 {
+        vec = vec.try_push(Rc::clone(&item)).unwrap();
+    }
+    // Only the last version (and the item itself) remain.
+    assert_eq!(Rc::strong_count(&item), 101);
+    let snapshot = vec.clone();
+    let updated = vec.try_update(0, Rc::new(())).unwrap();
+    // Only the leaf that was updated was copied.
+    assert_eq!(Rc::strong_count(&item), 101 + 31);
+    drop((vec, snapshot, updated));
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn test_slot_map() {
+    let mut map = SlotMap::new();
+    let keys: Vec<_> = (0..5).map(|i| map.try_insert(i).unwrap()).collect();
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.remove(keys[1]), Some(1));
+    assert_eq!(map.remove(keys[1]), None);
+    assert_eq!(map.remove(keys[3]), Some(3));
+
+    // Vacant slots are reused (most recently freed first) without allocating.
+    let capacity = map.capacity();
+    let reused = map.try_insert(30).unwrap();
+    let reused2 = map.try_insert(10).unwrap();
+    assert_eq!(map.capacity(), capacity);
+    assert!(!map.contains_key(keys[3]));
+    assert_eq!(map.get(reused), Some(&30));
+    *map.get_mut(reused2).unwrap() += 1;
+    let values: Vec<_> = map.iter().map(|(_, value)| *value).collect();
+    assert_eq!(values, [0, 11, 2, 30, 4]);
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.get(keys[0]), None);
+    assert_eq!(map.iter().count(), 0);
+
+    let mut map = SlotMap::new_in(LimitedAllocator {
+        limit: 4 * core::mem::size_of::<(u32, Option<u64>)>(),
+    });
+    map.try_reserve(4).unwrap();
+    for i in 0..4 {
+        map.try_insert(i).unwrap();
+    }
+    map.try_insert(4).unwrap_err();
+    assert_eq!(map.len(), 4);
+}
+
+#[test]
+fn test_try_append() {
+    let mut vec = vec![Rc::new(1), Rc::new(2)];
+    let mut other = vec![Rc::new(3)];
+    let item = Rc::clone(&other[0]);
+    vec.try_append(&mut other).unwrap();
+    assert_eq!(vec, [Rc::new(1), Rc::new(2), Rc::new(3)]);
+    assert!(other.is_empty());
+    assert_eq!(Rc::strong_count(&item), 2);
+
+    // If the reservation fails, then both `Vec`s are unchanged.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3]).unwrap();
+    let mut other = Vec::new_in(&alloc);
+    other.try_extend_from_slice(&[4, 5]).unwrap();
+    vec.try_append(&mut other).unwrap_err();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(other, [4, 5]);
+}
+
+#[test]
+fn test_try_split_off() {
+    let mut vec = vec![1, 2, 3, 4];
+    let capacity = vec.capacity();
+    assert_eq!(vec.try_split_off(4).unwrap(), []);
+    assert_eq!(vec.try_split_off(1).unwrap(), [2, 3, 4]);
+    assert_eq!(vec, [1]);
+    assert_eq!(vec.capacity(), capacity);
+
+    // The new `Vec` uses the same allocator.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3, 4]).unwrap();
+    let tail = vec.try_split_off(2).unwrap();
+    assert_eq!(tail, [3, 4]);
+    assert!(core::ptr::eq(*tail.allocator(), &alloc));
+}
+
+#[test]
+fn test_try_extend_from_within() {
+    let mut vec = vec![Rc::new(1), Rc::new(2), Rc::new(3)];
+    vec.try_extend_from_within(1..=1).unwrap();
+    vec.try_extend_from_within(..0).unwrap();
+    assert_eq!(vec, [Rc::new(1), Rc::new(2), Rc::new(3), Rc::new(2)]);
+    assert_eq!(Rc::strong_count(&vec[1]), 2);
+
+    // If the reservation fails, then the `Vec` is unchanged.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3]).unwrap();
+    vec.try_extend_from_within(..).unwrap_err();
+    assert_eq!(vec, [1, 2, 3]);
+}
+
+#[test]
+fn test_try_extend_from_within_copy() {
+    let mut vec = try_vec![1u32, 2, 3].unwrap();
+    vec.try_extend_from_within_copy(1..).unwrap();
+    vec.try_extend_from_within_copy(..0).unwrap();
+    assert_eq!(vec, [1, 2, 3, 2, 3]);
+
+    // If the reservation fails, then the `Vec` is unchanged.
+    let mut vec = try_vec_in![1u32, 2, 3 => LimitedAllocator { limit: 16 }].unwrap();
+    vec.try_extend_from_within_copy(..).unwrap_err();
+    assert_eq!(vec, [1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "range end index 4 out of range for slice of length 3")]
+fn test_try_extend_from_within_out_of_range() {
+    let mut vec = vec![1, 2, 3];
+    let _ = vec.try_extend_from_within(1..4);
+}
+
+#[test]
+fn test_try_extend_from_within_clone_panic() {
+    let drop_counter = AtomicI32::new(0);
+    let mut vec = vec![
+        ExplodingCloner {
+            clone_panics: Cell::new(false),
+            drop_counter: Some(&drop_counter),
+        },
+        ExplodingCloner {
+            clone_panics: Cell::new(true),
+            drop_counter: Some(&drop_counter),
+        },
+    ];
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = vec.try_extend_from_within(..);
+    }));
+    assert!(result.is_err());
+    // The first item was cloned before the second panicked.
+    assert_eq!(vec.len(), 3);
+    drop(vec);
+    assert_eq!(drop_counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_try_insert_slice() {
+    let mut vec = vec![1, 5];
+    vec.try_insert_slice(1, &[2, 3, 4]).unwrap();
+    vec.try_insert_slice(5, &[6]).unwrap();
+    vec.try_insert_slice(0, &[]).unwrap();
+    assert_eq!(vec, [1, 2, 3, 4, 5, 6]);
+
+    // If the reservation fails, then the `Vec` is unchanged.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3]).unwrap();
+    vec.try_insert_slice(1, &[7, 8]).unwrap_err();
+    assert_eq!(vec, [1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "insertion index (is 3) should be <= len (is 2)")]
+fn test_try_insert_slice_out_of_range() {
+    let mut vec = vec![1, 2];
+    let _ = vec.try_insert_slice(3, &[3]);
+}
+
+#[test]
+fn test_try_insert_many() {
+    let mut vec = vec![Rc::new(1), Rc::new(5)];
+    vec.try_insert_many(1, (2..5).map(Rc::new)).unwrap();
+    vec.try_insert_many(5, [Rc::new(6)]).unwrap();
+    assert_eq!(vec, (1..=6).map(Rc::new).collect::<Vec<_>>());
+
+    // An iterator that is shorter than its reported length.
+    struct Short(i32);
+    impl Iterator for Short {
+        type Item = i32;
+        fn next(&mut self) -> Option<i32> {
+            self.0 -= 1;
+            (self.0 >= 0).then_some(self.0)
+        }
+    }
+    impl ExactSizeIterator for Short {
+        fn len(&self) -> usize {
+            10
+        }
+    }
+    let mut vec = vec![1, 2];
+    vec.try_insert_many(1, Short(2)).unwrap();
+    assert_eq!(vec, [1, 1, 0, 2]);
+
+    // If the reservation fails, then the `Vec` is unchanged.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3]).unwrap();
+    vec.try_insert_many(0, [7, 8]).unwrap_err();
+    assert_eq!(vec, [1, 2, 3]);
+}
+
+#[test]
+fn test_try_drain_into() {
+    let mut vec = vec![1, 2, 3, 4, 5];
+    assert_eq!(vec.try_drain_into(..0).unwrap(), []);
+    assert_eq!(vec.try_drain_into(3..).unwrap(), [4, 5]);
+    assert_eq!(vec.try_drain_into(..=0).unwrap(), [1]);
+    assert_eq!(vec, [2, 3]);
+
+    // The new `Vec` uses the same allocator.
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let mut vec = Vec::new_in(&alloc);
+    vec.try_extend_from_slice(&[1u32, 2, 3, 4]).unwrap();
+    let drained = vec.try_drain_into(1..3).unwrap();
+    assert_eq!(drained, [2, 3]);
+    assert_eq!(vec, [1, 4]);
+    assert!(core::ptr::eq(*drained.allocator(), &alloc));
+}
+
+#[test]
+fn test_try_extend_repeat() {
+    let item = Rc::new(1);
+    let mut vec = vec![Rc::new(0)];
+    vec.try_extend_repeat(Rc::clone(&item), 3).unwrap();
+    assert_eq!(vec, [Rc::new(0), Rc::new(1), Rc::new(1), Rc::new(1)]);
+    // Two clones were made, and the original was moved into the `Vec`.
+    assert_eq!(Rc::strong_count(&item), 4);
+    vec.try_extend_repeat(Rc::clone(&item), 0).unwrap();
+    assert_eq!(vec.len(), 4);
+    assert_eq!(Rc::strong_count(&item), 4);
+
+    let mut vec: Vec<u8> = Vec::new();
+    assert!(vec.try_extend_repeat(0, usize::MAX).is_err());
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn test_try_extend_with() {
+    let mut vec = vec![0];
+    let mut next = 0;
+    vec.try_extend_with(3, || {
+        next += 1;
+        next
+    })
+    .unwrap();
+    vec.try_extend_with(0, || unreachable!()).unwrap();
+    assert_eq!(vec, [0, 1, 2, 3]);
+
+    // `f` isn't called if the reservation fails.
+    assert!(vec.try_extend_with(usize::MAX, || unreachable!()).is_err());
+    assert_eq!(vec, [0, 1, 2, 3]);
+
+    // Values returned before `f` panics are kept.
+    let mut vec = Vec::new();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut count = 0;
+        let _ = vec.try_extend_with(5, || {
+            count += 1;
+            assert!(count < 3, "BOOM");
+            count
+        });
+    }));
+    assert!(result.is_err());
+    assert_eq!(vec, [1, 2]);
+}
+
+#[test]
+fn test_try_from_fn() {
+    assert_eq!(try_from_fn(3, |i| i * 10).unwrap(), [0, 10, 20]);
+    assert_eq!(try_from_fn(0, |_| -> u8 { unreachable!() }).unwrap(), []);
+    assert!(try_from_fn(usize::MAX, |i| i).is_err());
+
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    let vec = try_from_fn_in(4, |i| i as u32, &alloc).unwrap();
+    assert_eq!(vec, [0, 1, 2, 3]);
+    assert_eq!(vec.capacity(), 4);
+    assert!(try_from_fn_in(5, |i| i as u32, &alloc).is_err());
+}
+
+#[test]
+fn test_try_from_elem() {
+    let item = Rc::new(1);
+    let vec = try_from_elem(Rc::clone(&item), 3).unwrap();
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.capacity(), 3);
+    assert_eq!(Rc::strong_count(&item), 4);
+    assert!(try_from_elem(0u64, usize::MAX).is_err());
+
+    let alloc = LimitedAllocator {
+        limit: 4 * core::mem::size_of::<u32>(),
+    };
+    assert_eq!(try_from_elem_in(7u32, 4, &alloc).unwrap(), [7; 4]);
+    assert!(try_from_elem_in(7u32, 5, &alloc).is_err());
+}
+
+#[test]
+fn test_try_into_vec() {
+    let item = Rc::new(1);
+    let vec = [Rc::clone(&item), Rc::clone(&item)].try_into_vec().unwrap();
+    assert_eq!(vec.capacity(), 2);
+    assert_eq!(Rc::strong_count(&item), 3);
+    drop(vec);
+    assert_eq!(Rc::strong_count(&item), 1);
+    assert_eq!(<[u8; 0]>::try_into_vec([]).unwrap(), []);
+
+    // If the allocation fails, then the array is dropped.
+    let alloc = LimitedAllocator {
+        limit: core::mem::size_of::<Rc<i32>>(),
+    };
+    [Rc::clone(&item), Rc::clone(&item)]
+        .try_into_vec_in(&alloc)
+        .unwrap_err();
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn test_try_with_exact_capacity() {
+    let vec = try_with_exact_capacity::<u8>(1).unwrap();
+    assert_eq!(vec.capacity(), 1);
+    assert!(try_with_exact_capacity::<u64>(usize::MAX).is_err());
+
+    let alloc = LimitedAllocator { limit: 3 };
+    let vec = try_with_exact_capacity_in::<u8, _>(3, &alloc).unwrap();
+    assert_eq!(vec.capacity(), 3);
+    assert!(try_with_exact_capacity_in::<u8, _>(4, &alloc).is_err());
+}
+
+#[test]
+fn test_try_vec_zeroed() {
+    let vec = try_vec_zeroed::<[u32; 3]>(5).unwrap();
+    assert_eq!(vec, [[0; 3]; 5]);
+    assert_eq!(vec.capacity(), 5);
+    assert_eq!(try_vec_zeroed::<()>(7).unwrap().len(), 7);
+    assert!(try_vec_zeroed::<u8>(0).unwrap().is_empty());
+    assert!(try_vec_zeroed::<u64>(usize::MAX).is_err());
+
+    let alloc = LimitedAllocator { limit: 16 };
+    let vec = try_vec_zeroed_in::<bool, _>(16, &alloc).unwrap();
+    assert!(vec.iter().all(|&b| !b));
+    assert!(try_vec_zeroed_in::<u8, _>(17, &alloc).is_err());
+}
+
+#[test]
+fn test_try_resize_zeroed() {
+    let mut vec = vec![u64::MAX; 2];
+    vec.try_resize_zeroed(4).unwrap();
+    assert_eq!(vec, [u64::MAX, u64::MAX, 0, 0]);
+    vec.try_resize_zeroed(4).unwrap();
+    vec.try_resize_zeroed(1).unwrap();
+    assert_eq!(vec, [u64::MAX]);
+
+    // The spare capacity is zeroed even if it was previously used.
+    vec.try_resize_zeroed(3).unwrap();
+    assert_eq!(vec, [u64::MAX, 0, 0]);
+    assert!(vec.try_resize_zeroed(usize::MAX).is_err());
+    assert_eq!(vec.len(), 3);
+}
+
+#[test]
+fn test_try_shrink_to_fit() {
+    let mut vec: Vec<u32> = try_with_capacity(10).unwrap();
+    FallibleVec::try_shrink_to_fit(&mut vec).unwrap();
+    assert_eq!(vec.capacity(), 0);
+    vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+    FallibleVec::try_shrink_to_fit(&mut vec).unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(vec.capacity(), 3);
+
+    // If shrinking fails, then the `Vec` is unchanged.
+    let mut vec = try_with_capacity_in(8, NoShrinkAllocator).unwrap();
+    vec.try_push(1u32).unwrap();
+    assert!(FallibleVec::try_shrink_to_fit(&mut vec).is_err());
+    assert_eq!(vec, [1]);
+    assert_eq!(vec.capacity(), 8);
+}
+
+#[test]
+fn test_try_shrink_to() {
+    let mut vec: Vec<u32> = try_with_capacity(10).unwrap();
+    vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+
+    // A lower bound above the current capacity is a no-op.
+    FallibleVec::try_shrink_to(&mut vec, 20).unwrap();
+    assert_eq!(vec.capacity(), 10);
+    FallibleVec::try_shrink_to(&mut vec, 5).unwrap();
+    assert_eq!(vec.capacity(), 5);
+    // The capacity never drops below the length.
+    FallibleVec::try_shrink_to(&mut vec, 1).unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(vec.capacity(), 3);
+
+    // If shrinking fails, then the `Vec` is unchanged.
+    let mut vec = try_with_capacity_in(8, NoShrinkAllocator).unwrap();
+    vec.try_push(1u32).unwrap();
+    assert!(FallibleVec::try_shrink_to(&mut vec, 2).is_err());
+    assert_eq!(vec, [1]);
+    assert_eq!(vec.capacity(), 8);
+}
+
+#[test]
+fn test_try_into_boxed_slice() {
+    let mut vec: Vec<u32> = try_with_capacity(10).unwrap();
+    vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+    let boxed = try_into_boxed_slice(vec).unwrap();
+    assert_eq!(*boxed, [1, 2, 3]);
+
+    // If shrinking fails, then the original `Vec` is returned.
+    let mut vec = try_with_capacity_in(8, NoShrinkAllocator).unwrap();
+    vec.try_push(1u32).unwrap();
+    let (vec, _) = try_into_boxed_slice(vec).unwrap_err();
+    assert_eq!(vec, [1]);
+    assert_eq!(vec.capacity(), 8);
+
+    // No reallocation is needed if there is no spare capacity.
+    let mut vec = try_with_exact_capacity_in(1, NoShrinkAllocator).unwrap();
+    vec.try_push(1u32).unwrap();
+    assert_eq!(*try_into_boxed_slice(vec).unwrap(), [1]);
+}
+
+#[test]
+fn test_try_map() {
+    // Same layout: the allocation is reused.
+    let mut vec: Vec<u32> = try_with_capacity(10).unwrap();
+    vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+    let ptr = vec.as_ptr() as usize;
+    let mapped = try_map(vec, |x| -(x as i32)).unwrap();
+    assert_eq!(mapped, [-1, -2, -3]);
+    assert_eq!(mapped.as_ptr() as usize, ptr);
+    assert_eq!(mapped.capacity(), 10);
+
+    // Different layout: a new allocation of exactly the length is made.
+    let mapped = try_map(mapped, |x| (x, x)).unwrap();
+    assert_eq!(mapped, [(-1, -1), (-2, -2), (-3, -3)]);
+    assert_eq!(mapped.capacity(), 3);
+
+    let mapped = try_map(mapped, |_| ()).unwrap();
+    assert_eq!(mapped.len(), 3);
+    let mapped = try_map(mapped, |_| 1u8).unwrap();
+    assert_eq!(mapped, [1, 1, 1]);
+
+    // If the allocation fails, then the original items are dropped.
+    let drop_counter = AtomicI32::new(0);
+    let mut vec = Vec::new_in(LimitedAllocator { limit: 64 });
+    for _ in 0..3 {
+        vec.try_push(ExplodingCloner {
+            clone_panics: Default::default(),
+            drop_counter: Some(&drop_counter),
+        })
+        .unwrap();
+    }
+    try_map(vec, |_| [0u64; 32]).unwrap_err();
+    assert_eq!(drop_counter.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn test_try_map_in_place() {
+    let vec = try_vec_in![1u32, 2, 3 => System].unwrap();
+    let ptr = vec.as_ptr() as usize;
+    let mapped = try_map_in_place(vec, f32::from_bits);
+    assert_eq!(mapped.as_ptr() as usize, ptr);
+    let mapped = try_map_in_place(mapped, f32::to_bits);
+    assert_eq!(mapped, [1, 2, 3]);
+
+    // If mapping panics, then every item is dropped exactly once.
+    let drop_counter = AtomicI32::new(0);
+    let mut vec = Vec::new();
+    for _ in 0..5 {
+        vec.try_push(ExplodingCloner {
+            clone_panics: Default::default(),
+            drop_counter: Some(&drop_counter),
+        })
+        .unwrap();
+    }
+    let mut mapped_count = 0;
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        try_map_in_place(vec, |item| {
+            mapped_count += 1;
+            if mapped_count == 3 {
+                panic!("BOOM");
+            }
+            item
+        })
+    }))
+    .is_err());
+    assert_eq!(drop_counter.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn test_try_insert_sorted() {
+    let mut vec = Vec::new();
+    for item in [5, 1, 4, 1, 3] {
+        vec.try_insert_sorted(item).unwrap();
+    }
+    assert_eq!(vec, [1, 1, 3, 4, 5]);
+
+    // Equal items are inserted after the existing ones.
+    let mut vec = try_vec![(1, 'a'), (2, 'a'), (3, 'a')].unwrap();
+    assert_eq!(
+        vec.try_insert_sorted_by_key((2, 'b'), |(key, _)| *key)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        vec.try_insert_sorted_by((1, 'b'), |a, b| a.0.cmp(&b.0))
+            .unwrap(),
+        1
+    );
+    assert_eq!(vec, [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b'), (3, 'a')]);
+
+    // If reserving fails, then the `Vec` is unchanged.
+    let mut vec = try_with_exact_capacity_in(2, LimitedAllocator { limit: 8 }).unwrap();
+    vec.try_extend_from_slice(&[1u32, 3]).unwrap();
+    assert!(vec.try_insert_sorted(2).is_err());
+    assert_eq!(vec, [1, 3]);
+}
+
+#[test]
+fn test_checked_insert() {
+    let mut vec = try_vec![1, 2].unwrap();
+    vec.checked_insert(2, 3).unwrap();
+    vec.checked_insert(0, 0).unwrap();
+    assert_eq!(vec, [0, 1, 2, 3]);
+    assert_eq!(
+        vec.checked_insert(5, 4),
+        Err(FallibleVecError::IndexOutOfBounds { index: 5, len: 4 })
+    );
+    assert_eq!(vec, [0, 1, 2, 3]);
+
+    let mut vec = try_with_exact_capacity_in(2, LimitedAllocator { limit: 8 }).unwrap();
+    vec.try_extend_from_slice(&[1u32, 3]).unwrap();
+    assert!(matches!(
+        vec.checked_insert(1, 2),
+        Err(FallibleVecError::Alloc(_))
+    ));
+    assert_eq!(vec, [1, 3]);
+}
+
+#[test]
+fn test_try_push_or_return() {
+    let mut vec = try_with_exact_capacity_in(1, LimitedAllocator { limit: 16 }).unwrap();
+    vec.try_push_or_return(Rc::new(1)).unwrap();
+    let item = Rc::new(2);
+    let (returned, _) = vec.try_push_or_return(Rc::clone(&item)).unwrap_err();
+    assert!(Rc::ptr_eq(&returned, &item));
+    assert_eq!(vec, [Rc::new(1)]);
+}
+
+#[test]
+fn test_try_insert_or_return() {
+    let mut vec = try_with_exact_capacity_in(2, LimitedAllocator { limit: 16 }).unwrap();
+    vec.try_insert_or_return(0, Rc::new(2)).unwrap();
+    vec.try_insert_or_return(0, Rc::new(1)).unwrap();
+    let item = Rc::new(3);
+    let (returned, _) = vec.try_insert_or_return(1, Rc::clone(&item)).unwrap_err();
+    assert!(Rc::ptr_eq(&returned, &item));
+    assert_eq!(vec, [Rc::new(1), Rc::new(2)]);
+}
+
+#[test]
+#[should_panic]
+fn test_try_insert_or_return_out_of_bounds() {
+    let mut vec = try_vec![1, 2].unwrap();
+    let _ = vec.try_insert_or_return(3, 3);
+}
+
+#[test]
+fn test_try_extend_resumable() {
+    let mut vec = Vec::new();
+    vec.try_extend_resumable([1, 2, 3]).unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+
+    // Extend until the allocator's limit is reached, then resume.
+    let mut vec = Vec::new_in(LimitedAllocator { limit: 16 });
+    let error = vec.try_extend_resumable(1u32..=6).unwrap_err();
+    assert_eq!(error.appended, 4);
+    assert_eq!(error.item, 5);
+    assert_eq!(vec, [1, 2, 3, 4]);
+    let mut rest = Vec::new();
+    rest.try_extend(error.into_remaining()).unwrap();
+    assert_eq!(rest, [5, 6]);
+}
+
+#[test]
+fn test_try_extend_resumable_unreservable_lower_bound() {
+    // The iterator claims more items than can be reserved, but each item
+    // still fits when reserved one at a time.
+    let mut vec = Vec::new_in(LimitedAllocator { limit: 16 });
+    assert!(vec
+        .try_extend_resumable(ShortIterator(1u32..=3, 100))
+        .is_ok());
+    assert_eq!(vec, [1, 2, 3]);
+
+    let mut vec = Vec::new();
+    assert!(vec
+        .try_extend_resumable(ShortIterator(1u32..=3, usize::MAX))
+        .is_ok());
+    assert_eq!(vec, [1, 2, 3]);
+}
+
+#[test]
+fn test_push_no_grow() {
+    let mut vec: Vec<u32> = Vec::new();
+    assert_eq!(vec.push_no_grow(1), Err(1));
+    assert_eq!(vec.capacity(), 0);
+
+    vec.try_reserve_exact(2).unwrap();
+    assert_eq!(vec.push_no_grow(1), Ok(()));
+    assert_eq!(vec.push_no_grow(2), Ok(()));
+    assert_eq!(vec.push_no_grow(3), Err(3));
+    assert_eq!(vec, [1, 2]);
+    assert_eq!(vec.capacity(), 2);
+
+    // Zero-sized types never run out of capacity.
+    let mut vec = Vec::new();
+    assert_eq!(vec.push_no_grow(()), Ok(()));
+}
+
+#[test]
+fn test_insert_within_capacity() {
+    let mut vec: Vec<u32> = try_with_exact_capacity(4).unwrap();
+    vec.insert_within_capacity(0, 2).unwrap();
+    vec.insert_within_capacity(0, 1).unwrap();
+    vec.insert_within_capacity(2, 4).unwrap();
+    vec.insert_within_capacity(2, 3).unwrap();
+    assert_eq!(vec.insert_within_capacity(1, 5), Err(5));
+    assert_eq!(vec, [1, 2, 3, 4]);
+    assert_eq!(vec.capacity(), 4);
+}
+
+#[test]
+#[should_panic]
+fn test_insert_within_capacity_out_of_bounds() {
+    let mut vec: Vec<u32> = try_with_exact_capacity(4).unwrap();
+    let _ = vec.insert_within_capacity(1, 1);
+}
+
+#[test]
+fn test_try_extend_from_copy_slice() {
+    let mut v = try_vec_in![1u32, 2 => LimitedAllocator { limit: 16 }].unwrap();
+    v.try_extend_from_copy_slice(&[]).unwrap();
+    v.try_extend_from_copy_slice(&[3, 4]).unwrap();
+    assert_eq!(v, [1, 2, 3, 4]);
+
+    // Reservation failure leaves the vec unchanged.
+    assert!(v.try_extend_from_copy_slice(&[5]).is_err());
+    assert_eq!(v, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_try_extend_zeroed() {
+    let mut v = try_vec_in![1u8, 2 => LimitedAllocator { limit: 8 }].unwrap();
+    v.try_extend_zeroed(0).unwrap();
+    v.try_extend_zeroed(3).unwrap();
+    assert_eq!(v, [1, 2, 0, 0, 0]);
+
+    assert!(v.try_extend_zeroed(4).is_err());
+    assert!(v.try_extend_zeroed(usize::MAX).is_err());
+    assert_eq!(v, [1, 2, 0, 0, 0]);
+}
+
+#[test]
+fn test_try_concat_vecs() {
+    let empty: Vec<Vec<u32>> = Vec::new();
+    assert!(try_concat_vecs(empty).unwrap().is_empty());
+
+    // The largest allocation is reused.
+    let mut middle = Vec::new();
+    middle.try_reserve_exact(16).unwrap();
+    middle.try_extend_from_slice(&[3, 4]).unwrap();
+    let ptr = middle.as_ptr();
+    let vecs = try_vec![
+        try_vec![1, 2].unwrap(),
+        middle,
+        Vec::new(),
+        try_vec![5].unwrap()
+    ]
+    .unwrap();
+    let flat = try_concat_vecs(vecs).unwrap();
+    assert_eq!(flat, [1, 2, 3, 4, 5]);
+    assert_eq!(flat.as_ptr(), ptr);
+
+    // If the total length overflows, then nothing is changed.
+    let mut zsts = Vec::<()>::new();
+    zsts.try_extend_zeroed(usize::MAX).unwrap();
+    let vecs = try_vec![zsts, try_vec![()].unwrap()].unwrap();
+    let (vecs, _) = try_concat_vecs(vecs).unwrap_err();
+    assert_eq!(vecs[0].len(), usize::MAX);
+    assert_eq!(vecs[1].len(), 1);
+}
+
+#[test]
+fn test_try_into_chunks() {
+    let chunks = Vec::<u32>::new().try_into_chunks(3).unwrap();
+    assert!(chunks.is_empty());
+
+    let chunks = try_vec![1, 2, 3, 4, 5, 6]
+        .unwrap()
+        .try_into_chunks(3)
+        .unwrap();
+    assert_eq!(chunks, [[1, 2, 3], [4, 5, 6]]);
+    assert!(chunks.iter().all(|chunk| chunk.capacity() == 3));
+
+    // There are too many chunks to allocate, so the `Vec` is returned.
+    let mut zsts = Vec::<()>::new();
+    zsts.try_extend_zeroed(usize::MAX).unwrap();
+    let (zsts, _) = zsts.try_into_chunks(1).unwrap_err();
+    assert_eq!(zsts.len(), usize::MAX);
+
+    // Allocating the chunks fails.
+    let alloc = LimitedAllocator { limit: 20 };
+    let vec = try_vec_in![1u32, 2, 3, 4, 5 => &alloc].unwrap();
+    let (vec, _) = vec.try_into_chunks(5).unwrap_err();
+    assert_eq!(vec, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_try_assign() {
+    let mut v = try_vec_in![1u32, 2, 3, 4 => LimitedAllocator { limit: 16 }].unwrap();
+    let ptr = v.as_ptr();
+    v.try_assign([5, 6]).unwrap();
+    assert_eq!(v, [5, 6]);
+    assert_eq!(v.as_ptr(), ptr);
+    v.try_assign(None).unwrap();
+    assert!(v.is_empty());
+
+    // Too many items to fit in the allocation.
+    assert!(v.try_assign(0..5).is_err());
+}
+
+#[test]
+fn test_try_chunk_by_collect() {
+    let empty: [u32; 0] = [];
+    assert!(empty
+        .try_chunk_by_collect(|a, b| a == b)
+        .unwrap()
+        .is_empty());
+
+    let runs = [1, 2, 3, 2, 3, 4, 5]
+        .try_chunk_by_collect(|a, b| a < b)
+        .unwrap();
+    assert_eq!(runs, [&[1, 2, 3][..], &[2, 3, 4, 5]]);
+    assert!(runs.iter().all(|run| run.capacity() == run.len()));
+
+    let runs = ["a"].try_chunk_by_collect(|_, _| false).unwrap();
+    assert_eq!(runs, [["a"]]);
+}
+
+#[test]
+fn test_try_extract_if_collect() {
+    let mut v = try_vec![1, 2, 3, 4, 5].unwrap();
+    let none = v.try_extract_if_collect(|_| false).unwrap();
+    assert!(none.is_empty());
+    assert_eq!(v, [1, 2, 3, 4, 5]);
+    let odds = v.try_extract_if_collect(|x| x % 2 == 1).unwrap();
+    assert_eq!(odds, [1, 3, 5]);
+    assert_eq!(v, [2, 4]);
+
+    // The predicate changing its mind can't remove more than was counted.
+    let mut calls = 0;
+    let extracted = v
+        .try_extract_if_collect(|_| {
+            calls += 1;
+            calls > 1
+        })
+        .unwrap();
+    assert_eq!(extracted, [2]);
+    assert_eq!(v, [4]);
+}
+
+#[test]
+fn test_try_resize_with_index() {
+    let mut v = try_vec_in![10usize, 11 => LimitedAllocator { limit: 64 }].unwrap();
+    v.try_resize_with_index(5, |i| i).unwrap();
+    assert_eq!(v, [10, 11, 2, 3, 4]);
+    v.try_resize_with_index(1, |_| unreachable!()).unwrap();
+    assert_eq!(v, [10]);
+
+    assert!(v.try_resize_with_index(64, |i| i).is_err());
+    assert_eq!(v, [10]);
+}
+
+#[test]
+fn test_try_extend_from_iter_exact() {
+    let mut v = try_vec_in![1u32 => LimitedAllocator { limit: 16 }].unwrap();
+    v.try_extend_from_iter_exact(2..4).unwrap();
+    assert_eq!(v, [1, 2, 3]);
+
+    // Iterators that lie about their length are handled safely.
+    v.try_extend_from_iter_exact(ShortIterator(4..10, 1))
+        .unwrap();
+    assert_eq!(v, [1, 2, 3, 4]);
+
+    assert!(v.try_extend_from_iter_exact(0..1).is_err());
+    assert_eq!(v, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_try_reserve_in_increments() {
+    let mut v: Vec<u8, _> = Vec::new_in(LimitedAllocator { limit: 100 });
+    v.try_reserve_in_increments(10, 4).unwrap();
+    assert!(v.capacity() >= 10);
+    v.try_extend_from_slice(&[1, 2, 3]).unwrap();
+
+    // Already reserved.
+    let capacity = v.capacity();
+    v.try_reserve_in_increments(capacity - 3, 1).unwrap();
+    assert_eq!(v.capacity(), capacity);
+
+    // Some, but not all, of the space is obtained.
+    let (available, _) = v.try_reserve_in_increments(200, 16).unwrap_err();
+    assert_eq!(v.capacity() - v.len(), available);
+    assert!(available >= 80);
+    assert_eq!(v, [1, 2, 3]);
+}
+
+#[test]
+fn test_try_to_vec() {
+    let values = [Rc::new(1), Rc::new(2)];
+    let vec = values.try_to_vec().unwrap();
+    assert_eq!(vec, values);
+    assert_eq!(vec.capacity(), 2);
+    assert_eq!(Rc::strong_count(&values[0]), 2);
+
+    let bytes = [1u8, 2, 3];
+    let vec = bytes.try_copy_to_vec().unwrap();
+    assert_eq!(vec, bytes);
+    assert_eq!(vec.capacity(), 3);
+}
+
+#[test]
+fn test_try_to_vec_in() {
+    let values = [Rc::new(1), Rc::new(2)];
+    let vec = values
+        .try_to_vec_in(LimitedAllocator { limit: 16 })
+        .unwrap();
+    assert_eq!(vec, values);
+    assert_eq!(vec.capacity(), 2);
+
+    let vec = values[..0]
+        .try_to_vec_in(LimitedAllocator { limit: 0 })
+        .unwrap();
+    assert!(vec.is_empty());
+    assert!(values.try_to_vec_in(LimitedAllocator { limit: 8 }).is_err());
+}
+
+#[test]
+fn test_try_repeat() {
+    assert!([1u8, 2].try_repeat(0).unwrap().is_empty());
+    assert!(<[u8]>::try_repeat(&[], 5).unwrap().is_empty());
+    for n in 1..10 {
+        let repeated = [1u16, 2, 3].try_repeat(n).unwrap();
+        assert_eq!(repeated.len(), 3 * n);
+        assert_eq!(repeated.capacity(), 3 * n);
+        assert!(repeated.chunks(3).all(|chunk| chunk == [1, 2, 3]));
+    }
+    assert!([1u8, 2].try_repeat(usize::MAX).is_err());
+
+    let repeated = [7u32]
+        .try_repeat_in(4, LimitedAllocator { limit: 16 })
+        .unwrap();
+    assert_eq!(repeated, [7, 7, 7, 7]);
+    assert!([7u32]
+        .try_repeat_in(5, LimitedAllocator { limit: 16 })
+        .is_err());
+}
+
+#[test]
+fn test_try_concat() {
+    let empty: [&[u32]; 0] = [];
+    assert!(try_concat(&empty).unwrap().is_empty());
+    let joined = try_concat(&[&[1, 2][..], &[], &[3]]).unwrap();
+    assert_eq!(joined, [1, 2, 3]);
+
+    let joined = try_concat_in(&[[1u32, 2], [3, 4]], LimitedAllocator { limit: 16 }).unwrap();
+    assert_eq!(joined, [1, 2, 3, 4]);
+    assert!(try_concat_in(&[[1u32, 2], [3, 4], [5, 6]], LimitedAllocator { limit: 16 }).is_err());
+}
+
+#[test]
+fn test_try_join() {
+    let empty: [&[u32]; 0] = [];
+    assert!(try_join(&empty, &[0]).unwrap().is_empty());
+    assert_eq!(try_join(&[[1u32]], &[0]).unwrap(), [1]);
+    let joined = try_join(&[&[1u32, 2][..], &[], &[3]], &[0]).unwrap();
+    assert_eq!(joined, [1, 2, 0, 0, 3]);
+    assert_eq!(joined.capacity(), 5);
+
+    let joined = try_join_in(&[[1u32], [2]], &[0, 0], LimitedAllocator { limit: 16 }).unwrap();
+    assert_eq!(joined, [1, 0, 0, 2]);
+    assert!(try_join_in(&[[1u32], [2], [3]], &[0], LimitedAllocator { limit: 16 }).is_err());
+}
+
+#[test]
+fn test_try_to_owned() {
+    let owned = [Rc::new(1)][..].try_to_owned().unwrap();
+    assert_eq!(owned, [Rc::new(1)]);
+    assert_eq!(owned.capacity(), 1);
+
+    let owned = "héllo".try_to_owned().unwrap();
+    assert_eq!(owned, "héllo");
+
+    let bytes = [b'a', b'b', 0];
+    let c_str = std::ffi::CStr::from_bytes_with_nul(&bytes).unwrap();
+    let owned = c_str.try_to_owned().unwrap();
+    assert_eq!(owned.as_c_str(), c_str);
+}
+
+#[test]
+fn test_try_collect_array_chunks() {
+    let (chunks, leftovers) = (0..6).try_collect_array_chunks::<2>().unwrap();
+    assert_eq!(chunks, [[0, 1], [2, 3], [4, 5]]);
+    assert!(leftovers.is_empty());
+
+    let (chunks, leftovers) = [Rc::new(1), Rc::new(2)]
+        .try_collect_array_chunks::<4>()
+        .unwrap();
+    assert!(chunks.is_empty());
+    assert_eq!(leftovers, [Rc::new(1), Rc::new(2)]);
+
+    // An empty iterator doesn't allocate.
+    let (chunks, leftovers) = (0..0).try_collect_array_chunks::<2>().unwrap();
+    assert_eq!(chunks.capacity(), 0);
+    assert_eq!(leftovers.capacity(), 0);
+}
+
+#[test]
+fn test_try_boxed_slice_from() {
+    let boxed = try_boxed_slice_from(&[Rc::new(1), Rc::new(2)]).unwrap();
+    assert_eq!(*boxed, [Rc::new(1), Rc::new(2)]);
+    assert!(try_boxed_slice_from::<u32>(&[]).unwrap().is_empty());
+
+    let boxed = try_boxed_slice_from_in(&[1u32, 2], LimitedAllocator { limit: 8 }).unwrap();
+    assert_eq!(*boxed, [1, 2]);
+    assert!(try_boxed_slice_from_in(&[1u32, 2, 3], LimitedAllocator { limit: 8 }).is_err());
+}
+
+#[test]
+fn test_try_to_ascii_case_vec() {
+    let header = b"X-Request-Id: \xc3\xa9A1";
+    assert_eq!(
+        header.try_to_ascii_uppercase_vec().unwrap(),
+        b"X-REQUEST-ID: \xc3\xa9A1"
+    );
+    assert_eq!(
+        header.try_to_ascii_lowercase_vec().unwrap(),
+        b"x-request-id: \xc3\xa9a1"
+    );
+    assert!(b"".try_to_ascii_lowercase_vec().unwrap().is_empty());
+}
+
+#[test]
+fn test_try_split_collect() {
+    let empty: [u32; 0] = [];
+    let pieces = empty.try_split_collect(|_| true).unwrap();
+    assert_eq!(pieces, [[]]);
+
+    let pieces = [0, 1, 0, 0, 2, 3, 0]
+        .try_split_collect(|&x| x == 0)
+        .unwrap();
+    assert_eq!(pieces, [&[][..], &[1], &[], &[2, 3], &[]]);
+    assert!(pieces.iter().all(|piece| piece.capacity() == piece.len()));
+}
+
+#[test]
+fn test_try_sorted_copy() {
+    let values = pseudo_random_values(100);
+    let mut expected = values.clone();
+    expected.sort();
+    assert_eq!(values.try_sorted_copy().unwrap(), expected);
+    expected.reverse();
+    assert_eq!(
+        values.try_sorted_copy_by(|a, b| b.cmp(a)).unwrap(),
+        expected
+    );
+
+    // The sort is stable.
+    let pairs = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+    let sorted = pairs.try_sorted_copy_by_key(|pair| pair.0).unwrap();
+    assert_eq!(sorted, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    assert_eq!(pairs[0], (2, 'a'));
+}
@@ -0,0 +1,120 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{alloc_error, error::capacity_overflow, TryReserveError};
+use alloc::{alloc::Layout, vec::Vec};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Types for which a value with every byte set to zero is valid.
+///
+/// This allows vectors of these types to be created (or grown) from zeroed
+/// memory, rather than by writing each item individually.
+///
+/// # Safety
+///
+/// Implementors must guarantee that the all-zero bit pattern is a valid value
+/// of the type.
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($t:ty),*) => {
+        $(unsafe impl Zeroable for $t {})*
+    };
+}
+
+impl_zeroable! {
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ()
+}
+
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+
+/// Creates a `Vec<T, A>` of `n` zeroed items (i.e., with both its length and
+/// capacity equal to `n`), using the provided allocator.
+///
+/// The memory is requested with [`Allocator::allocate_zeroed`], so allocators
+/// that can provide memory that is already zeroed (e.g., fresh pages from the
+/// operating system) avoid writing the zeros at all.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+/// use std::alloc::System;
+///
+/// let buffer = try_vec_zeroed_in::<u8, _>(4096, System)?;
+/// assert_eq!(buffer.len(), 4096);
+/// assert!(buffer.iter().all(|&b| b == 0));
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+#[cfg(feature = "allocator_api")]
+pub fn try_vec_zeroed_in<T: Zeroable, A: Allocator>(
+    n: usize,
+    alloc: A,
+) -> Result<Vec<T, A>, TryReserveError> {
+    let layout = Layout::array::<T>(n).map_err(|_| capacity_overflow())?;
+    if layout.size() == 0 {
+        let mut vec = Vec::new_in(alloc);
+        // SAFETY: Either `n` is 0 or `T` is zero-sized, and all-zero is a
+        // valid `T`.
+        unsafe { vec.set_len(n) };
+        return Ok(vec);
+    }
+    let ptr = alloc
+        .allocate_zeroed(layout)
+        .map_err(|_| alloc_error(layout))?;
+    // SAFETY: The allocation has the layout of `n` items, which are all
+    // zeroed, and all-zero is a valid `T`.
+    Ok(unsafe { Vec::from_raw_parts_in(ptr.as_ptr().cast(), n, n, alloc) })
+}
+
+/// Creates a `Vec<T>` of `n` zeroed items (i.e., with both its length and
+/// capacity equal to `n`).
+///
+/// The memory is requested with [`alloc_zeroed`](alloc::alloc::alloc_zeroed),
+/// so allocators that can provide memory that is already zeroed (e.g., fresh
+/// pages from the operating system) avoid writing the zeros at all. This is
+/// the fallible equivalent of `vec![0u8; n]`.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// use fallible_vec::*;
+///
+/// let buffer = try_vec_zeroed::<u8>(4096)?;
+/// assert_eq!(buffer.len(), 4096);
+/// assert_eq!(buffer.capacity(), 4096);
+/// assert!(buffer.iter().all(|&b| b == 0));
+/// # Ok::<(), std::collections::TryReserveError>(())
+/// ```
+// Clippy can't tell that `set_len` is only called for zero-sized values.
+#[cfg_attr(not(feature = "allocator_api"), allow(clippy::uninit_vec))]
+pub fn try_vec_zeroed<T: Zeroable>(n: usize) -> Result<Vec<T>, TryReserveError> {
+    #[cfg(feature = "allocator_api")]
+    {
+        try_vec_zeroed_in(n, alloc::alloc::Global)
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    {
+        let layout = Layout::array::<T>(n).map_err(|_| capacity_overflow())?;
+        if layout.size() == 0 {
+            let mut vec = Vec::new();
+            // SAFETY: Either `n` is 0 or `T` is zero-sized, and all-zero is a
+            // valid `T`.
+            unsafe { vec.set_len(n) };
+            return Ok(vec);
+        }
+        // SAFETY: The layout has a non-zero size.
+        let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return Err(alloc_error(layout));
+        }
+        // SAFETY: The allocation has the layout of `n` items, which are all
+        // zeroed, and all-zero is a valid `T`.
+        Ok(unsafe { Vec::from_raw_parts(ptr.cast(), n, n) })
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use crate::{error::capacity_overflow, CloneAllocator, FallibleVec, TryReserveError};
+use alloc::vec::Vec;
+use core::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+macro_rules! struct_zeroizing_vec {
+    { $(#[doc = $doc:expr])+ pub struct ZeroizingVec $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        $(#[doc = $doc])+
+        pub struct ZeroizingVec<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        $(#[doc = $doc])+
+        pub struct ZeroizingVec<T, A: Allocator = Global> $impl
+    }
+}
+
+struct_zeroizing_vec! {
+    /// A growable array that wipes its memory before releasing it.
+    ///
+    /// Every byte of a buffer owned by a `ZeroizingVec` is overwritten with
+    /// zeros before the buffer is freed (when the `ZeroizingVec` is dropped,
+    /// or when it is replaced by a larger or smaller buffer), and the memory
+    /// of elements that are removed is wiped as they are removed. This makes
+    /// it suitable for holding sensitive data, such as key material.
+    ///
+    /// Like the rest of this crate, all allocations are fallible. Growth
+    /// never uses the allocator's `realloc`/`grow` operation (which may free
+    /// the old buffer without wiping it): instead a new buffer is allocated,
+    /// the elements are moved into it, and then the old buffer is wiped and
+    /// freed.
+    ///
+    /// The elements are accessible as a slice via `Deref`, but the underlying
+    /// `Vec` is never exposed, so it cannot be grown behind the wrapper's back.
+    ///
+    /// NOTE: Wiping is best-effort: it cannot remove copies made by the
+    /// elements themselves (e.g., via `Clone`) or by moves of the
+    /// `ZeroizingVec` value itself, which only copy its pointer and lengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// use fallible_vec::*;
+    ///
+    /// let mut key = ZeroizingVec::new();
+    /// key.try_extend_from_slice(&[0x2b, 0x7e, 0x15, 0x16])?;
+    /// key.try_push(0x28)?;
+    /// assert_eq!(*key, [0x2b, 0x7e, 0x15, 0x16, 0x28]);
+    /// key.truncate(2);
+    /// assert_eq!(*key, [0x2b, 0x7e]);
+    /// # Ok::<(), std::collections::TryReserveError>(())
+    /// ```
+    pub struct ZeroizingVec {
+        #[cfg(not(feature = "allocator_api"))]
+        vec: Vec<T>,
+        #[cfg(feature = "allocator_api")]
+        vec: Vec<T, A>,
+    }
+}
+
+macro_rules! impl_zeroizing_vec {
+    { impl ZeroizingVec $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> ZeroizingVec<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> ZeroizingVec<T, A> $impl
+    };
+    { impl ZeroizingVec where A: Clone $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> ZeroizingVec<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator + Clone> ZeroizingVec<T, A> $impl
+    };
+    { impl $trait:ident for ZeroizingVec $impl:tt } => {
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> $trait for ZeroizingVec<T> $impl
+
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> $trait for ZeroizingVec<T, A> $impl
+    };
+}
+
+impl<T> ZeroizingVec<T> {
+    /// Creates a new, empty `ZeroizingVec`.
+    ///
+    /// This does not allocate.
+    pub const fn new() -> Self {
+        ZeroizingVec { vec: Vec::new() }
+    }
+
+    /// Creates a new, empty `ZeroizingVec` with at least the specified
+    /// capacity.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        let mut vec = Self::new();
+        vec.try_reserve(capacity)?;
+        Ok(vec)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> ZeroizingVec<T, A> {
+    /// Creates a new, empty `ZeroizingVec` that will use the provided
+    /// allocator.
+    ///
+    /// This does not allocate.
+    pub fn new_in(alloc: A) -> Self {
+        ZeroizingVec {
+            vec: Vec::new_in(alloc),
+        }
+    }
+}
+
+impl<T> Default for ZeroizingVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_zeroizing_vec! {
+    impl ZeroizingVec {
+        /// Returns the number of elements in the `ZeroizingVec`.
+        pub fn len(&self) -> usize {
+            self.vec.len()
+        }
+
+        /// Returns `true` if the `ZeroizingVec` contains no elements.
+        pub fn is_empty(&self) -> bool {
+            self.vec.is_empty()
+        }
+
+        /// Returns the number of elements the `ZeroizingVec` can hold without
+        /// reallocating.
+        pub fn capacity(&self) -> usize {
+            self.vec.capacity()
+        }
+
+        /// Shortens the `ZeroizingVec` to `len` elements, dropping the rest and
+        /// wiping the memory they occupied.
+        ///
+        /// Has no effect if `len` is greater than or equal to the current
+        /// length.
+        pub fn truncate(&mut self, len: usize) {
+            let old_len = self.vec.len();
+            if len < old_len {
+                self.vec.truncate(len);
+                unsafe {
+                    wipe(self.vec.as_mut_ptr().add(len), old_len - len);
+                }
+            }
+        }
+
+        /// Removes all elements, wiping the memory they occupied.
+        pub fn clear(&mut self) {
+            self.truncate(0);
+        }
+
+        /// Removes the last element and returns it, or `None` if the
+        /// `ZeroizingVec` is empty. The memory it occupied is wiped.
+        pub fn pop(&mut self) -> Option<T> {
+            let len = self.vec.len().checked_sub(1)?;
+            unsafe {
+                self.vec.set_len(len);
+                let slot = self.vec.as_mut_ptr().add(len);
+                let item = slot.read();
+                wipe(slot, 1);
+                Some(item)
+            }
+        }
+    }
+}
+
+impl_zeroizing_vec! {
+    impl ZeroizingVec where A: Clone {
+        /// Reserves capacity for at least `additional` more elements.
+        ///
+        /// If the `ZeroizingVec` needs to grow, then a new buffer is allocated
+        /// and the old buffer is wiped and freed. If allocating fails, then the
+        /// `ZeroizingVec` is unchanged.
+        pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+            let len = self.vec.len();
+            let capacity = self.vec.capacity();
+            if capacity - len >= additional {
+                return Ok(());
+            }
+
+            let required = len.checked_add(additional).ok_or_else(capacity_overflow)?;
+            // Try to grow geometrically (to amortize the cost of moving
+            // elements), but fall back to exactly what is required.
+            let preferred = required.max(capacity.saturating_mul(2)).max(4);
+            if preferred > required && self.try_reallocate(preferred).is_ok() {
+                return Ok(());
+            }
+            self.try_reallocate(required)
+        }
+
+        /// Shrinks the capacity of the `ZeroizingVec` to its length, wiping
+        /// and freeing the old buffer.
+        ///
+        /// If allocating the smaller buffer fails, then the `ZeroizingVec` is
+        /// unchanged.
+        pub fn try_shrink_to_fit(&mut self) -> Result<(), TryReserveError> {
+            if core::mem::size_of::<T>() == 0 || self.vec.capacity() == self.vec.len() {
+                return Ok(());
+            }
+            self.try_reallocate(self.vec.len())
+        }
+
+        /// Appends an element to the back of the `ZeroizingVec`.
+        pub fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+            self.try_reserve(1)?;
+            // Cannot fail (or reallocate) as the space has been reserved.
+            self.vec.try_push(item)
+        }
+
+        /// Clones and appends all elements in `slice` to the `ZeroizingVec`.
+        ///
+        /// # Panic safety
+        ///
+        /// If a call to `clone` panics, then all items before the panicking
+        /// item will have been added to the `ZeroizingVec`.
+        pub fn try_extend_from_slice(&mut self, slice: &[T]) -> Result<(), TryReserveError>
+        where
+            T: Clone,
+        {
+            self.try_reserve(slice.len())?;
+            // Cannot fail (or reallocate) as the space has been reserved.
+            self.vec.try_extend_from_slice(slice)
+        }
+
+        /// Moves the elements into a new buffer with exactly `capacity`
+        /// elements, then wipes and frees the old buffer.
+        fn try_reallocate(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+            let len = self.vec.len();
+            let mut new_vec = self.vec.new_with_cloned_allocator();
+            new_vec.try_reserve_exact(capacity)?;
+            unsafe {
+                core::ptr::copy_nonoverlapping(self.vec.as_ptr(), new_vec.as_mut_ptr(), len);
+                self.vec.set_len(0);
+                new_vec.set_len(len);
+            }
+            let mut old_vec = core::mem::replace(&mut self.vec, new_vec);
+            unsafe {
+                wipe(old_vec.as_mut_ptr(), old_vec.capacity());
+            }
+            Ok(())
+        }
+    }
+}
+
+impl_zeroizing_vec! {
+    impl Drop for ZeroizingVec {
+        fn drop(&mut self) {
+            self.vec.clear();
+            unsafe {
+                wipe(self.vec.as_mut_ptr(), self.vec.capacity());
+            }
+        }
+    }
+}
+
+impl_zeroizing_vec! {
+    impl Deref for ZeroizingVec {
+        type Target = [T];
+
+        fn deref(&self) -> &[T] {
+            &self.vec
+        }
+    }
+}
+
+impl_zeroizing_vec! {
+    impl DerefMut for ZeroizingVec {
+        fn deref_mut(&mut self) -> &mut [T] {
+            &mut self.vec
+        }
+    }
+}
+
+/// Overwrites the memory of `count` elements starting at `ptr` with zeros, in
+/// a way that will not be optimized away.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `count` elements, and they must not
+/// contain any live values.
+unsafe fn wipe<T>(ptr: *mut T, count: usize) {
+    // Zero-sized types occupy no memory (and `count` may be `usize::MAX`).
+    if core::mem::size_of::<T>() == 0 {
+        return;
+    }
+    let bytes = ptr.cast::<u8>();
+    for i in 0..count * core::mem::size_of::<T>() {
+        unsafe {
+            core::ptr::write_volatile(bytes.add(i), 0);
+        }
+    }
+    compiler_fence(Ordering::SeqCst);
+}